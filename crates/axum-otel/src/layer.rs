@@ -0,0 +1,220 @@
+use crate::{
+    AxumOtelOnFailure, AxumOtelOnResponse, AxumOtelSpanCreator, HttpMetrics, HttpMetricsLayer,
+    TraceResponse, TraceResponseLayer,
+};
+use axum::http::HeaderName;
+use tower::Layer;
+use tower_http::{
+    classify::{ServerErrorsAsFailures, SharedClassifier},
+    request_id::{
+        MakeRequestId, MakeRequestUuid, PropagateRequestId, PropagateRequestIdLayer, SetRequestId,
+        SetRequestIdLayer,
+    },
+    trace::{DefaultOnBodyChunk, DefaultOnEos, DefaultOnRequest, Trace, TraceLayer},
+};
+use tracing::Level;
+use tracing_otel_extra::extract::{
+    fields::{QueryRedaction, X_REQUEST_ID},
+    headers::HeaderCapture,
+};
+
+/// The [`TraceLayer`] configured with the axum-otel components.
+pub type OtelTraceLayer = TraceLayer<
+    SharedClassifier<ServerErrorsAsFailures>,
+    AxumOtelSpanCreator,
+    DefaultOnRequest,
+    AxumOtelOnResponse,
+    DefaultOnBodyChunk,
+    DefaultOnEos,
+    AxumOtelOnFailure,
+>;
+
+/// The service created by [`OtelLayer`].
+pub type OtelService<S, M = MakeRequestUuid> = SetRequestId<
+    Trace<
+        HttpMetrics<TraceResponse<PropagateRequestId<S>>>,
+        SharedClassifier<ServerErrorsAsFailures>,
+        AxumOtelSpanCreator,
+        DefaultOnRequest,
+        AxumOtelOnResponse,
+        DefaultOnBodyChunk,
+        DefaultOnEos,
+        AxumOtelOnFailure,
+    >,
+    M,
+>;
+
+/// A single [`Layer`] with the whole axum-otel middleware stack.
+///
+/// It is the equivalent of the following `ServiceBuilder`, outermost first:
+///
+/// 1. [`SetRequestIdLayer`], which sets a request id when the request has none
+/// 2. A [`TraceLayer`] with [`AxumOtelSpanCreator`], [`AxumOtelOnResponse`]
+///    and [`AxumOtelOnFailure`]; the span records the request id from the
+///    header set with [`OtelLayer::request_id_header`], `x-request-id` by default
+/// 3. An [`HttpMetricsLayer`] recording on the global meter provider, which
+///    can be replaced with [`OtelLayer::metrics`]
+/// 4. A [`TraceResponseLayer`], which writes no headers unless configured with
+///    [`OtelLayer::trace_response`]
+/// 5. [`PropagateRequestIdLayer`], which copies the request id to the response
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_otel::{Level, OtelLayer};
+///
+/// let app: Router<()> = Router::new()
+///     .route("/", get(|| async { "Hello, world!" }))
+///     .layer(OtelLayer::new().level(Level::INFO));
+/// ```
+#[derive(Clone, Debug)]
+pub struct OtelLayer<M = MakeRequestUuid> {
+    span: AxumOtelSpanCreator,
+    on_response: AxumOtelOnResponse,
+    on_failure: AxumOtelOnFailure,
+    metrics: HttpMetricsLayer,
+    trace_response: TraceResponseLayer,
+    header_name: HeaderName,
+    make_request_id: M,
+}
+
+impl OtelLayer {
+    /// Create a new `OtelLayer` generating UUID request ids in `x-request-id`.
+    pub fn new() -> Self {
+        Self {
+            span: AxumOtelSpanCreator::new(),
+            on_response: AxumOtelOnResponse::new(),
+            on_failure: AxumOtelOnFailure::new(),
+            metrics: HttpMetricsLayer::new(),
+            trace_response: TraceResponseLayer::new(),
+            header_name: X_REQUEST_ID,
+            make_request_id: MakeRequestUuid,
+        }
+    }
+}
+
+impl Default for OtelLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> OtelLayer<M> {
+    /// Set the [`Level`] of the request spans and of the response events.
+    ///
+    /// See [`AxumOtelSpanCreator::level`] and [`AxumOtelOnResponse::level`].
+    pub fn level(mut self, level: Level) -> Self {
+        self.span = self.span.level(level);
+        self.on_response = self.on_response.level(level);
+        self
+    }
+
+    /// Set the [`Level`] of the request spans only.
+    ///
+    /// Defaults to [`Level::TRACE`].
+    pub fn span_level(mut self, level: Level) -> Self {
+        self.span = self.span.level(level);
+        self
+    }
+
+    /// Set the [`Level`] of the events emitted for responses.
+    ///
+    /// Defaults to [`Level::DEBUG`].
+    pub fn response_level(mut self, level: Level) -> Self {
+        self.on_response = self.on_response.level(level);
+        self
+    }
+
+    /// Set the [`Level`] of the events emitted for failures.
+    ///
+    /// Defaults to [`Level::ERROR`].
+    pub fn failure_level(mut self, level: Level) -> Self {
+        self.on_failure = self.on_failure.level(level);
+        self
+    }
+
+    /// Set the request and response headers recorded as
+    /// `http.request.header.<name>` and `http.response.header.<name>`.
+    pub fn headers(mut self, headers: HeaderCapture) -> Self {
+        self.span = self.span.headers(headers.clone());
+        self.on_response = self.on_response.headers(headers);
+        self
+    }
+
+    /// Set the query parameters whose values are redacted in `url.query`.
+    ///
+    /// See [`AxumOtelSpanCreator::query_redaction`].
+    pub fn query_redaction(mut self, query: QueryRedaction) -> Self {
+        self.span = self.span.query_redaction(query);
+        self
+    }
+
+    /// Set how HTTP server metrics are recorded.
+    ///
+    /// Defaults to [`HttpMetricsLayer::new`]; use [`HttpMetricsLayer::disabled`]
+    /// to record none.
+    pub fn metrics(mut self, metrics: HttpMetricsLayer) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Set the trace context headers written to responses.
+    ///
+    /// No headers are written by default.
+    pub fn trace_response(mut self, trace_response: TraceResponseLayer) -> Self {
+        self.trace_response = trace_response;
+        self
+    }
+
+    /// Set the header carrying the request id, which is also the header the
+    /// span's `request_id` is recorded from.
+    ///
+    /// Defaults to `x-request-id`.
+    pub fn request_id_header(mut self, header_name: HeaderName) -> Self {
+        self.span = self.span.request_id_header(header_name.clone());
+        self.header_name = header_name;
+        self
+    }
+
+    /// Set how request ids are generated for requests without one.
+    ///
+    /// Defaults to [`MakeRequestUuid`].
+    pub fn request_id_generator<M2>(self, make_request_id: M2) -> OtelLayer<M2>
+    where
+        M2: MakeRequestId,
+    {
+        OtelLayer {
+            span: self.span,
+            on_response: self.on_response,
+            on_failure: self.on_failure,
+            metrics: self.metrics,
+            trace_response: self.trace_response,
+            header_name: self.header_name,
+            make_request_id,
+        }
+    }
+
+    /// Returns the [`TraceLayer`] of the stack, without the request id layers.
+    pub fn trace_layer(&self) -> OtelTraceLayer {
+        TraceLayer::new_for_http()
+            .make_span_with(self.span.clone())
+            .on_response(self.on_response.clone())
+            .on_failure(self.on_failure)
+    }
+}
+
+impl<S, M> Layer<S> for OtelLayer<M>
+where
+    M: MakeRequestId + Clone,
+{
+    type Service = OtelService<S, M>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let inner = PropagateRequestIdLayer::new(self.header_name.clone()).layer(inner);
+        let inner = self.trace_response.layer(inner);
+        let inner = self.metrics.layer(inner);
+        let inner = self.trace_layer().layer(inner);
+        SetRequestIdLayer::new(self.header_name.clone(), self.make_request_id.clone()).layer(inner)
+    }
+}
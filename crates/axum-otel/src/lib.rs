@@ -50,11 +50,27 @@
 //!     );
 //! ```
 //!
+//! The same stack, with a request id set on requests and copied to responses,
+//! is available as a single [`OtelLayer`]:
+//!
+//! ```rust
+//! use axum::{routing::get, Router};
+//! use axum_otel::{Level, OtelLayer};
+//!
+//! let app: Router<()> = Router::new()
+//!     .route("/", get(|| async { "Hello, world!" }))
+//!     .layer(OtelLayer::new().level(Level::INFO));
+//! ```
+//!
 //! ## Components
 //!
+//! - [`OtelLayer`] - Bundles the request id layers and the trace layer below
 //! - [`AxumOtelSpanCreator`] - Creates spans for each request with relevant HTTP information
 //! - [`AxumOtelOnResponse`] - Records response status and latency
 //! - [`AxumOtelOnFailure`] - Handles error cases and updates span status
+//! - [`TraceResponseLayer`] - Writes `traceresponse`, a trace id header and/or
+//!   a `Server-Timing` entry to responses
+//! - [`HttpMetricsLayer`] - Records the OpenTelemetry HTTP server metrics
 //!
 //! ## HTTP span attributes
 //!
@@ -71,14 +87,29 @@
 //!
 //! See the [examples](https://github.com/nivek-ph/tracing-otel-extra/tree/main/examples) directory for complete examples.
 //!
+mod layer;
 mod make_span;
+mod metrics;
 mod on_failure;
 mod on_response;
+mod trace_response;
 
 // Exports for the tower-http::trace::TraceLayer based middleware
+pub use layer::{OtelLayer, OtelService, OtelTraceLayer};
 pub use make_span::AxumOtelSpanCreator;
+pub use metrics::{DURATION_BUCKETS, HttpMetrics, HttpMetricsFuture, HttpMetricsLayer};
 pub use on_failure::AxumOtelOnFailure;
 pub use on_response::AxumOtelOnResponse;
+pub use trace_response::{
+    SERVER_TIMING, TRACERESPONSE, TraceResponse, TraceResponseFuture, TraceResponseLayer,
+    X_TRACE_ID,
+};
 
 // Re-export the Level enum from tracing crate
 pub use tracing::Level;
+
+// Re-export the header allow-list shared by the span creator and response hook
+pub use tracing_otel_extra::extract::headers::HeaderCapture;
+
+// Re-export the query redaction applied to `url.query`
+pub use tracing_otel_extra::extract::fields::QueryRedaction;
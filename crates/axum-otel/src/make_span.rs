@@ -1,6 +1,6 @@
 use axum::{
     extract::{ConnectInfo, MatchedPath},
-    http,
+    http::{self, HeaderName},
 };
 use opentelemetry::trace::SpanKind;
 use std::net::SocketAddr;
@@ -8,7 +8,11 @@ use tower_http::trace::MakeSpan;
 use tracing::{Level, field::Empty};
 use tracing_otel_extra::{
     dyn_span,
-    extract::{context, fields},
+    extract::{
+        context,
+        fields::{self, QueryRedaction},
+        headers::HeaderCapture,
+    },
 };
 
 /// An implementor of [`MakeSpan`] which creates `tracing` spans populated with information about
@@ -26,11 +30,13 @@ use tracing_otel_extra::{
 /// - `network.protocol.name`: The network protocol name
 /// - `network.protocol.version`: The network protocol version
 /// - `url.path`: The request path
-/// - `url.query`: The request query string
+/// - `url.query`: The request query string, with sensitive parameters redacted (see [`AxumOtelSpanCreator::query_redaction`])
 /// - `url.scheme`: The request scheme
 /// - `user_agent.original`: The `User-Agent` header
-/// - `request_id`: A unique request identifier
+/// - `request_id`: A unique request identifier, from the header set with
+///   [`AxumOtelSpanCreator::request_id_header`], or else `x-request-id` or `request-id`
 /// - `trace_id`: The OpenTelemetry trace ID
+/// - `http.request.header.<name>`: Headers allow-listed with [`AxumOtelSpanCreator::headers`]
 ///
 /// # Example
 ///
@@ -41,9 +47,12 @@ use tracing_otel_extra::{
 /// let layer = TraceLayer::new_for_http()
 ///     .make_span_with(AxumOtelSpanCreator::new().level(Level::INFO));
 /// ```
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct AxumOtelSpanCreator {
     level: Level,
+    headers: HeaderCapture,
+    query: QueryRedaction,
+    request_id_header: Option<HeaderName>,
 }
 
 impl AxumOtelSpanCreator {
@@ -51,6 +60,9 @@ impl AxumOtelSpanCreator {
     pub fn new() -> Self {
         Self {
             level: Level::TRACE,
+            headers: HeaderCapture::default(),
+            query: QueryRedaction::default(),
+            request_id_header: None,
         }
     }
 
@@ -63,6 +75,33 @@ impl AxumOtelSpanCreator {
         self.level = level;
         self
     }
+
+    /// Set the request headers recorded as `http.request.header.<name>` attributes.
+    ///
+    /// No headers are recorded by default, and sensitive headers are masked.
+    pub fn headers(mut self, headers: HeaderCapture) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Set the query parameters whose values are redacted in `url.query`.
+    ///
+    /// Defaults to [`QueryRedaction::default`], which redacts signatures and
+    /// tokens such as `X-Amz-Signature` and `token`.
+    pub fn query_redaction(mut self, query: QueryRedaction) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// Set the header `request_id` is recorded from.
+    ///
+    /// Defaults to `x-request-id`, falling back to `request-id`.
+    /// [`OtelLayer::request_id_header`](crate::OtelLayer::request_id_header)
+    /// sets it to the header the layer reads and writes request ids in.
+    pub fn request_id_header(mut self, header_name: HeaderName) -> Self {
+        self.request_id_header = Some(header_name);
+        self
+    }
 }
 
 impl Default for AxumOtelSpanCreator {
@@ -123,11 +162,15 @@ impl<B> MakeSpan<B> for AxumOtelSpanCreator {
         if let Some(version) = fields::extract_network_protocol_version(request) {
             span.record("network.protocol.version", version);
         }
-        if let Some(request_id) = fields::extract_request_id(request) {
+        let request_id = match &self.request_id_header {
+            Some(header_name) => fields::extract_field_from_headers(request.headers(), header_name),
+            None => fields::extract_request_id(request),
+        };
+        if let Some(request_id) = request_id {
             span.record("request_id", request_id);
         }
-        if let Some(query) = fields::extract_url_query(request) {
-            span.record("url.query", query);
+        if let Some(query) = fields::extract_redacted_url_query(request, &self.query) {
+            span.record("url.query", query.as_ref());
         }
         if let Some(scheme) = fields::extract_url_scheme(request) {
             span.record("url.scheme", scheme);
@@ -137,6 +180,7 @@ impl<B> MakeSpan<B> for AxumOtelSpanCreator {
         }
 
         context::set_otel_parent(request.headers(), &span);
+        self.headers.record_request(&span, request.headers());
         span
     }
 }
@@ -0,0 +1,312 @@
+use axum::{
+    extract::MatchedPath,
+    http::{self, Method},
+};
+use http_body::Body;
+use opentelemetry::{
+    InstrumentationScope, KeyValue, global,
+    metrics::{Histogram, Meter, UpDownCounter},
+};
+use pin_project_lite::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+    time::Instant,
+};
+use tower::{Layer, Service};
+use tracing_otel_extra::extract::fields;
+
+/// Bucket boundaries of `http.server.request.duration`, in seconds, as
+/// advised by the OpenTelemetry HTTP metrics conventions.
+pub const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.075, 0.1, 0.25, 0.5, 0.75, 1.0, 2.5, 5.0, 7.5, 10.0,
+];
+
+/// A [`Layer`] which records [OpenTelemetry HTTP server metrics](https://opentelemetry.io/docs/specs/semconv/http/http-metrics/#http-server):
+///
+/// - `http.server.request.duration`: Histogram of the time until the response
+///   head is produced, in seconds
+/// - `http.server.active_requests`: Number of requests being processed
+/// - `http.server.request.body.size`: Histogram of request body sizes, in bytes
+/// - `http.server.response.body.size`: Histogram of response body sizes, in bytes
+///
+/// Body sizes are taken from `Content-Length`, or from the body when its size
+/// is known up front, and are not recorded otherwise.
+///
+/// Measurements carry `http.request.method`, `url.scheme`, `http.route`,
+/// `http.response.status_code`, `network.protocol.name`,
+/// `network.protocol.version` and, for `5xx` responses, `error.type`. To bound
+/// their cardinality, `http.route` is only the axum [`MatchedPath`] and is
+/// omitted for unmatched requests, unknown methods are recorded as `_OTHER`
+/// and schemes other than `https` as `http`.
+///
+/// Instruments are created on the global meter provider when the layer is
+/// applied, so set the provider before building the router, or pass a
+/// [`Meter`] to [`HttpMetricsLayer::meter`].
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_otel::HttpMetricsLayer;
+///
+/// let app: Router<()> = Router::new()
+///     .route("/", get(|| async { "Hello, world!" }))
+///     .layer(HttpMetricsLayer::new());
+/// ```
+#[derive(Clone, Debug)]
+pub struct HttpMetricsLayer {
+    enabled: bool,
+    meter: Option<Meter>,
+}
+
+impl Default for HttpMetricsLayer {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            meter: None,
+        }
+    }
+}
+
+impl HttpMetricsLayer {
+    /// Create a new `HttpMetricsLayer` recording on the global meter provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a layer which records nothing.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            meter: None,
+        }
+    }
+
+    /// Record on `meter` instead of the `axum-otel` meter of the global
+    /// meter provider.
+    pub fn meter(mut self, meter: Meter) -> Self {
+        self.meter = Some(meter);
+        self
+    }
+}
+
+impl<S> Layer<S> for HttpMetricsLayer {
+    type Service = HttpMetrics<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let instruments = self.enabled.then(|| {
+            let meter = self.meter.clone().unwrap_or_else(|| {
+                global::meter_with_scope(
+                    InstrumentationScope::builder(env!("CARGO_PKG_NAME"))
+                        .with_version(env!("CARGO_PKG_VERSION"))
+                        .build(),
+                )
+            });
+            Arc::new(Instruments::new(&meter))
+        });
+        HttpMetrics { inner, instruments }
+    }
+}
+
+#[derive(Debug)]
+struct Instruments {
+    duration: Histogram<f64>,
+    active_requests: UpDownCounter<i64>,
+    request_body_size: Histogram<u64>,
+    response_body_size: Histogram<u64>,
+}
+
+impl Instruments {
+    fn new(meter: &Meter) -> Self {
+        Self {
+            duration: meter
+                .f64_histogram("http.server.request.duration")
+                .with_description("Duration of HTTP server requests.")
+                .with_unit("s")
+                .with_boundaries(DURATION_BUCKETS.to_vec())
+                .build(),
+            active_requests: meter
+                .i64_up_down_counter("http.server.active_requests")
+                .with_description("Number of active HTTP server requests.")
+                .with_unit("{request}")
+                .build(),
+            request_body_size: meter
+                .u64_histogram("http.server.request.body.size")
+                .with_description("Size of HTTP server request bodies.")
+                .with_unit("By")
+                .build(),
+            response_body_size: meter
+                .u64_histogram("http.server.response.body.size")
+                .with_description("Size of HTTP server response bodies.")
+                .with_unit("By")
+                .build(),
+        }
+    }
+}
+
+/// The service created by [`HttpMetricsLayer`].
+#[derive(Clone, Debug)]
+pub struct HttpMetrics<S> {
+    inner: S,
+    instruments: Option<Arc<Instruments>>,
+}
+
+/// Returns the method as recorded in `http.request.method`.
+fn bounded_method(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::HEAD => "HEAD",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::CONNECT => "CONNECT",
+        Method::OPTIONS => "OPTIONS",
+        Method::TRACE => "TRACE",
+        Method::PATCH => "PATCH",
+        _ => "_OTHER",
+    }
+}
+
+/// Returns the body size from `Content-Length`, or from the body itself.
+fn body_size<B: Body>(headers: &http::HeaderMap, body: &B) -> Option<u64> {
+    headers
+        .get(http::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok()?.parse().ok())
+        .or_else(|| body.size_hint().exact())
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for HttpMetrics<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    ReqBody: Body,
+    ResBody: Body,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = HttpMetricsFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        let request_metrics = self.instruments.as_ref().map(|instruments| {
+            let scheme = match fields::extract_url_scheme(&request) {
+                Some("https") => "https",
+                _ => "http",
+            };
+            let active_attributes = vec![
+                KeyValue::new("http.request.method", bounded_method(request.method())),
+                KeyValue::new("url.scheme", scheme),
+            ];
+            let mut attributes = active_attributes.clone();
+            attributes.push(KeyValue::new(
+                "network.protocol.name",
+                fields::extract_network_protocol_name(&request),
+            ));
+            if let Some(version) = fields::extract_network_protocol_version(&request) {
+                attributes.push(KeyValue::new("network.protocol.version", version));
+            }
+            if let Some(route) = request.extensions().get::<MatchedPath>() {
+                attributes.push(KeyValue::new("http.route", route.as_str().to_string()));
+            }
+            instruments.active_requests.add(1, &active_attributes);
+            RequestMetrics {
+                instruments: Arc::clone(instruments),
+                start: Instant::now(),
+                request_body_size: body_size(request.headers(), request.body()),
+                attributes,
+                active_attributes,
+            }
+        });
+        HttpMetricsFuture {
+            inner: self.inner.call(request),
+            request_metrics,
+        }
+    }
+}
+
+/// The measurements of a request in progress.
+///
+/// Dropping it ends the request in `http.server.active_requests`, also when
+/// the response future is cancelled.
+#[derive(Debug)]
+struct RequestMetrics {
+    instruments: Arc<Instruments>,
+    start: Instant,
+    request_body_size: Option<u64>,
+    attributes: Vec<KeyValue>,
+    active_attributes: Vec<KeyValue>,
+}
+
+impl RequestMetrics {
+    fn record<B: Body, E>(mut self, result: &Result<http::Response<B>, E>) {
+        let mut response_body_size = None;
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                self.attributes.push(KeyValue::new(
+                    "http.response.status_code",
+                    i64::from(status.as_u16()),
+                ));
+                if status.is_server_error() {
+                    self.attributes
+                        .push(KeyValue::new("error.type", status.as_str().to_string()));
+                }
+                response_body_size = body_size(response.headers(), response.body());
+            }
+            Err(_) => self.attributes.push(KeyValue::new("error.type", "_OTHER")),
+        }
+
+        let instruments = &self.instruments;
+        instruments
+            .duration
+            .record(self.start.elapsed().as_secs_f64(), &self.attributes);
+        if let Some(size) = self.request_body_size {
+            instruments.request_body_size.record(size, &self.attributes);
+        }
+        if let Some(size) = response_body_size {
+            instruments
+                .response_body_size
+                .record(size, &self.attributes);
+        }
+    }
+}
+
+impl Drop for RequestMetrics {
+    fn drop(&mut self) {
+        self.instruments
+            .active_requests
+            .add(-1, &self.active_attributes);
+    }
+}
+
+pin_project! {
+    /// The response future of [`HttpMetrics`].
+    #[derive(Debug)]
+    pub struct HttpMetricsFuture<F> {
+        #[pin]
+        inner: F,
+        request_metrics: Option<RequestMetrics>,
+    }
+}
+
+impl<F, ResBody, E> Future for HttpMetricsFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+    ResBody: Body,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = ready!(this.inner.poll(cx));
+        if let Some(request_metrics) = this.request_metrics.take() {
+            request_metrics.record(&result);
+        }
+        Poll::Ready(result)
+    }
+}
@@ -1,6 +1,6 @@
 use tower_http::{classify::ServerErrorsFailureClass, trace::OnFailure};
 use tracing::Level;
-use tracing_otel_extra::dyn_event;
+use tracing_otel_extra::{OtelSpanExt as _, dyn_event};
 
 /// An implementor of [`OnFailure`] which records the failure status code.
 ///
@@ -63,11 +63,7 @@ impl OnFailure<ServerErrorsFailureClass> for AxumOtelOnFailure {
         );
         match failure_classification {
             ServerErrorsFailureClass::StatusCode(status) if status.is_server_error() => {
-                span.record("otel.status_code", "ERROR");
-                span.record(
-                    "otel.status_description",
-                    tracing::field::display(failure_classification),
-                );
+                span.set_error(failure_classification);
             }
             _ => {}
         }
@@ -1,7 +1,7 @@
 use axum::http;
 use tower_http::trace::OnResponse;
 use tracing::Level;
-use tracing_otel_extra::dyn_event;
+use tracing_otel_extra::{dyn_event, extract::headers::HeaderCapture};
 
 /// An implementor of [`OnResponse`] which records the response status code and latency.
 ///
@@ -10,7 +10,14 @@ use tracing_otel_extra::dyn_event;
 /// This component adds the following attributes to the span:
 ///
 /// - `http.response.status_code`: The response status code
-/// - `otel.status_code`: The OpenTelemetry status code (OK for successful responses)
+/// - `http.response.header.<name>`: Headers allow-listed with [`AxumOtelOnResponse::headers`]
+///
+/// The span status is left unset, as the HTTP semantic conventions advise for
+/// server spans of non-`5xx` responses. [`AxumOtelOnFailure`] sets it to
+/// `ERROR` for `5xx` responses; setting `OK` here would prevent that, since an
+/// `OK` status cannot be replaced.
+///
+/// [`AxumOtelOnFailure`]: crate::AxumOtelOnFailure
 ///
 /// # Example
 ///
@@ -21,15 +28,17 @@ use tracing_otel_extra::dyn_event;
 /// let layer = TraceLayer::new_for_http()
 ///     .on_response(AxumOtelOnResponse::new().level(Level::INFO));
 /// ```
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct AxumOtelOnResponse {
     level: Level,
+    headers: HeaderCapture,
 }
 
 impl Default for AxumOtelOnResponse {
     fn default() -> Self {
         Self {
             level: Level::DEBUG,
+            headers: HeaderCapture::default(),
         }
     }
 }
@@ -55,6 +64,14 @@ impl AxumOtelOnResponse {
         self.level = level;
         self
     }
+
+    /// Set the response headers recorded as `http.response.header.<name>` attributes.
+    ///
+    /// No headers are recorded by default, and sensitive headers are masked.
+    pub fn headers(mut self, headers: HeaderCapture) -> Self {
+        self.headers = headers;
+        self
+    }
 }
 
 impl<B> OnResponse<B> for AxumOtelOnResponse {
@@ -66,7 +83,7 @@ impl<B> OnResponse<B> for AxumOtelOnResponse {
     ) {
         let status = response.status().as_u16();
         span.record("http.response.status_code", i64::from(status));
-        span.record("otel.status_code", "OK");
+        self.headers.record_response(span, response.headers());
 
         dyn_event!(
             self.level,
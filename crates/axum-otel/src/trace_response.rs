@@ -0,0 +1,191 @@
+use axum::http::{self, HeaderName, HeaderValue};
+use opentelemetry::trace::{SpanContext, TraceContextExt as _};
+use pin_project_lite::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+use tower::{Layer, Service};
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+
+/// The W3C [`traceresponse`](https://www.w3.org/TR/trace-context-2/#traceresponse-header) header.
+pub const TRACERESPONSE: HeaderName = HeaderName::from_static("traceresponse");
+
+/// The conventional header for the bare trace id.
+pub const X_TRACE_ID: HeaderName = HeaderName::from_static("x-trace-id");
+
+/// The [`Server-Timing`](https://www.w3.org/TR/server-timing/) header.
+pub const SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+/// A [`Layer`] which writes the trace context of the request span into the
+/// response headers, so browsers and support staff can look up the trace.
+///
+/// Every header is opt-in:
+///
+/// - [`traceresponse`](TraceResponseLayer::traceresponse): the W3C
+///   `traceresponse` header, e.g. `00-<trace-id>-<span-id>-01`
+/// - [`trace_id_header`](TraceResponseLayer::trace_id_header): the bare trace
+///   id in a header of your choice, e.g. [`X_TRACE_ID`]
+/// - [`server_timing`](TraceResponseLayer::server_timing): a
+///   `Server-Timing: traceparent;desc="<traceresponse>"` entry, which browser
+///   RUM agents can read from the Performance API
+///
+/// The layer must run inside the request span, i.e. be added after the
+/// `TraceLayer`; [`OtelLayer::trace_response`](crate::OtelLayer::trace_response)
+/// places it there. Nothing is written when there is no valid span context,
+/// e.g. when the request span is disabled and has no parent.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_otel::{OtelLayer, TraceResponseLayer, X_TRACE_ID};
+///
+/// let app: Router<()> = Router::new()
+///     .route("/", get(|| async { "Hello, world!" }))
+///     .layer(OtelLayer::new().trace_response(
+///         TraceResponseLayer::new()
+///             .traceresponse()
+///             .trace_id_header(X_TRACE_ID)
+///             .server_timing(),
+///     ));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TraceResponseLayer {
+    traceresponse: bool,
+    trace_id_header: Option<HeaderName>,
+    server_timing: bool,
+}
+
+impl TraceResponseLayer {
+    /// Create a new `TraceResponseLayer` which writes no headers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the W3C `traceresponse` header.
+    pub fn traceresponse(mut self) -> Self {
+        self.traceresponse = true;
+        self
+    }
+
+    /// Write the trace id to `header_name`, e.g. [`X_TRACE_ID`].
+    pub fn trace_id_header(mut self, header_name: HeaderName) -> Self {
+        self.trace_id_header = Some(header_name);
+        self
+    }
+
+    /// Append a `traceparent` entry to the `Server-Timing` header.
+    pub fn server_timing(mut self) -> Self {
+        self.server_timing = true;
+        self
+    }
+
+    /// Whether no header is written.
+    pub fn is_empty(&self) -> bool {
+        !self.traceresponse && self.trace_id_header.is_none() && !self.server_timing
+    }
+
+    fn write_headers(&self, span_context: &SpanContext, headers: &mut http::HeaderMap) {
+        if !span_context.is_valid() {
+            return;
+        }
+        let traceresponse = format!(
+            "00-{}-{}-{:02x}",
+            span_context.trace_id(),
+            span_context.span_id(),
+            span_context.trace_flags().to_u8()
+        );
+        if self.server_timing
+            && let Ok(value) =
+                HeaderValue::try_from(format!("traceparent;desc=\"{traceresponse}\""))
+        {
+            headers.append(SERVER_TIMING, value);
+        }
+        if let Some(header_name) = &self.trace_id_header
+            && let Ok(value) = HeaderValue::try_from(span_context.trace_id().to_string())
+        {
+            headers.insert(header_name.clone(), value);
+        }
+        if self.traceresponse
+            && let Ok(value) = HeaderValue::try_from(traceresponse)
+        {
+            headers.insert(TRACERESPONSE, value);
+        }
+    }
+}
+
+impl<S> Layer<S> for TraceResponseLayer {
+    type Service = TraceResponse<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TraceResponse {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// The service created by [`TraceResponseLayer`].
+#[derive(Clone, Debug)]
+pub struct TraceResponse<S> {
+    inner: S,
+    layer: TraceResponseLayer,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for TraceResponse<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = TraceResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        let span_context = (!self.layer.is_empty()).then(|| {
+            tracing::Span::current()
+                .context()
+                .span()
+                .span_context()
+                .clone()
+        });
+        TraceResponseFuture {
+            inner: self.inner.call(request),
+            layer: self.layer.clone(),
+            span_context,
+        }
+    }
+}
+
+pin_project! {
+    /// The response future of [`TraceResponse`].
+    #[derive(Debug)]
+    pub struct TraceResponseFuture<F> {
+        #[pin]
+        inner: F,
+        layer: TraceResponseLayer,
+        span_context: Option<SpanContext>,
+    }
+}
+
+impl<F, ResBody, E> Future for TraceResponseFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut result = ready!(this.inner.poll(cx));
+        if let (Ok(response), Some(span_context)) = (&mut result, this.span_context.as_ref()) {
+            this.layer
+                .write_headers(span_context, response.headers_mut());
+        }
+        Poll::Ready(result)
+    }
+}
@@ -1,14 +1,25 @@
 use axum::{
     Router,
     body::Body,
-    http::{Method, Request, StatusCode},
+    http::{HeaderName, Method, Request, StatusCode},
     routing::get,
 };
-use axum_otel::{AxumOtelOnFailure, AxumOtelOnResponse, AxumOtelSpanCreator, Level};
+use axum_otel::{
+    AxumOtelOnFailure, AxumOtelOnResponse, AxumOtelSpanCreator, HeaderCapture, HttpMetricsLayer,
+    Level, OtelLayer, TraceResponseLayer, X_TRACE_ID,
+};
 use http_body_util::BodyExt;
-use opentelemetry::{global, trace::TracerProvider};
+use opentelemetry::{
+    Array, KeyValue, Value, global,
+    metrics::MeterProvider as _,
+    trace::{Status, TracerProvider},
+};
 use opentelemetry_sdk::{
     Resource,
+    metrics::{
+        InMemoryMetricExporter, PeriodicReader, SdkMeterProvider,
+        data::{AggregatedMetrics, MetricData},
+    },
     trace::{InMemorySpanExporter, RandomIdGenerator, Sampler, SdkTracerProvider},
 };
 use std::sync::{Mutex, OnceLock};
@@ -50,6 +61,7 @@ fn app() -> Router<()> {
 }
 
 #[tokio::test(flavor = "current_thread")]
+#[allow(clippy::await_holding_lock)] // The test lock serializes tests across awaits
 async fn test_axum_otel_middleware() {
     let _test_guard = test_lock().lock().expect("test lock poisoned");
 
@@ -205,6 +217,7 @@ async fn test_axum_otel_middleware() {
 }
 
 #[tokio::test(flavor = "current_thread")]
+#[allow(clippy::await_holding_lock)] // The test lock serializes tests across awaits
 async fn test_axum_otel_omits_missing_optional_fields() {
     let _test_guard = test_lock().lock().expect("test lock poisoned");
 
@@ -280,3 +293,493 @@ async fn test_axum_otel_omits_missing_optional_fields() {
         .shutdown()
         .expect("Failed to shutdown tracer provider");
 }
+
+#[tokio::test(flavor = "current_thread")]
+#[allow(clippy::await_holding_lock)] // The test lock serializes tests across awaits
+async fn test_axum_otel_sets_error_status_for_server_errors() {
+    let _test_guard = test_lock().lock().expect("test lock poisoned");
+
+    let exporter = InMemorySpanExporter::default();
+    let provider: SdkTracerProvider = SdkTracerProvider::builder()
+        .with_sampler(Sampler::AlwaysOn)
+        .with_simple_exporter(exporter.clone())
+        .build();
+
+    let tracer = provider.tracer("axum-otel-test-status".to_string());
+    let otel_layer = tracing_opentelemetry::OpenTelemetryLayer::new(tracer);
+    let subscriber = Registry::default().with(otel_layer);
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let app = Router::new()
+        .route("/", get(hello))
+        .route("/fail", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+        .route("/missing", get(|| async { StatusCode::NOT_FOUND }))
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(AxumOtelSpanCreator::new().level(Level::INFO))
+                .on_response(AxumOtelOnResponse::new().level(Level::INFO))
+                .on_failure(AxumOtelOnFailure::new()),
+        );
+
+    for (uri, expected) in [
+        ("/", StatusCode::OK),
+        ("/fail", StatusCode::INTERNAL_SERVER_ERROR),
+        ("/missing", StatusCode::NOT_FOUND),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .expect("Failed to send request");
+        assert_eq!(response.status(), expected);
+        // The request span ends with the response body
+        drop(response);
+    }
+
+    let spans = exporter
+        .get_finished_spans()
+        .expect("Failed to get finished spans");
+    let status = |code: &str| {
+        spans
+            .iter()
+            .find(|span| span_attr(span, "http.response.status_code").as_deref() == Some(code))
+            .unwrap_or_else(|| panic!("no span with status code {code}"))
+            .status
+            .clone()
+    };
+    assert_eq!(status("200"), Status::Unset);
+    assert!(
+        matches!(status("500"), Status::Error { .. }),
+        "Expected an error status for a 500 response"
+    );
+    assert_eq!(status("404"), Status::Unset);
+
+    provider
+        .shutdown()
+        .expect("Failed to shutdown tracer provider");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[allow(clippy::await_holding_lock)] // The test lock serializes tests across awaits
+async fn test_axum_otel_captures_headers_and_redacts_query() {
+    let _test_guard = test_lock().lock().expect("test lock poisoned");
+
+    let exporter = InMemorySpanExporter::default();
+    let provider: SdkTracerProvider = SdkTracerProvider::builder()
+        .with_sampler(Sampler::AlwaysOn)
+        .with_id_generator(RandomIdGenerator::default())
+        .with_simple_exporter(exporter.clone())
+        .with_resource(Resource::builder().build())
+        .build();
+
+    global::set_tracer_provider(provider.clone());
+
+    let tracer = provider.tracer("axum-otel-test-headers".to_string());
+    let otel_layer = tracing_opentelemetry::OpenTelemetryLayer::new(tracer);
+    let subscriber = Registry::default().with(otel_layer);
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let headers = HeaderCapture::new()
+        .with_request_headers(["x-tenant-id", "authorization"])
+        .with_response_headers(["content-type"]);
+    let app = Router::new().route("/", get(hello)).layer(
+        TraceLayer::new_for_http()
+            .make_span_with(
+                AxumOtelSpanCreator::new()
+                    .level(Level::INFO)
+                    .headers(headers.clone()),
+            )
+            .on_response(
+                AxumOtelOnResponse::new()
+                    .level(Level::INFO)
+                    .headers(headers),
+            ),
+    );
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/?sig=abc&page=2")
+                .header("x-tenant-id", "acme")
+                .header("authorization", "Bearer secret")
+                .header("x-other", "ignored")
+                .method(Method::GET)
+                .body(Body::empty())
+                .expect("Failed to build request"),
+        )
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let _body = response
+        .into_body()
+        .collect()
+        .await
+        .expect("Failed to read body");
+
+    let _ = provider.force_flush();
+
+    let spans = exporter
+        .get_finished_spans()
+        .expect("Failed to get finished spans");
+
+    let request_span = spans
+        .iter()
+        .find(|s| s.name == "GET" || s.name == "GET /")
+        .expect("Request span not found");
+
+    let header_attr = |key: &str| {
+        request_span
+            .attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| kv.value.clone())
+    };
+    let strings = |value: &str| Value::Array(Array::String(vec![value.to_string().into()]));
+
+    assert_eq!(
+        header_attr("http.request.header.x-tenant-id"),
+        Some(strings("acme")),
+        "Expected allow-listed request header to be recorded"
+    );
+    assert_eq!(
+        header_attr("http.request.header.authorization"),
+        Some(strings("[REDACTED]")),
+        "Expected sensitive request header to be masked"
+    );
+    assert_eq!(
+        header_attr("http.request.header.x-other"),
+        None,
+        "Expected headers outside the allow-list to be omitted"
+    );
+    assert_eq!(
+        header_attr("http.response.header.content-type"),
+        Some(strings("text/plain; charset=utf-8")),
+        "Expected allow-listed response header to be recorded"
+    );
+    assert_eq!(
+        span_attr(request_span, "url.query"),
+        Some("sig=REDACTED&page=2".to_string()),
+        "Expected sensitive query parameters to be redacted"
+    );
+
+    provider
+        .shutdown()
+        .expect("Failed to shutdown tracer provider");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[allow(clippy::await_holding_lock)] // The test lock serializes tests across awaits
+async fn test_otel_layer_sets_and_propagates_request_id() {
+    let _test_guard = test_lock().lock().expect("test lock poisoned");
+
+    let exporter = InMemorySpanExporter::default();
+    let provider: SdkTracerProvider = SdkTracerProvider::builder()
+        .with_sampler(Sampler::AlwaysOn)
+        .with_simple_exporter(exporter.clone())
+        .build();
+
+    let tracer = provider.tracer("axum-otel-test-layer".to_string());
+    let otel_layer = tracing_opentelemetry::OpenTelemetryLayer::new(tracer);
+    let subscriber = Registry::default().with(otel_layer);
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let app = Router::new()
+        .route("/", get(hello))
+        .layer(OtelLayer::new().level(Level::INFO));
+
+    // A request without an id gets a generated one
+    let response = app
+        .clone()
+        .oneshot(Request::get("/").body(Body::empty()).unwrap())
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), StatusCode::OK);
+    let generated = response.headers()["x-request-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(generated.len(), 36, "Expected a UUID request id");
+    drop(response);
+
+    // A request with an id keeps it
+    let response = app
+        .oneshot(
+            Request::get("/")
+                .header("x-request-id", "req-1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.headers()["x-request-id"], "req-1");
+    // The request spans end with the response bodies
+    drop(response);
+
+    let spans = exporter
+        .get_finished_spans()
+        .expect("Failed to get finished spans");
+    let request_ids: Vec<_> = spans
+        .iter()
+        .filter(|span| span.name == "GET" || span.name == "GET /")
+        .filter_map(|span| span_attr(span, "request_id"))
+        .collect();
+    assert_eq!(request_ids, vec![generated, "req-1".to_string()]);
+
+    provider
+        .shutdown()
+        .expect("Failed to shutdown tracer provider");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[allow(clippy::await_holding_lock)] // The test lock serializes tests across awaits
+async fn test_otel_layer_records_request_id_from_custom_header() {
+    let _test_guard = test_lock().lock().expect("test lock poisoned");
+
+    let exporter = InMemorySpanExporter::default();
+    let provider: SdkTracerProvider = SdkTracerProvider::builder()
+        .with_sampler(Sampler::AlwaysOn)
+        .with_simple_exporter(exporter.clone())
+        .build();
+
+    let tracer = provider.tracer("axum-otel-test-request-id-header".to_string());
+    let otel_layer = tracing_opentelemetry::OpenTelemetryLayer::new(tracer);
+    let subscriber = Registry::default().with(otel_layer);
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let app = Router::new().route("/", get(hello)).layer(
+        OtelLayer::new()
+            .level(Level::INFO)
+            .request_id_header(HeaderName::from_static("x-correlation-id")),
+    );
+
+    // A request without an id gets a generated one in the configured header
+    let response = app
+        .clone()
+        .oneshot(
+            Request::get("/")
+                .header("x-request-id", "ignored")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("Failed to send request");
+    let generated = response.headers()["x-correlation-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    drop(response);
+
+    let response = app
+        .oneshot(
+            Request::get("/")
+                .header("x-correlation-id", "corr-1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.headers()["x-correlation-id"], "corr-1");
+    drop(response);
+
+    let spans = exporter
+        .get_finished_spans()
+        .expect("Failed to get finished spans");
+    let request_ids: Vec<_> = spans
+        .iter()
+        .filter(|span| span.name == "GET" || span.name == "GET /")
+        .filter_map(|span| span_attr(span, "request_id"))
+        .collect();
+    assert_eq!(request_ids, vec![generated, "corr-1".to_string()]);
+
+    provider
+        .shutdown()
+        .expect("Failed to shutdown tracer provider");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[allow(clippy::await_holding_lock)] // The test lock serializes tests across awaits
+async fn test_otel_layer_writes_trace_response_headers() {
+    let _test_guard = test_lock().lock().expect("test lock poisoned");
+
+    let exporter = InMemorySpanExporter::default();
+    let provider: SdkTracerProvider = SdkTracerProvider::builder()
+        .with_sampler(Sampler::AlwaysOn)
+        .with_simple_exporter(exporter.clone())
+        .build();
+
+    let tracer = provider.tracer("axum-otel-test-trace-response".to_string());
+    let otel_layer = tracing_opentelemetry::OpenTelemetryLayer::new(tracer);
+    let subscriber = Registry::default().with(otel_layer);
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let app = Router::new().route("/", get(hello)).layer(
+        OtelLayer::new().level(Level::INFO).trace_response(
+            TraceResponseLayer::new()
+                .traceresponse()
+                .trace_id_header(X_TRACE_ID)
+                .server_timing(),
+        ),
+    );
+
+    let response = app
+        .oneshot(Request::get("/").body(Body::empty()).unwrap())
+        .await
+        .expect("Failed to send request");
+    let header = |name: &str| response.headers()[name].to_str().unwrap().to_string();
+    let (traceresponse, trace_id, server_timing) = (
+        header("traceresponse"),
+        header("x-trace-id"),
+        header("server-timing"),
+    );
+    drop(response);
+
+    let spans = exporter
+        .get_finished_spans()
+        .expect("Failed to get finished spans");
+    let request_span = spans
+        .iter()
+        .find(|s| s.name == "GET" || s.name == "GET /")
+        .expect("Request span not found");
+    let expected = format!(
+        "00-{}-{}-01",
+        request_span.span_context.trace_id(),
+        request_span.span_context.span_id()
+    );
+    assert_eq!(traceresponse, expected);
+    assert_eq!(trace_id, request_span.span_context.trace_id().to_string());
+    assert_eq!(server_timing, format!("traceparent;desc=\"{expected}\""));
+
+    provider
+        .shutdown()
+        .expect("Failed to shutdown tracer provider");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[allow(clippy::await_holding_lock)] // The test lock serializes tests across awaits
+async fn test_otel_layer_records_http_server_metrics() {
+    let exporter = InMemoryMetricExporter::default();
+    let provider = SdkMeterProvider::builder()
+        .with_reader(PeriodicReader::builder(exporter.clone()).build())
+        .build();
+
+    let app = Router::new()
+        .route("/users/{id}", get(hello))
+        .route(
+            "/error",
+            get(|| async { (StatusCode::INTERNAL_SERVER_ERROR, "boom") }),
+        )
+        .layer(
+            OtelLayer::new()
+                .metrics(HttpMetricsLayer::new().meter(provider.meter("axum-otel-test-metrics"))),
+        );
+
+    for uri in ["/users/42", "/error"] {
+        let response = app
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .expect("Failed to send request");
+        response.into_body().collect().await.unwrap();
+    }
+    let response = app
+        .oneshot(
+            Request::post("/users/7")
+                .header("content-length", "5")
+                .body(Body::from("hello"))
+                .unwrap(),
+        )
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    drop(response);
+
+    provider.force_flush().expect("Failed to flush metrics");
+    let resource_metrics = exporter
+        .get_finished_metrics()
+        .expect("Failed to get finished metrics");
+    let metrics: Vec<_> = resource_metrics
+        .iter()
+        .flat_map(|rm| rm.scope_metrics())
+        .flat_map(|sm| sm.metrics())
+        .collect();
+    let metric = |name: &str| {
+        metrics
+            .iter()
+            .rev()
+            .find(|m| m.name() == name)
+            .unwrap_or_else(|| panic!("{name} not recorded"))
+            .data()
+    };
+    let has = |attributes: Vec<&KeyValue>, key: &str, value: Value| {
+        attributes
+            .iter()
+            .any(|kv| kv.key.as_str() == key && kv.value == value)
+    };
+
+    let AggregatedMetrics::F64(MetricData::Histogram(duration)) =
+        metric("http.server.request.duration")
+    else {
+        panic!("http.server.request.duration is not an f64 histogram");
+    };
+    let points: Vec<_> = duration.data_points().collect();
+    assert_eq!(points.len(), 3);
+    let point = |route: Option<&str>, status: i64| {
+        points
+            .iter()
+            .find(|p| {
+                let attributes: Vec<_> = p.attributes().collect();
+                has(
+                    attributes.clone(),
+                    "http.response.status_code",
+                    Value::from(status),
+                ) && route.map_or(
+                    !attributes.iter().any(|kv| kv.key.as_str() == "http.route"),
+                    |route| has(attributes, "http.route", Value::from(route.to_string())),
+                )
+            })
+            .unwrap_or_else(|| panic!("no duration for {route:?} {status}"))
+    };
+
+    let ok: Vec<_> = point(Some("/users/{id}"), 200).attributes().collect();
+    for (key, value) in [
+        ("http.request.method", Value::from("GET")),
+        ("url.scheme", Value::from("http")),
+        ("network.protocol.name", Value::from("http")),
+        ("network.protocol.version", Value::from("1.1")),
+    ] {
+        assert!(has(ok.clone(), key, value), "{key}");
+    }
+    assert!(!ok.iter().any(|kv| kv.key.as_str() == "error.type"));
+    let error: Vec<_> = point(Some("/error"), 500).attributes().collect();
+    assert!(has(error, "error.type", Value::from("500")));
+    let post: Vec<_> = point(Some("/users/{id}"), 405).attributes().collect();
+    assert!(has(post, "http.request.method", Value::from("POST")));
+
+    let AggregatedMetrics::U64(MetricData::Histogram(request_size)) =
+        metric("http.server.request.body.size")
+    else {
+        panic!("http.server.request.body.size is not a u64 histogram");
+    };
+    let sizes: Vec<_> = request_size.data_points().map(|p| p.sum()).collect();
+    assert!(sizes.contains(&5), "{sizes:?}");
+
+    let AggregatedMetrics::U64(MetricData::Histogram(response_size)) =
+        metric("http.server.response.body.size")
+    else {
+        panic!("http.server.response.body.size is not a u64 histogram");
+    };
+    let sizes: Vec<_> = response_size.data_points().map(|p| p.sum()).collect();
+    assert!(sizes.contains(&4), "{sizes:?}");
+
+    let AggregatedMetrics::I64(MetricData::Sum(active)) = metric("http.server.active_requests")
+    else {
+        panic!("http.server.active_requests is not an i64 sum");
+    };
+    assert!(active.data_points().all(|p| p.value() == 0));
+
+    provider
+        .shutdown()
+        .expect("Failed to shutdown meter provider");
+}
@@ -0,0 +1,58 @@
+#![deny(unsafe_code)]
+#![warn(
+    missing_docs,
+    missing_debug_implementations,
+    missing_copy_implementations,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+#![doc(html_root_url = "https://docs.rs/reqwest-otel/latest")]
+
+//! OpenTelemetry tracing for reqwest clients.
+//!
+//! This crate provides a [`reqwest_middleware`] middleware that creates a
+//! client span per request with
+//! [OpenTelemetry HTTP semantic conventions](https://opentelemetry.io/docs/specs/semconv/http/http-spans/#http-client)
+//! and injects its context into the request headers.
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use reqwest_middleware::ClientBuilder;
+//! use reqwest_otel::OtelMiddleware;
+//! use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
+//!
+//! let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+//! let client = ClientBuilder::new(reqwest::Client::new())
+//!     // Around the retry loop, so all attempts share one span
+//!     .with(OtelMiddleware::default())
+//!     .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+//!     // Inside the retry loop, to record every resend
+//!     .with(OtelMiddleware::default())
+//!     .build();
+//! ```
+//!
+//! ## Span attributes
+//!
+//! - `http.request.method`: The HTTP method
+//! - `http.request.resend_count`: The number of resends, when retried
+//! - `http.response.status_code`: The HTTP response status code
+//! - `server.address`: The host the request was sent to
+//! - `server.port`: The port the request was sent to
+//! - `url.full`: The URL, with user info and sensitive query parameters redacted
+//! - `error.type`: The status code for `4xx` and `5xx` responses, or the kind
+//!   of error for requests without a response
+//! - `request_id`: The `x-request-id` sent with the request
+//! - `trace_id`: The OpenTelemetry trace ID
+
+mod middleware;
+
+pub use middleware::{OtelMiddleware, RETRY_EVENT, RequestId, error_type};
+
+// Re-export the Level enum from tracing crate
+pub use tracing::Level;
+
+// Re-export the query redaction used for `url.full`
+pub use tracing_otel_extra::extract::fields::QueryRedaction;
@@ -0,0 +1,304 @@
+use http::{Extensions, HeaderValue};
+use opentelemetry::{KeyValue, trace::TraceContextExt as _};
+use reqwest::{Request, Response, StatusCode};
+use reqwest_middleware::{Error, Middleware, Next, Result};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use tracing::{Level, field::Empty};
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+use tracing_otel_extra::{
+    OtelSpanExt as _, dyn_span,
+    extract::{
+        context,
+        fields::{self, QueryRedaction},
+        http as propagation,
+    },
+};
+
+/// Name of the span event added for every resend of a request.
+pub const RETRY_EVENT: &str = "retry";
+
+/// The request id to send in the `x-request-id` header.
+///
+/// Insert it in the request extensions to forward the id of the request
+/// being served; requests that already carry the header keep their value.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// client
+///     .get(url)
+///     .with_extension(RequestId::from(request_id.header_value().clone()))
+///     .send()
+///     .await?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct RequestId(pub HeaderValue);
+
+impl From<HeaderValue> for RequestId {
+    fn from(value: HeaderValue) -> Self {
+        Self(value)
+    }
+}
+
+/// A [`Middleware`] which creates a client span per request.
+///
+/// The context of the span is injected into the request headers, so the
+/// server continues the trace. The span ends when the middleware returns.
+///
+/// To share one span between the attempts of
+/// `reqwest_retry::RetryTransientMiddleware`, add the middleware both before
+/// and after it. The outer one creates the span around the retry loop and the
+/// inner one records each resend as a [`RETRY_EVENT`] event; the span reports
+/// the outcome of the last attempt. Added only after the retry middleware,
+/// every attempt gets a span of its own.
+///
+/// # Example
+///
+/// ```rust
+/// use reqwest_middleware::ClientBuilder;
+/// use reqwest_otel::{Level, OtelMiddleware};
+/// use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
+///
+/// let otel = OtelMiddleware::default().level(Level::INFO);
+/// let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+/// let client = ClientBuilder::new(reqwest::Client::new())
+///     .with(otel.clone())
+///     .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+///     .with(otel)
+///     .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct OtelMiddleware {
+    level: Level,
+    query: QueryRedaction,
+}
+
+impl Default for OtelMiddleware {
+    fn default() -> Self {
+        Self {
+            level: Level::TRACE,
+            query: QueryRedaction::default(),
+        }
+    }
+}
+
+impl OtelMiddleware {
+    /// Create a new middleware with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the [`Level`] of the client spans.
+    ///
+    /// Defaults to [`Level::TRACE`].
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set how sensitive query parameters are redacted in `url.full`.
+    ///
+    /// Defaults to [`QueryRedaction::default`]. User info in the URL is always
+    /// redacted.
+    pub fn query_redaction(mut self, query: QueryRedaction) -> Self {
+        self.query = query;
+        self
+    }
+
+    fn make_span(&self, request: &Request) -> tracing::Span {
+        let method = request.method().as_str();
+        let url = request.url();
+        dyn_span!(
+            self.level,
+            "HTTP request",
+            otel.name = method,
+            otel.kind = ?opentelemetry::trace::SpanKind::Client,
+            otel.status_code = Empty,
+            otel.status_description = Empty,
+            http.request.method = method,
+            http.request.resend_count = Empty,
+            http.response.status_code = Empty,
+            server.address = url.host_str(),
+            server.port = url.port_or_known_default().map(i64::from),
+            url.full = %self.full_url(url),
+            error.type = Empty,
+            request_id = Empty,
+            trace_id = Empty
+        )
+    }
+
+    fn full_url(&self, url: &reqwest::Url) -> String {
+        let mut url = url.clone();
+        if !url.username().is_empty() || url.password().is_some() {
+            // Both only fail for URLs that cannot carry credentials
+            let _ = url.set_username(fields::DEFAULT_QUERY_MASK);
+            let _ = url.set_password(Some(fields::DEFAULT_QUERY_MASK));
+        }
+        self.query.redact_url(url.as_str()).into_owned()
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for OtelMiddleware {
+    async fn handle(
+        &self,
+        mut request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        use tracing::Instrument as _;
+
+        // Inside the retry loop of an outer call, only the attempt is recorded
+        if let Some(attempts) = extensions
+            .get::<ActiveAttempts>()
+            .and_then(|active| active.0.upgrade())
+        {
+            let span = lock(&attempts).start();
+            let result = next.run(request, extensions).instrument(span).await;
+            lock(&attempts).outcome = Some(Outcome::of(&result));
+            return result;
+        }
+
+        let span = self.make_span(&request);
+        if !request.headers().contains_key(fields::X_REQUEST_ID)
+            && let Some(RequestId(request_id)) = extensions.get::<RequestId>()
+        {
+            request
+                .headers_mut()
+                .insert(fields::X_REQUEST_ID, request_id.clone());
+        }
+        if let Some(request_id) = fields::extract_request_id_from_headers(request.headers()) {
+            span.record("request_id", request_id);
+        }
+
+        // Fall back to the caller's context when the span is filtered out
+        let cx = if span.is_disabled() {
+            tracing::Span::current().context()
+        } else {
+            span.context()
+        };
+        span.record(
+            context::TRACE_ID,
+            tracing::field::display(cx.span().span_context().trace_id()),
+        );
+        propagation::inject_context_into_headers(&cx, request.headers_mut());
+
+        // Inner calls reach the attempts through a weak handle, so nothing
+        // is left behind in the extensions once this call is over
+        let attempts = Arc::new(Mutex::new(Attempts::new(span.clone())));
+        extensions.insert(ActiveAttempts(Arc::downgrade(&attempts)));
+        let result = next.run(request, extensions).instrument(span).await;
+        extensions.remove::<ActiveAttempts>();
+
+        let mut attempts = lock(&attempts);
+        // Errors returned by a retry middleware wrap the error of the last attempt
+        if attempts.outcome.is_none() {
+            attempts.outcome = Some(Outcome::of(&result));
+        }
+        attempts.finish();
+        result
+    }
+}
+
+/// Returns the `error.type` of a request which failed without a response.
+pub fn error_type(error: &Error) -> &'static str {
+    if error.is_timeout() {
+        "timeout"
+    } else if error.is_connect() {
+        "connect"
+    } else if error.is_redirect() {
+        "redirect"
+    } else if error.is_body() {
+        "body"
+    } else if error.is_decode() {
+        "decode"
+    } else if error.is_request() {
+        "request"
+    } else {
+        "_OTHER"
+    }
+}
+
+/// A handle on the attempts of the outer call running the retry loop.
+#[derive(Clone, Debug)]
+struct ActiveAttempts(Weak<Mutex<Attempts>>);
+
+fn lock(attempts: &Mutex<Attempts>) -> MutexGuard<'_, Attempts> {
+    attempts.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Debug)]
+enum Outcome {
+    Status(StatusCode),
+    Error(&'static str, String),
+}
+
+impl Outcome {
+    fn of(result: &Result<Response>) -> Self {
+        match result {
+            Ok(response) => Self::Status(response.status()),
+            Err(error) => Self::Error(error_type(error), error.to_string()),
+        }
+    }
+
+    fn attribute(&self) -> KeyValue {
+        match self {
+            Self::Status(status) => {
+                KeyValue::new("http.response.status_code", i64::from(status.as_u16()))
+            }
+            Self::Error(error_type, _) => KeyValue::new("error.type", *error_type),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Attempts {
+    span: tracing::Span,
+    started: i64,
+    outcome: Option<Outcome>,
+}
+
+impl Attempts {
+    fn new(span: tracing::Span) -> Self {
+        Self {
+            span,
+            started: 0,
+            outcome: None,
+        }
+    }
+
+    /// Count an attempt, recording a [`RETRY_EVENT`] if it is a resend.
+    fn start(&mut self) -> tracing::Span {
+        if self.started > 0 {
+            let mut attributes = vec![KeyValue::new("http.request.resend_count", self.started)];
+            attributes.extend(self.outcome.take().map(|outcome| outcome.attribute()));
+            tracing_otel_extra::OtelSpanExt::add_event(&self.span, RETRY_EVENT, attributes);
+        }
+        self.started += 1;
+        self.span.clone()
+    }
+
+    /// Record the outcome of the last attempt once the retry loop is over, so
+    /// that a failed attempt followed by a successful resend does not leave an
+    /// `error.type`.
+    fn finish(&mut self) {
+        let span = &self.span;
+        if self.started > 1 {
+            span.record("http.request.resend_count", self.started - 1);
+        }
+        match self.outcome.take() {
+            Some(Outcome::Status(status)) => {
+                span.record("http.response.status_code", i64::from(status.as_u16()));
+                if status.is_client_error() || status.is_server_error() {
+                    span.record("error.type", status.as_str());
+                    span.set_error(status);
+                }
+            }
+            Some(Outcome::Error(error_type, message)) => {
+                span.record("error.type", error_type);
+                span.set_error(message);
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use axum::{Router, http::HeaderMap, routing::get};
+use http::{HeaderValue, StatusCode};
+use opentelemetry::{
+    KeyValue, Value,
+    trace::{SpanKind, Status, TracerProvider as _},
+};
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    trace::{InMemorySpanExporter, SdkTracerProvider},
+};
+use reqwest_middleware::ClientBuilder;
+use reqwest_otel::{OtelMiddleware, RETRY_EVENT, RequestId};
+use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+use tokio::net::TcpListener;
+use tracing_subscriber::{Registry, layer::SubscriberExt as _};
+
+fn setup() -> (InMemorySpanExporter, tracing::subscriber::DefaultGuard) {
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let subscriber = Registry::default()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("reqwest-otel-test")));
+    let guard = tracing::subscriber::set_default(subscriber);
+    (exporter, guard)
+}
+
+/// Serve `/flaky`, which fails twice before succeeding, and `/missing`.
+/// Both echo the `traceparent` and `x-request-id` they received.
+async fn serve() -> String {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let echo = |headers: &HeaderMap| {
+        let mut echoed = HeaderMap::new();
+        for (name, echo) in [
+            ("traceparent", "x-echo-traceparent"),
+            ("x-request-id", "x-echo-request-id"),
+        ] {
+            if let Some(value) = headers.get(name) {
+                echoed.insert(echo, value.clone());
+            }
+        }
+        echoed
+    };
+    let app = Router::new()
+        .route(
+            "/flaky",
+            get(move |headers: HeaderMap| async move {
+                let status = match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => StatusCode::SERVICE_UNAVAILABLE,
+                    _ => StatusCode::OK,
+                };
+                (status, echo(&headers))
+            }),
+        )
+        .route(
+            "/missing",
+            get(move |headers: HeaderMap| async move { (StatusCode::NOT_FOUND, echo(&headers)) }),
+        );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_retries_share_one_span() {
+    let (exporter, _guard) = setup();
+    let base_url = serve().await;
+    let retry_policy = ExponentialBackoff::builder()
+        .retry_bounds(Duration::from_millis(1), Duration::from_millis(1))
+        .build_with_max_retries(3);
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(OtelMiddleware::default())
+        .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+        .with(OtelMiddleware::default())
+        .build();
+
+    let response = client
+        .get(format!("{base_url}/flaky?token=secret"))
+        .with_extension(RequestId::from(HeaderValue::from_static("req-1")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-echo-request-id"], "req-1");
+    let traceparent = response.headers()["x-echo-traceparent"]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 1);
+    let span = &spans[0];
+    assert_eq!(span.name, "GET");
+    assert_eq!(span.span_kind, SpanKind::Client);
+    assert_eq!(span.status, Status::Unset);
+    assert_eq!(
+        traceparent,
+        format!(
+            "00-{}-{}-01",
+            span.span_context.trace_id(),
+            span.span_context.span_id()
+        )
+    );
+    for (key, value) in [
+        ("http.request.method", Value::from("GET")),
+        ("http.request.resend_count", Value::from(2_i64)),
+        ("http.response.status_code", Value::from(200_i64)),
+        ("server.address", Value::from("127.0.0.1")),
+        (
+            "url.full",
+            Value::from(format!("{base_url}/flaky?token=REDACTED")),
+        ),
+        ("request_id", Value::from("req-1")),
+    ] {
+        assert!(
+            span.attributes.contains(&KeyValue::new(key, value)),
+            "{key}"
+        );
+    }
+    assert!(
+        !span
+            .attributes
+            .iter()
+            .any(|kv| kv.key.as_str() == "error.type")
+    );
+
+    let retries: Vec<_> = span
+        .events
+        .iter()
+        .filter(|event| event.name == RETRY_EVENT)
+        .collect();
+    assert_eq!(retries.len(), 2);
+    for (resend_count, event) in (1_i64..).zip(retries) {
+        assert!(event.attributes.iter().any(|kv| {
+            kv.key.as_str() == "http.request.resend_count" && kv.value == Value::from(resend_count)
+        }));
+        assert!(event.attributes.iter().any(|kv| {
+            kv.key.as_str() == "http.response.status_code" && kv.value == Value::from(503_i64)
+        }));
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_reused_extensions_start_new_spans() {
+    let (exporter, _guard) = setup();
+    let base_url = serve().await;
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(OtelMiddleware::default())
+        .build();
+
+    let mut extensions = http::Extensions::new();
+    for _ in 0..2 {
+        let request = client.get(format!("{base_url}/missing")).build().unwrap();
+        client
+            .execute_with_extensions(request, &mut extensions)
+            .await
+            .unwrap();
+        // The span has ended by the time the request returns
+        assert!(!exporter.get_finished_spans().unwrap().is_empty());
+    }
+
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 2);
+    for span in &spans {
+        assert!(
+            !span
+                .attributes
+                .iter()
+                .any(|kv| kv.key.as_str() == "http.request.resend_count")
+        );
+        assert!(span.events.iter().all(|event| event.name != RETRY_EVENT));
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_error_status_and_types() {
+    let (exporter, _guard) = setup();
+    let base_url = serve().await;
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(OtelMiddleware::default())
+        .build();
+
+    let response = client
+        .get(format!("{base_url}/missing"))
+        .header("x-request-id", "from-caller")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["x-echo-request-id"], "from-caller");
+
+    // Nothing listens on port 1
+    let error = client.get("http://127.0.0.1:1/").send().await.unwrap_err();
+    assert_eq!(reqwest_otel::error_type(&error), "connect");
+
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 2);
+    assert_eq!(spans[0].status, Status::error("404 Not Found"));
+    assert!(
+        spans[0]
+            .attributes
+            .contains(&KeyValue::new("error.type", "404"))
+    );
+    assert!(
+        spans[0]
+            .attributes
+            .contains(&KeyValue::new("http.response.status_code", 404_i64))
+    );
+    assert!(
+        !spans[0]
+            .attributes
+            .iter()
+            .any(|kv| kv.key.as_str() == "http.request.resend_count")
+    );
+    assert!(matches!(spans[1].status, Status::Error { .. }));
+    assert!(
+        spans[1]
+            .attributes
+            .contains(&KeyValue::new("error.type", "connect"))
+    );
+    assert!(
+        spans[1]
+            .attributes
+            .contains(&KeyValue::new("server.port", 1_i64))
+    );
+}
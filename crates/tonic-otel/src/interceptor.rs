@@ -0,0 +1,29 @@
+use crate::metadata::inject_context_into_metadata;
+use tonic::{Request, Status, service::Interceptor};
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+
+/// A tonic client [`Interceptor`] which injects the context of the current
+/// span into the request metadata.
+///
+/// The interceptor only propagates the context; use
+/// [`TonicOtelLayer::client`](crate::TonicOtelLayer::client) to also create a
+/// client span per call.
+///
+/// # Example
+///
+/// ```rust
+/// use tonic_otel::TonicOtelInterceptor;
+/// use tonic::service::Interceptor as _;
+///
+/// let request = TonicOtelInterceptor.call(tonic::Request::new(())).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TonicOtelInterceptor;
+
+impl Interceptor for TonicOtelInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let context = tracing::Span::current().context();
+        inject_context_into_metadata(&context, request.metadata_mut());
+        Ok(request)
+    }
+}
@@ -0,0 +1,265 @@
+use crate::status::{parse_grpc_path, record_grpc_status};
+use http_body::{Body, Frame, SizeHint};
+use opentelemetry::trace::{SpanKind, TraceContextExt as _};
+use pin_project_lite::pin_project;
+use std::{
+    fmt::Display,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+use tonic::Status;
+use tower::{Layer, Service};
+use tracing::{Level, field::Empty};
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+use tracing_otel_extra::{
+    OtelSpanExt as _, dyn_span,
+    extract::{context, fields, http as propagation},
+};
+
+/// A tower [`Layer`] which creates a span per gRPC call.
+///
+/// Server spans are parented on the context in the request metadata, and
+/// client spans inject their context into it. The gRPC status is read from
+/// the response headers for trailers-only responses, and from the trailers
+/// otherwise, so the span stays open until the response body ends.
+///
+/// # Example
+///
+/// ```rust
+/// use tonic_otel::{Level, TonicOtelLayer};
+///
+/// let server = TonicOtelLayer::server().level(Level::INFO);
+/// let client = TonicOtelLayer::client();
+/// ```
+#[derive(Clone, Debug)]
+pub struct TonicOtelLayer {
+    kind: SpanKind,
+    level: Level,
+}
+
+impl TonicOtelLayer {
+    /// Create a layer for tonic servers.
+    pub fn server() -> Self {
+        Self {
+            kind: SpanKind::Server,
+            level: Level::TRACE,
+        }
+    }
+
+    /// Create a layer for tonic clients, e.g. around a `Channel`.
+    pub fn client() -> Self {
+        Self {
+            kind: SpanKind::Client,
+            level: Level::TRACE,
+        }
+    }
+
+    /// Set the [`Level`] of the call spans.
+    ///
+    /// Defaults to [`Level::TRACE`].
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+impl Default for TonicOtelLayer {
+    fn default() -> Self {
+        Self::server()
+    }
+}
+
+impl<S> Layer<S> for TonicOtelLayer {
+    type Service = TonicOtelService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TonicOtelService {
+            inner,
+            kind: self.kind.clone(),
+            level: self.level,
+        }
+    }
+}
+
+/// The service created by [`TonicOtelLayer`].
+#[derive(Clone, Debug)]
+pub struct TonicOtelService<S> {
+    inner: S,
+    kind: SpanKind,
+    level: Level,
+}
+
+impl<S> TonicOtelService<S> {
+    fn make_span<B>(&self, request: &http::Request<B>) -> tracing::Span {
+        let path = request.uri().path();
+        let (service, method) = parse_grpc_path(path).unzip();
+        let span = dyn_span!(
+            self.level,
+            "grpc",
+            otel.name = service.map_or_else(|| path.to_string(), |_| path[1..].to_string()),
+            otel.kind = ?self.kind,
+            otel.status_code = Empty,
+            otel.status_description = Empty,
+            rpc.system = "grpc",
+            rpc.service = service,
+            rpc.method = method,
+            rpc.grpc.status_code = Empty,
+            server.address = Empty,
+            trace_id = Empty
+        );
+
+        let authority = request
+            .uri()
+            .host()
+            .or_else(|| fields::extract_host(request));
+        if let Some(authority) = authority {
+            span.record("server.address", authority);
+        }
+        span
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for TonicOtelService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Error: Display,
+{
+    type Response = http::Response<TonicOtelBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<ReqBody>) -> Self::Future {
+        let span = self.make_span(&request);
+        match self.kind {
+            SpanKind::Server => context::set_otel_parent(request.headers(), &span),
+            _ => {
+                // Fall back to the caller's context when the span is filtered out
+                let cx = if span.is_disabled() {
+                    tracing::Span::current().context()
+                } else {
+                    span.context()
+                };
+                span.record(
+                    context::TRACE_ID,
+                    tracing::field::display(cx.span().span_context().trace_id()),
+                );
+                propagation::inject_context_into_request(&cx, &mut request);
+            }
+        }
+
+        let inner = {
+            let _entered = span.enter();
+            self.inner.call(request)
+        };
+        ResponseFuture {
+            inner,
+            span: Some(span),
+            kind: self.kind.clone(),
+        }
+    }
+}
+
+pin_project! {
+    /// The response future of [`TonicOtelService`].
+    #[derive(Debug)]
+    pub struct ResponseFuture<F> {
+        #[pin]
+        inner: F,
+        span: Option<tracing::Span>,
+        kind: SpanKind,
+    }
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+    E: Display,
+{
+    type Output = Result<http::Response<TonicOtelBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = {
+            let _entered = this.span.as_ref().map(tracing::Span::enter);
+            ready!(this.inner.poll(cx))
+        };
+        let span = this.span.take().unwrap_or_else(tracing::Span::none);
+        let kind = this.kind.clone();
+        Poll::Ready(match result {
+            Ok(response) => {
+                // Trailers-only responses carry the status in the headers
+                let span = match Status::from_header_map(response.headers()) {
+                    Some(status) => {
+                        record_grpc_status(&span, &status, &kind);
+                        None
+                    }
+                    None => Some(span),
+                };
+                Ok(response.map(|inner| TonicOtelBody { inner, span, kind }))
+            }
+            Err(error) => {
+                span.set_error(&error);
+                Err(error)
+            }
+        })
+    }
+}
+
+pin_project! {
+    /// The response body of [`TonicOtelService`], which records the gRPC status
+    /// from the trailers.
+    #[derive(Debug)]
+    pub struct TonicOtelBody<B> {
+        #[pin]
+        inner: B,
+        span: Option<tracing::Span>,
+        kind: SpanKind,
+    }
+}
+
+impl<B> Body for TonicOtelBody<B>
+where
+    B: Body,
+    B::Error: Display,
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let result = ready!(this.inner.poll_frame(cx));
+        match &result {
+            Some(Ok(frame)) => {
+                if let Some(trailers) = frame.trailers_ref()
+                    && let Some(status) = Status::from_header_map(trailers)
+                    && let Some(span) = this.span.take()
+                {
+                    record_grpc_status(&span, &status, this.kind);
+                }
+            }
+            Some(Err(error)) => {
+                if let Some(span) = this.span.take() {
+                    span.set_error(error);
+                }
+            }
+            None => {}
+        }
+        Poll::Ready(result)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,88 @@
+#![deny(unsafe_code)]
+#![warn(
+    missing_docs,
+    missing_debug_implementations,
+    missing_copy_implementations,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+#![doc(html_root_url = "https://docs.rs/tonic-otel/latest")]
+
+//! OpenTelemetry tracing for tonic gRPC servers and clients.
+//!
+//! This crate provides a tower layer that creates a span per gRPC call with
+//! [OpenTelemetry RPC semantic conventions](https://opentelemetry.io/docs/specs/semconv/rpc/grpc/),
+//! and an interceptor that propagates the trace context through request
+//! metadata.
+//!
+//! ## Usage
+//!
+//! Instrument a server by adding [`TonicOtelLayer::server`] in front of the
+//! generated services. The remote parent is extracted from the request
+//! metadata:
+//!
+//! ```rust,ignore
+//! use tonic::transport::Server;
+//! use tonic_otel::{Level, TonicOtelLayer};
+//!
+//! Server::builder()
+//!     .layer(TonicOtelLayer::server().level(Level::INFO))
+//!     .add_service(GreeterServer::new(greeter))
+//!     .serve(addr)
+//!     .await?;
+//! ```
+//!
+//! Instrument a client by wrapping its channel with [`TonicOtelLayer::client`],
+//! which also injects the context of the client span into the metadata:
+//!
+//! ```rust,ignore
+//! use tonic_otel::TonicOtelLayer;
+//! use tower::ServiceBuilder;
+//!
+//! let channel = ServiceBuilder::new()
+//!     .layer(TonicOtelLayer::client())
+//!     .service(Channel::from_static("http://[::1]:50051").connect().await?);
+//! let client = GreeterClient::new(channel);
+//! ```
+//!
+//! When only propagation is needed, [`TonicOtelInterceptor`] injects the
+//! context of the current span into every request:
+//!
+//! ```rust,ignore
+//! let client = GreeterClient::with_interceptor(channel, tonic_otel::TonicOtelInterceptor);
+//! ```
+//!
+//! ## Span attributes
+//!
+//! - `rpc.system`: Always `grpc`
+//! - `rpc.service`: The fully-qualified service, e.g. `helloworld.Greeter`
+//! - `rpc.method`: The method, e.g. `SayHello`
+//! - `rpc.grpc.status_code`: The numeric gRPC status code
+//! - `server.address`: The authority the call was sent to
+//! - `trace_id`: The OpenTelemetry trace ID
+//!
+//! The span status is `ERROR` for the status codes that are errors for the
+//! span kind: every code but `OK` for clients, and `UNKNOWN`,
+//! `DEADLINE_EXCEEDED`, `UNIMPLEMENTED`, `INTERNAL`, `UNAVAILABLE` and
+//! `DATA_LOSS` for servers.
+
+mod interceptor;
+mod layer;
+mod metadata;
+mod status;
+
+pub use interceptor::TonicOtelInterceptor;
+pub use layer::{ResponseFuture, TonicOtelBody, TonicOtelLayer, TonicOtelService};
+pub use metadata::{
+    MetadataExtractor, MetadataInjector, extract_context_from_metadata,
+    inject_context_into_metadata, set_otel_parent_from_metadata,
+};
+pub use status::{is_error_status, parse_grpc_path, record_grpc_status};
+
+// Re-export the Level enum from tracing crate
+pub use tracing::Level;
+
+// Re-export the span kind used to choose between server and client semantics
+pub use opentelemetry::trace::SpanKind;
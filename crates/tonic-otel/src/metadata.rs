@@ -0,0 +1,59 @@
+use opentelemetry::{
+    Context,
+    propagation::{Extractor, Injector},
+};
+use tonic::metadata::{KeyRef, MetadataKey, MetadataMap, MetadataValue};
+use tracing_otel_extra::extract::{context, http as propagation};
+
+/// Helper for extracting a context from gRPC metadata.
+///
+/// Only ASCII metadata is read; binary (`-bin`) entries are ignored.
+#[derive(Debug)]
+pub struct MetadataExtractor<'a>(pub &'a MetadataMap);
+
+impl Extractor for MetadataExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0
+            .keys()
+            .filter_map(|key| match key {
+                KeyRef::Ascii(key) => Some(key.as_str()),
+                KeyRef::Binary(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Helper for injecting a context into gRPC metadata.
+///
+/// Fields whose key or value is not valid ASCII metadata are skipped.
+#[derive(Debug)]
+pub struct MetadataInjector<'a>(pub &'a mut MetadataMap);
+
+impl Injector for MetadataInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let Ok(key) = MetadataKey::from_bytes(key.as_bytes())
+            && let Ok(value) = MetadataValue::try_from(value.as_str())
+        {
+            self.0.insert(key, value);
+        }
+    }
+}
+
+/// Extract the context from the metadata of an incoming request
+pub fn extract_context_from_metadata(metadata: &MetadataMap) -> Context {
+    propagation::extract_context(&MetadataExtractor(metadata))
+}
+
+/// Inject specific context into the metadata of an outgoing request
+pub fn inject_context_into_metadata(context: &Context, metadata: &mut MetadataMap) {
+    propagation::inject_context(context, &mut MetadataInjector(metadata));
+}
+
+/// Set the parent of `span` from request metadata and record the trace id.
+pub fn set_otel_parent_from_metadata(metadata: &MetadataMap, span: &tracing::Span) {
+    context::set_otel_parent_from_carrier(&MetadataExtractor(metadata), span);
+}
@@ -0,0 +1,56 @@
+use opentelemetry::trace::SpanKind;
+use tonic::{Code, Status};
+use tracing_otel_extra::OtelSpanExt as _;
+
+/// Split a gRPC request path, `/package.Service/Method`, into service and method.
+///
+/// # Example
+///
+/// ```rust
+/// use tonic_otel::parse_grpc_path;
+///
+/// assert_eq!(
+///     parse_grpc_path("/helloworld.Greeter/SayHello"),
+///     Some(("helloworld.Greeter", "SayHello"))
+/// );
+/// assert_eq!(parse_grpc_path("/health"), None);
+/// ```
+pub fn parse_grpc_path(path: &str) -> Option<(&str, &str)> {
+    let (service, method) = path.strip_prefix('/')?.split_once('/')?;
+    (!service.is_empty() && !method.is_empty() && !method.contains('/'))
+        .then_some((service, method))
+}
+
+/// Returns whether `code` sets the span status to `ERROR` for a span of `kind`.
+///
+/// Clients treat every code but `OK` as an error. Servers only treat codes
+/// that point at the server itself as errors, so that, for example,
+/// `NOT_FOUND` or `INVALID_ARGUMENT` do not count against the server.
+pub fn is_error_status(code: Code, kind: &SpanKind) -> bool {
+    match kind {
+        SpanKind::Server => matches!(
+            code,
+            Code::Unknown
+                | Code::DeadlineExceeded
+                | Code::Unimplemented
+                | Code::Internal
+                | Code::Unavailable
+                | Code::DataLoss
+        ),
+        _ => code != Code::Ok,
+    }
+}
+
+/// Record a gRPC status on `span` as `rpc.grpc.status_code` and the span status.
+pub fn record_grpc_status(span: &tracing::Span, status: &Status, kind: &SpanKind) {
+    let code = status.code();
+    span.record("rpc.grpc.status_code", i64::from(code as i32));
+    if is_error_status(code, kind) {
+        let message = status.message();
+        if message.is_empty() {
+            span.set_error(code.description());
+        } else {
+            span.set_error(message);
+        }
+    }
+}
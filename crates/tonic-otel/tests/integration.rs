@@ -0,0 +1,220 @@
+use http_body_util::{BodyExt as _, Empty, Full};
+use opentelemetry::{
+    KeyValue, Value,
+    trace::{SpanKind, Status, TraceContextExt as _, TracerProvider as _},
+};
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    trace::{InMemorySpanExporter, SdkTracerProvider},
+};
+use std::convert::Infallible;
+use tonic::service::Interceptor as _;
+use tonic_otel::{TonicOtelInterceptor, TonicOtelLayer, extract_context_from_metadata};
+use tower::{Layer as _, ServiceExt as _, service_fn};
+use tracing::Instrument as _;
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+use tracing_subscriber::{Registry, filter::LevelFilter, layer::SubscriberExt as _};
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+fn setup() -> (InMemorySpanExporter, tracing::subscriber::DefaultGuard) {
+    setup_with_level(LevelFilter::TRACE)
+}
+
+fn setup_with_level(
+    level: LevelFilter,
+) -> (InMemorySpanExporter, tracing::subscriber::DefaultGuard) {
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let subscriber = Registry::default()
+        .with(level)
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("tonic-otel-test")));
+    let guard = tracing::subscriber::set_default(subscriber);
+    (exporter, guard)
+}
+
+fn grpc_request() -> http::Request<Empty<&'static [u8]>> {
+    http::Request::builder()
+        .uri("http://greeter.local/helloworld.Greeter/SayHello")
+        .header("content-type", "application/grpc")
+        .header("traceparent", format!("00-{TRACE_ID}-00f067aa0ba902b7-01"))
+        .body(Empty::new())
+        .unwrap()
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_server_records_status_from_trailers() {
+    let (exporter, _guard) = setup();
+    let service = TonicOtelLayer::server().layer(service_fn(|_request| async {
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert("grpc-status", "0".parse().unwrap());
+        let body = Full::new(&b"\0\0\0\0\0"[..])
+            .with_trailers(async move { Some(Ok::<_, Infallible>(trailers)) });
+        Ok::<_, Infallible>(http::Response::new(body))
+    }));
+
+    let response = service.oneshot(grpc_request()).await.unwrap();
+    let collected = response.into_body().collect().await.unwrap();
+    assert!(collected.trailers().is_some());
+
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 1);
+    let span = &spans[0];
+    assert_eq!(span.name, "helloworld.Greeter/SayHello");
+    assert_eq!(span.span_kind, SpanKind::Server);
+    assert_eq!(span.status, Status::Unset);
+    assert_eq!(span.span_context.trace_id().to_string(), TRACE_ID);
+    for (key, value) in [
+        ("rpc.system", Value::from("grpc")),
+        ("rpc.service", Value::from("helloworld.Greeter")),
+        ("rpc.method", Value::from("SayHello")),
+        ("rpc.grpc.status_code", Value::from(0_i64)),
+        ("server.address", Value::from("greeter.local")),
+    ] {
+        assert!(
+            span.attributes.contains(&KeyValue::new(key, value)),
+            "{key}"
+        );
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_server_maps_status_codes() {
+    let (exporter, _guard) = setup();
+    for code in ["14", "5"] {
+        let service = TonicOtelLayer::server().layer(service_fn(move |_request| async move {
+            // Trailers-only response, as sent for an error returned by a handler
+            let response = http::Response::builder()
+                .header("grpc-status", code)
+                .header("grpc-message", "backend%20down")
+                .body(Empty::<&'static [u8]>::new())
+                .unwrap();
+            Ok::<_, Infallible>(response)
+        }));
+        let response = service.oneshot(grpc_request()).await.unwrap();
+        response.into_body().collect().await.unwrap();
+    }
+
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 2);
+    // UNAVAILABLE is a server error, NOT_FOUND is the client's
+    assert_eq!(spans[0].status, Status::error("backend down"));
+    assert!(
+        spans[0]
+            .attributes
+            .contains(&KeyValue::new("rpc.grpc.status_code", 14_i64))
+    );
+    assert_eq!(spans[1].status, Status::Unset);
+    assert!(
+        spans[1]
+            .attributes
+            .contains(&KeyValue::new("rpc.grpc.status_code", 5_i64))
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_client_injects_context_and_maps_status() {
+    let (exporter, _guard) = setup();
+    let service = TonicOtelLayer::client().layer(service_fn(
+        |request: http::Request<Empty<&'static [u8]>>| async move {
+            let traceparent = request
+                .headers()
+                .get("traceparent")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let response = http::Response::builder()
+                .header("grpc-status", "5")
+                .header("x-traceparent", traceparent.unwrap_or_default())
+                .body(Empty::<&'static [u8]>::new())
+                .unwrap();
+            Ok::<_, Infallible>(response)
+        },
+    ));
+
+    let mut request = grpc_request();
+    request.headers_mut().remove("traceparent");
+    let response = service.oneshot(request).await.unwrap();
+    let traceparent = response.headers()["x-traceparent"]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 1);
+    let span = &spans[0];
+    assert_eq!(span.span_kind, SpanKind::Client);
+    assert_eq!(
+        span.status,
+        Status::error("Some requested entity was not found")
+    );
+    assert_eq!(
+        traceparent,
+        format!(
+            "00-{}-{}-01",
+            span.span_context.trace_id(),
+            span.span_context.span_id()
+        )
+    );
+    assert!(
+        span.attributes
+            .contains(&KeyValue::new("rpc.grpc.status_code", 5_i64))
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_client_injects_caller_context_when_span_is_filtered_out() {
+    let (exporter, _guard) = setup_with_level(LevelFilter::INFO);
+    let service = TonicOtelLayer::client()
+        .level(tracing::Level::DEBUG)
+        .layer(service_fn(
+            |request: http::Request<Empty<&'static [u8]>>| async move {
+                let traceparent = request.headers()["traceparent"].clone();
+                let response = http::Response::builder()
+                    .header("grpc-status", "0")
+                    .header("x-traceparent", traceparent)
+                    .body(Empty::<&'static [u8]>::new())
+                    .unwrap();
+                Ok::<_, Infallible>(response)
+            },
+        ));
+
+    let caller = tracing::info_span!("caller");
+    let mut request = grpc_request();
+    request.headers_mut().remove("traceparent");
+    let response = service
+        .oneshot(request)
+        .instrument(caller.clone())
+        .await
+        .unwrap();
+    drop(caller);
+
+    // Only the caller's span is recorded, and its context is propagated
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].name, "caller");
+    assert_eq!(
+        response.headers()["x-traceparent"],
+        format!(
+            "00-{}-{}-01",
+            spans[0].span_context.trace_id(),
+            spans[0].span_context.span_id()
+        )
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_interceptor_injects_current_context() {
+    let (_exporter, _guard) = setup();
+    let span = tracing::info_span!("caller");
+    let request = span.in_scope(|| TonicOtelInterceptor.call(tonic::Request::new(())).unwrap());
+
+    let extracted = extract_context_from_metadata(request.metadata());
+    assert_eq!(
+        extracted.span().span_context().trace_id(),
+        span.context().span().span_context().trace_id()
+    );
+    assert!(extracted.span().span_context().is_remote());
+}
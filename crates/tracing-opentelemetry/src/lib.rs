@@ -13,6 +13,7 @@
 //! - Configurable sampling and resource attributes
 //! - Automatic cleanup with guard pattern
 //! - Support for both tracing and metrics
+//! - Optional redaction of span and log attributes before export
 //!
 //! ## Examples
 //!
@@ -56,16 +57,26 @@
 
 mod guard;
 mod otel;
+mod redact;
 mod resource;
 #[cfg(feature = "subscriber")]
 mod subscriber;
 
 // Re-exports
 pub use guard::OtelGuard;
-pub use otel::{init_logger_provider, init_meter_provider, init_tracer_provider};
+pub use otel::{
+    ProviderOptions, Providers, init_logger_provider, init_meter_provider, init_providers,
+    init_tracer_provider,
+};
+pub use redact::{
+    Redact, RedactingLogRecord, RedactingLogger, RedactingLoggerProvider, RedactingSpanProcessor,
+};
 pub use resource::get_resource;
 #[cfg(feature = "subscriber")]
-pub use subscriber::{BoxLayer, init_env_filter, init_tracing_subscriber};
+pub use subscriber::{
+    BoxFilter, BoxLayer, SubscriberOptions, init_env_filter, init_tracing_subscriber,
+    init_tracing_subscriber_with_options,
+};
 
 // Re-exports opentelemetry crates
 pub use opentelemetry;
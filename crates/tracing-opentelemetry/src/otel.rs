@@ -5,17 +5,20 @@
 //!
 //! - Configuring resource attributes
 //! - Initializing tracer and meter providers
-use crate::macros::build_exporter;
+use crate::{
+    macros::build_exporter,
+    redact::{Redact, RedactingSpanProcessor},
+};
 use anyhow::Result;
 use opentelemetry::global;
 use opentelemetry_sdk::{
     Resource,
-    logs::SdkLoggerProvider,
+    logs::{BatchLogProcessor, SdkLoggerProvider},
     metrics::{MeterProviderBuilder, PeriodicReader, SdkMeterProvider, Temporality},
     propagation::TraceContextPropagator,
-    trace::{RandomIdGenerator, Sampler, SdkTracerProvider},
+    trace::{BatchSpanProcessor, RandomIdGenerator, Sampler, SdkTracerProvider},
 };
-use std::{env::var, time::Duration};
+use std::{env::var, sync::Arc, time::Duration};
 
 /// Global OTLP endpoint environment variable.
 const OTEL_EXPORTER_OTLP_ENDPOINT: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
@@ -158,6 +161,15 @@ fn build_log_exporter() -> Result<opentelemetry_otlp::LogExporter> {
 /// }
 /// ```
 pub fn init_tracer_provider(resource: &Resource, sample_ratio: f64) -> Result<SdkTracerProvider> {
+    build_tracer_provider(resource, sample_ratio, None)
+}
+
+/// Build the tracer provider, redacting exported span attributes with `redactor`.
+fn build_tracer_provider(
+    resource: &Resource,
+    sample_ratio: f64,
+    redactor: Option<Arc<dyn Redact>>,
+) -> Result<SdkTracerProvider> {
     global::set_text_map_propagator(TraceContextPropagator::new());
 
     let builder = SdkTracerProvider::builder()
@@ -168,8 +180,13 @@ pub fn init_tracer_provider(resource: &Resource, sample_ratio: f64) -> Result<Sd
         .with_resource(resource.clone());
 
     let tracer_provider = if exporter_enabled(OTEL_EXPORTER_OTLP_TRACES_ENDPOINT) {
-        let exporter = build_span_exporter()?;
-        builder.with_batch_exporter(exporter).build()
+        let processor = BatchSpanProcessor::builder(build_span_exporter()?).build();
+        match redactor {
+            Some(redactor) => builder
+                .with_span_processor(RedactingSpanProcessor::new(processor, redactor))
+                .build(),
+            None => builder.with_span_processor(processor).build(),
+        }
     } else {
         builder.build()
     };
@@ -256,8 +273,8 @@ pub fn init_logger_provider(resource: &Resource) -> Result<SdkLoggerProvider> {
     let builder = SdkLoggerProvider::builder().with_resource(resource.clone());
 
     let logger_provider = if exporter_enabled(OTEL_EXPORTER_OTLP_LOGS_ENDPOINT) {
-        let exporter = build_log_exporter()?;
-        builder.with_batch_exporter(exporter).build()
+        let processor = BatchLogProcessor::builder(build_log_exporter()?).build();
+        builder.with_log_processor(processor).build()
     } else {
         builder.build()
     };
@@ -265,6 +282,113 @@ pub fn init_logger_provider(resource: &Resource) -> Result<SdkLoggerProvider> {
     Ok(logger_provider)
 }
 
+/// Settings of the providers created by [`init_providers`].
+#[derive(Clone, Debug)]
+pub struct ProviderOptions {
+    /// The ratio of traces to sample (0.0 to 1.0).
+    pub sample_ratio: f64,
+    /// The interval in seconds between metric collections.
+    pub metrics_interval_secs: u64,
+    /// Whether a logger provider is created for OpenTelemetry logs.
+    pub logs_enabled: bool,
+    /// Redactor applied to span attributes and log records before export.
+    pub redactor: Option<Arc<dyn Redact>>,
+}
+
+impl Default for ProviderOptions {
+    fn default() -> Self {
+        Self {
+            sample_ratio: 1.0,
+            metrics_interval_secs: 30,
+            logs_enabled: false,
+            redactor: None,
+        }
+    }
+}
+
+impl ProviderOptions {
+    /// Set the ratio of traces to sample.
+    pub fn with_sample_ratio(mut self, sample_ratio: f64) -> Self {
+        self.sample_ratio = sample_ratio;
+        self
+    }
+
+    /// Set the interval in seconds between metric collections.
+    pub fn with_metrics_interval_secs(mut self, metrics_interval_secs: u64) -> Self {
+        self.metrics_interval_secs = metrics_interval_secs;
+        self
+    }
+
+    /// Set whether a logger provider is created for OpenTelemetry logs.
+    pub fn with_logs_enabled(mut self, logs_enabled: bool) -> Self {
+        self.logs_enabled = logs_enabled;
+        self
+    }
+
+    /// Set the redactor applied to span attributes and log records.
+    pub fn with_redactor(mut self, redactor: Option<Arc<dyn Redact>>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+}
+
+/// The providers created by [`init_providers`].
+#[derive(Debug)]
+pub struct Providers {
+    /// The tracer provider, also set as the global one.
+    pub tracer_provider: SdkTracerProvider,
+    /// The meter provider, also set as the global one.
+    pub meter_provider: SdkMeterProvider,
+    /// The logger provider, when OpenTelemetry logs are enabled.
+    pub logger_provider: Option<SdkLoggerProvider>,
+    /// The redactor the tracer provider was created with, which the logs
+    /// bridge of [`init_tracing_subscriber_with_options`] applies to log
+    /// records.
+    ///
+    /// [`init_tracing_subscriber_with_options`]: crate::init_tracing_subscriber_with_options
+    pub redactor: Option<Arc<dyn Redact>>,
+}
+
+/// Initialize the tracer, meter and (optionally) logger providers.
+///
+/// # Errors
+///
+/// Returns an error if an exporter cannot be built.
+///
+/// # Examples
+///
+/// ```rust
+/// use tracing_opentelemetry_extra::{ProviderOptions, get_resource, init_providers};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let resource = get_resource("my-service", &[]);
+///     let providers = init_providers(
+///         &resource,
+///         &ProviderOptions::default()
+///             .with_sample_ratio(0.5)
+///             .with_logs_enabled(true),
+///     )?;
+///     Ok(())
+/// }
+/// ```
+pub fn init_providers(resource: &Resource, options: &ProviderOptions) -> Result<Providers> {
+    let tracer_provider =
+        build_tracer_provider(resource, options.sample_ratio, options.redactor.clone())?;
+    let meter_provider = init_meter_provider(resource, options.metrics_interval_secs)?;
+    let logger_provider = if options.logs_enabled {
+        Some(init_logger_provider(resource)?)
+    } else {
+        None
+    };
+    Ok(Providers {
+        tracer_provider,
+        meter_provider,
+        logger_provider,
+        redactor: options.redactor.clone(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
@@ -0,0 +1,339 @@
+//! Redaction of span and log attributes before export.
+//!
+//! A [`Redact`] implementation decides which attribute values must not leave
+//! the process. [`RedactingSpanProcessor`] wraps the processor that feeds the
+//! span exporter and applies it to every finished span, and
+//! [`RedactingLoggerProvider`] applies it to log records as their body and
+//! attributes are written.
+use opentelemetry::{
+    Context, InstrumentationScope, Key, KeyValue, SpanId, TraceFlags, TraceId, Value,
+    logs::{AnyValue, LogRecord, Logger, LoggerProvider, Severity},
+};
+use opentelemetry_sdk::{
+    Resource,
+    error::OTelSdkResult,
+    trace::{Span, SpanData, SpanProcessor},
+};
+use std::{
+    borrow::Cow,
+    fmt::Debug,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+/// Decides which attribute values are replaced before export.
+pub trait Redact: Debug + Send + Sync {
+    /// Returns the replacement for `value`, or `None` to keep it.
+    ///
+    /// `key` is the attribute key, or `message` for span event names and log
+    /// bodies. Numbers and booleans are passed in their string form.
+    fn redact(&self, key: &str, value: &str) -> Option<String>;
+}
+
+fn redact_value(redactor: &dyn Redact, key: &str, value: &Value) -> Option<Value> {
+    let redacted = match value {
+        Value::String(s) => redactor.redact(key, s.as_str()),
+        Value::I64(i) => redactor.redact(key, &i.to_string()),
+        Value::F64(f) => redactor.redact(key, &f.to_string()),
+        Value::Bool(b) => redactor.redact(key, &b.to_string()),
+        _ => None,
+    };
+    redacted.map(Value::from)
+}
+
+fn redact_any_value(redactor: &dyn Redact, key: &str, value: &AnyValue) -> Option<AnyValue> {
+    let redacted = match value {
+        AnyValue::String(s) => redactor.redact(key, s.as_str()),
+        AnyValue::Int(i) => redactor.redact(key, &i.to_string()),
+        AnyValue::Double(f) => redactor.redact(key, &f.to_string()),
+        AnyValue::Boolean(b) => redactor.redact(key, &b.to_string()),
+        _ => None,
+    };
+    redacted.map(AnyValue::from)
+}
+
+fn redact_attributes(redactor: &dyn Redact, attributes: &mut [KeyValue]) {
+    for kv in attributes {
+        if let Some(value) = redact_value(redactor, kv.key.as_str(), &kv.value) {
+            kv.value = value;
+        }
+    }
+}
+
+/// A span processor that redacts span and event attributes before handing
+/// finished spans to the wrapped processor.
+#[derive(Debug)]
+pub struct RedactingSpanProcessor<P> {
+    inner: P,
+    redactor: Arc<dyn Redact>,
+}
+
+impl<P> RedactingSpanProcessor<P> {
+    /// Wrap `inner`, redacting spans with `redactor`.
+    pub fn new(inner: P, redactor: Arc<dyn Redact>) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+impl<P: SpanProcessor> SpanProcessor for RedactingSpanProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx);
+    }
+
+    fn on_end(&self, mut span: SpanData) {
+        let redactor = self.redactor.as_ref();
+        redact_attributes(redactor, &mut span.attributes);
+        for event in &mut span.events.events {
+            if let Some(name) = redactor.redact("message", &event.name) {
+                event.name = name.into();
+            }
+            redact_attributes(redactor, &mut event.attributes);
+        }
+        self.inner.on_end(span);
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
+
+/// A logger provider whose loggers redact the body and attributes of log
+/// records as they are written, before the record reaches any processor.
+///
+/// Wrap the provider given to the logs bridge, e.g.
+/// `OpenTelemetryTracingBridge::new(&RedactingLoggerProvider::new(provider, redactor))`.
+#[derive(Clone, Debug)]
+pub struct RedactingLoggerProvider<P> {
+    inner: P,
+    redactor: Arc<dyn Redact>,
+}
+
+impl<P> RedactingLoggerProvider<P> {
+    /// Wrap `inner`, redacting log records with `redactor`.
+    pub fn new(inner: P, redactor: Arc<dyn Redact>) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+impl<P: LoggerProvider> LoggerProvider for RedactingLoggerProvider<P> {
+    type Logger = RedactingLogger<P::Logger>;
+
+    fn logger_with_scope(&self, scope: InstrumentationScope) -> Self::Logger {
+        RedactingLogger {
+            inner: self.inner.logger_with_scope(scope),
+            redactor: self.redactor.clone(),
+        }
+    }
+}
+
+/// The logger of a [`RedactingLoggerProvider`].
+#[derive(Debug)]
+pub struct RedactingLogger<L> {
+    inner: L,
+    redactor: Arc<dyn Redact>,
+}
+
+impl<L: Logger> Logger for RedactingLogger<L> {
+    type LogRecord = RedactingLogRecord<L::LogRecord>;
+
+    fn create_log_record(&self) -> Self::LogRecord {
+        RedactingLogRecord {
+            inner: self.inner.create_log_record(),
+            redactor: self.redactor.clone(),
+        }
+    }
+
+    fn emit(&self, record: Self::LogRecord) {
+        self.inner.emit(record.inner);
+    }
+}
+
+/// A log record of a [`RedactingLogger`], redacting its body and attributes
+/// as they are set.
+#[derive(Debug)]
+pub struct RedactingLogRecord<R> {
+    inner: R,
+    redactor: Arc<dyn Redact>,
+}
+
+impl<R: LogRecord> LogRecord for RedactingLogRecord<R> {
+    fn set_event_name(&mut self, name: &'static str) {
+        self.inner.set_event_name(name);
+    }
+
+    fn set_target<T>(&mut self, target: T)
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.inner.set_target(target);
+    }
+
+    fn set_timestamp(&mut self, timestamp: SystemTime) {
+        self.inner.set_timestamp(timestamp);
+    }
+
+    fn set_observed_timestamp(&mut self, timestamp: SystemTime) {
+        self.inner.set_observed_timestamp(timestamp);
+    }
+
+    fn set_severity_text(&mut self, text: &'static str) {
+        self.inner.set_severity_text(text);
+    }
+
+    fn set_severity_number(&mut self, number: Severity) {
+        self.inner.set_severity_number(number);
+    }
+
+    fn set_body(&mut self, body: AnyValue) {
+        let body = redact_any_value(self.redactor.as_ref(), "message", &body).unwrap_or(body);
+        self.inner.set_body(body);
+    }
+
+    fn add_attributes<I, K, V>(&mut self, attributes: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<Key>,
+        V: Into<AnyValue>,
+    {
+        for (key, value) in attributes {
+            self.add_attribute(key, value);
+        }
+    }
+
+    fn add_attribute<K, V>(&mut self, key: K, value: V)
+    where
+        K: Into<Key>,
+        V: Into<AnyValue>,
+    {
+        let key = key.into();
+        let value = value.into();
+        let value = redact_any_value(self.redactor.as_ref(), key.as_str(), &value).unwrap_or(value);
+        self.inner.add_attribute(key, value);
+    }
+
+    fn set_trace_context(
+        &mut self,
+        trace_id: TraceId,
+        span_id: SpanId,
+        trace_flags: Option<TraceFlags>,
+    ) {
+        self.inner.set_trace_context(trace_id, span_id, trace_flags);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::{Span as _, Tracer as _, TracerProvider as _};
+    use opentelemetry_sdk::{
+        logs::{LogProcessor, SdkLogRecord, SdkLoggerProvider},
+        trace::SdkTracerProvider,
+    };
+    use std::sync::Mutex;
+
+    /// Masks `password` and any value containing `4111`.
+    #[derive(Debug)]
+    struct TestRedactor;
+
+    impl Redact for TestRedactor {
+        fn redact(&self, key: &str, value: &str) -> Option<String> {
+            (key == "password" || value.contains("4111")).then(|| "[REDACTED]".to_string())
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct Capture<T>(Arc<Mutex<Vec<T>>>);
+
+    impl SpanProcessor for Capture<SpanData> {
+        fn on_start(&self, _span: &mut Span, _cx: &Context) {}
+
+        fn on_end(&self, span: SpanData) {
+            self.0.lock().unwrap().push(span);
+        }
+
+        fn force_flush(&self) -> OTelSdkResult {
+            Ok(())
+        }
+
+        fn shutdown_with_timeout(&self, _timeout: Duration) -> OTelSdkResult {
+            Ok(())
+        }
+    }
+
+    impl LogProcessor for Capture<SdkLogRecord> {
+        fn emit(&self, record: &mut SdkLogRecord, _scope: &InstrumentationScope) {
+            self.0.lock().unwrap().push(record.clone());
+        }
+
+        fn force_flush(&self) -> OTelSdkResult {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_redacts_span_attributes_and_events() {
+        let spans = Arc::new(Mutex::new(Vec::new()));
+        let processor = RedactingSpanProcessor::new(Capture(spans.clone()), Arc::new(TestRedactor));
+        let provider = SdkTracerProvider::builder()
+            .with_span_processor(processor)
+            .build();
+
+        let mut span = provider.tracer("test").start("login");
+        span.set_attribute(KeyValue::new("password", "hunter2"));
+        span.set_attribute(KeyValue::new("user", "alice"));
+        span.add_event(
+            "card 4111 1111 1111 1111",
+            vec![KeyValue::new("card", 4111)],
+        );
+        span.end();
+
+        let spans = spans.lock().unwrap();
+        let attributes = &spans[0].attributes;
+        assert_eq!(attributes[0].value, Value::from("[REDACTED]"));
+        assert_eq!(attributes[1].value, Value::from("alice"));
+        let event = &spans[0].events.events[0];
+        assert_eq!(event.name, "[REDACTED]");
+        assert_eq!(event.attributes[0].value, Value::from("[REDACTED]"));
+    }
+
+    #[test]
+    fn test_redacts_log_body_and_attributes() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let provider = SdkLoggerProvider::builder()
+            .with_log_processor(Capture(records.clone()))
+            .build();
+        let provider = RedactingLoggerProvider::new(provider, Arc::new(TestRedactor));
+
+        let logger = provider.logger("test");
+        let mut record = logger.create_log_record();
+        record.set_severity_number(Severity::Info);
+        record.set_body("paid with 4111 1111 1111 1111".into());
+        record.add_attribute("password", "hunter2");
+        record.add_attribute("user", "alice");
+        logger.emit(record);
+
+        let records = records.lock().unwrap();
+        let record = &records[0];
+        assert_eq!(record.severity_number(), Some(Severity::Info));
+        assert_eq!(record.body(), Some(&AnyValue::from("[REDACTED]")));
+        let attributes: Vec<_> = record
+            .attributes_iter()
+            .map(|(key, value)| (key.as_str().to_string(), value.clone()))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("password".to_string(), AnyValue::from("[REDACTED]")),
+                ("user".to_string(), AnyValue::from("alice")),
+            ]
+        );
+    }
+}
@@ -1,16 +1,27 @@
-use crate::guard::OtelGuard;
+use crate::{
+    guard::OtelGuard,
+    otel::Providers,
+    redact::{Redact, RedactingLoggerProvider},
+};
 use anyhow::Result;
 use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
 use opentelemetry_sdk::{
     logs::SdkLoggerProvider, metrics::SdkMeterProvider, trace::SdkTracerProvider,
 };
+use std::sync::Arc;
 use tracing::Level;
 use tracing_subscriber::{
-    EnvFilter, Layer, Registry, layer::SubscriberExt, util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
+    layer::{Filter, SubscriberExt},
+    util::SubscriberInitExt,
 };
 
 pub type BoxLayer = Box<dyn Layer<Registry> + Sync + Send>;
 
+/// A boxed per-layer filter, e.g. an [`EnvFilter`] combined with other filters
+/// through [`FilterExt`](tracing_subscriber::filter::FilterExt).
+pub type BoxFilter = Box<dyn Filter<Registry> + Sync + Send>;
+
 /// Creates an environment filter for tracing based on the given level.
 ///
 /// This function attempts to create a filter from environment variables first,
@@ -32,35 +43,186 @@ pub fn init_env_filter(level: &Level) -> EnvFilter {
     EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level.to_string()))
 }
 
-// Initialize tracing-subscriber and return OtelGuard for opentelemetry-related termination processing
-// https://github.com/tokio-rs/tracing-opentelemetry/blob/6b4da4a08b4f6481a2feb2974f06c67765cd44c6/examples/opentelemetry-otlp.rs#L76
-pub fn init_tracing_subscriber(
+/// Settings of [`init_tracing_subscriber_with_options`].
+#[derive(Default)]
+pub struct SubscriberOptions {
+    /// Filter applied to every layer, on top of their own filters.
+    pub filter: Option<EnvFilter>,
+    /// Filter for the traces and metrics layers.
+    pub traces_filter: Option<EnvFilter>,
+    /// Filter for the OpenTelemetry logs bridge layer.
+    pub logs_filter: Option<BoxFilter>,
+    /// Wraps every layer, including the OpenTelemetry ones, before the
+    /// subscriber is installed.
+    pub wrapper: Option<Box<dyn FnOnce(Vec<BoxLayer>) -> BoxLayer>>,
+}
+
+impl SubscriberOptions {
+    /// Set the filter applied to every layer, on top of their own filters.
+    pub fn with_filter(mut self, filter: EnvFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Set the filter for the traces and metrics layers.
+    pub fn with_traces_filter(mut self, filter: EnvFilter) -> Self {
+        self.traces_filter = Some(filter);
+        self
+    }
+
+    /// Set the filter for the OpenTelemetry logs bridge layer.
+    pub fn with_logs_filter(mut self, filter: BoxFilter) -> Self {
+        self.logs_filter = Some(filter);
+        self
+    }
+
+    /// Pass every layer through `wrap` before the subscriber is installed.
+    ///
+    /// This lets a single layer see every event before and after all outputs,
+    /// e.g. `|layers| layers.and_then(my_layer).boxed()`.
+    pub fn with_wrapper<F>(mut self, wrap: F) -> Self
+    where
+        F: FnOnce(Vec<BoxLayer>) -> BoxLayer + 'static,
+    {
+        self.wrapper = Some(Box::new(wrap));
+        self
+    }
+}
+
+impl std::fmt::Debug for SubscriberOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubscriberOptions")
+            .field("filter", &self.filter)
+            .field("traces_filter", &self.traces_filter)
+            .field(
+                "logs_filter",
+                &self.logs_filter.as_ref().map(|_| "BoxFilter"),
+            )
+            .field("wrapper", &self.wrapper.as_ref().map(|_| "FnOnce"))
+            .finish()
+    }
+}
+
+/// Wrap a layer with an optional per-layer filter.
+fn with_optional_filter<L>(layer: L, filter: Option<BoxFilter>) -> BoxLayer
+where
+    L: Layer<Registry> + Send + Sync + 'static,
+{
+    match filter {
+        Some(filter) => layer.with_filter(filter).boxed(),
+        None => layer.boxed(),
+    }
+}
+
+/// Build the metrics, traces and (optionally) logs bridge layers.
+fn build_otel_layers(
     name: &str,
-    env_filter: EnvFilter,
-    mut layers: Vec<BoxLayer>,
-    tracer_provider: SdkTracerProvider,
-    meter_provider: SdkMeterProvider,
-    logger_provider: Option<SdkLoggerProvider>,
-) -> Result<OtelGuard> {
+    tracer_provider: &SdkTracerProvider,
+    meter_provider: &SdkMeterProvider,
+    logger_provider: Option<&SdkLoggerProvider>,
+    redactor: Option<Arc<dyn Redact>>,
+    traces_filter: Option<EnvFilter>,
+    logs_filter: Option<BoxFilter>,
+) -> Vec<BoxLayer> {
     use opentelemetry::trace::TracerProvider as _;
     // Set up telemetry layer with tracer
     let tracer = tracer_provider.tracer(name.to_string());
     let metrics_layer = tracing_opentelemetry::MetricsLayer::new(meter_provider.clone());
     let otel_layer = tracing_opentelemetry::OpenTelemetryLayer::new(tracer);
 
-    let mut extended_layers: Vec<BoxLayer> = vec![Box::new(metrics_layer), Box::new(otel_layer)];
+    let mut layers: Vec<BoxLayer> = match traces_filter {
+        Some(filter) => vec![
+            metrics_layer
+                .and_then(otel_layer)
+                .with_filter(filter)
+                .boxed(),
+        ],
+        None => vec![Box::new(metrics_layer), Box::new(otel_layer)],
+    };
 
     // Add OpenTelemetry logs bridge layer if logger_provider is provided
-    if let Some(ref logger_provider) = logger_provider {
-        let otel_logs_layer = OpenTelemetryTracingBridge::new(logger_provider);
-        extended_layers.push(Box::new(otel_logs_layer));
+    if let Some(logger_provider) = logger_provider {
+        let otel_logs_layer = match redactor {
+            Some(redactor) => {
+                let provider = RedactingLoggerProvider::new(logger_provider.clone(), redactor);
+                with_optional_filter(OpenTelemetryTracingBridge::new(&provider), logs_filter)
+            }
+            None => with_optional_filter(
+                OpenTelemetryTracingBridge::new(logger_provider),
+                logs_filter,
+            ),
+        };
+        layers.push(otel_logs_layer);
     }
 
-    layers.extend(extended_layers);
+    layers
+}
+
+// Initialize tracing-subscriber and return OtelGuard for opentelemetry-related termination processing
+// https://github.com/tokio-rs/tracing-opentelemetry/blob/6b4da4a08b4f6481a2feb2974f06c67765cd44c6/examples/opentelemetry-otlp.rs#L76
+pub fn init_tracing_subscriber(
+    name: &str,
+    env_filter: EnvFilter,
+    layers: Vec<BoxLayer>,
+    tracer_provider: SdkTracerProvider,
+    meter_provider: SdkMeterProvider,
+    logger_provider: Option<SdkLoggerProvider>,
+) -> Result<OtelGuard> {
+    init_tracing_subscriber_with_options(
+        name,
+        layers,
+        Providers {
+            tracer_provider,
+            meter_provider,
+            logger_provider,
+            redactor: None,
+        },
+        SubscriberOptions::default().with_filter(env_filter),
+    )
+}
+
+/// Initialize tracing-subscriber with the OpenTelemetry layers of `providers`.
+///
+/// Layers in `layers` may carry their own filter (see [`Layer::with_filter`]),
+/// and the OpenTelemetry layers are filtered by `options`. This lets, for
+/// example, a file layer record `DEBUG` while the console stays at `INFO` and
+/// the logs bridge only exports `WARN` and above. The filter of
+/// [`SubscriberOptions::with_filter`] applies to every layer.
+pub fn init_tracing_subscriber_with_options(
+    name: &str,
+    mut layers: Vec<BoxLayer>,
+    providers: Providers,
+    options: SubscriberOptions,
+) -> Result<OtelGuard> {
+    let Providers {
+        tracer_provider,
+        meter_provider,
+        logger_provider,
+        redactor,
+    } = providers;
+    let SubscriberOptions {
+        filter,
+        traces_filter,
+        logs_filter,
+        wrapper,
+    } = options;
+    layers.extend(build_otel_layers(
+        name,
+        &tracer_provider,
+        &meter_provider,
+        logger_provider.as_ref(),
+        redactor,
+        traces_filter,
+        logs_filter,
+    ));
 
+    let layers = match wrapper {
+        Some(wrap) => wrap(layers),
+        None => layers.boxed(),
+    };
     tracing_subscriber::registry()
         .with(layers)
-        .with(env_filter)
+        .with(filter)
         .init();
     Ok(OtelGuard::new(
         Some(tracer_provider),
@@ -1,7 +1,7 @@
 //! OpenTelemetry trace context management.
 
-use crate::http::propagation::extract_context_from_headers;
-use opentelemetry::{SpanId, TraceId};
+use crate::http::propagation::{Extractor, extract_context, extract_context_from_headers};
+use opentelemetry::{Context, SpanId, TraceId};
 use tracing::warn;
 
 /// The key for the trace id in the span attributes.
@@ -31,9 +31,20 @@ pub fn current_span_id() -> SpanId {
 
 /// Set the parent span for the current span and record the trace id.
 pub fn set_otel_parent(headers: &http::HeaderMap, span: &tracing::Span) {
+    set_otel_parent_context(extract_context_from_headers(headers), span);
+}
+
+/// Set the parent span from any carrier, e.g. message headers, and record the trace id.
+pub fn set_otel_parent_from_carrier<E>(carrier: &E, span: &tracing::Span)
+where
+    E: Extractor,
+{
+    set_otel_parent_context(extract_context(carrier), span);
+}
+
+fn set_otel_parent_context(remote_context: Context, span: &tracing::Span) {
     use opentelemetry::trace::TraceContextExt as _;
     use tracing_opentelemetry::OpenTelemetrySpanExt as _;
-    let remote_context = extract_context_from_headers(headers);
     if let Err(e) = span.set_parent(remote_context) {
         warn!("Failed to set parent on span: {:?}", e);
     }
@@ -110,6 +121,25 @@ mod tests {
         assert_eq!(trace_id, expected_trace_id);
     }
 
+    #[tokio::test]
+    async fn test_set_otel_parent_from_carrier() {
+        init_tracing();
+        let expected_trace_id = "4bf92f3577b34da6a3ce929d0e0e4736";
+        let headers = vec![(
+            "traceparent".to_string(),
+            format!("00-{expected_trace_id}-00f067aa0ba902b7-01").into_bytes(),
+        )];
+
+        let span = create_span();
+        set_otel_parent_from_carrier(
+            &crate::http::propagation::ByteHeaderExtractor(&headers),
+            &span,
+        );
+
+        let trace_id = span.context().span().span_context().trace_id().to_string();
+        assert_eq!(trace_id, expected_trace_id);
+    }
+
     #[tokio::test]
     async fn test_current_trace_id() {
         init_tracing();
@@ -0,0 +1,230 @@
+//! Recording of Rust errors on spans as OpenTelemetry exceptions.
+//!
+//! Following the [OpenTelemetry exception semantic conventions](https://opentelemetry.io/docs/specs/semconv/exceptions/exceptions-spans/),
+//! an error is recorded as an `exception` event with `exception.type`,
+//! `exception.message` and `exception.stacktrace` attributes, and the span gets
+//! an `ERROR` status and an `error.type` attribute.
+//!
+//! Rust errors have no stack trace, so `exception.stacktrace` holds the chain
+//! of causes, and the backtrace of an [`anyhow::Error`] when one was captured.
+//!
+//! # Example
+//!
+//! ```rust
+//! use tracing_otel_extra::extract::error::record_error;
+//!
+//! let span = tracing::info_span!("read_config");
+//! if let Err(error) = std::fs::read_to_string("/missing/config.toml") {
+//!     record_error(&span, &error);
+//! }
+//! ```
+
+use super::span_ext::OtelSpanExt;
+use opentelemetry::KeyValue;
+use std::{error::Error, fmt::Write as _};
+
+/// Name of the span event an error is recorded as.
+pub const EXCEPTION_EVENT: &str = "exception";
+
+/// Record `error` on `span`, using its Rust type name as `exception.type` and `error.type`.
+///
+/// The type name is the static type of `error`, so a trait object such as
+/// `Box<dyn Error>` or `&dyn Error` is recorded as `dyn core::error::Error`.
+/// Use [`record_error_with_type`] to name the type of such errors.
+pub fn record_error<E>(span: &tracing::Span, error: &E)
+where
+    E: Error + ?Sized,
+{
+    record_error_with_type(span, error, std::any::type_name::<E>());
+}
+
+/// Record an [`anyhow::Error`] on `span`.
+///
+/// `anyhow::Error` erases the type of the error it wraps, so `exception.type`
+/// and `error.type` are `anyhow::Error`. The stack trace holds the chain of
+/// contexts and causes, followed by the backtrace if one was captured.
+pub fn record_anyhow_error(span: &tracing::Span, error: &anyhow::Error) {
+    record_exception(
+        span,
+        std::any::type_name::<anyhow::Error>(),
+        error.to_string(),
+        format!("{error:?}"),
+    );
+}
+
+/// Record `error` on `span` with an explicit `error_type`, e.g. a short,
+/// low-cardinality name such as `timeout` or `db_unavailable`.
+pub fn record_error_with_type<E>(span: &tracing::Span, error: &E, error_type: &str)
+where
+    E: Error + ?Sized,
+{
+    record_exception(span, error_type, error.to_string(), cause_chain(error));
+}
+
+/// Returns the attributes of the `exception` event for an error.
+pub fn exception_attributes(
+    error_type: &str,
+    message: impl Into<String>,
+    stacktrace: impl Into<String>,
+) -> Vec<KeyValue> {
+    vec![
+        KeyValue::new("exception.type", error_type.to_string()),
+        KeyValue::new("exception.message", message.into()),
+        KeyValue::new("exception.stacktrace", stacktrace.into()),
+    ]
+}
+
+/// Formats an error and its sources the way `anyhow` does:
+///
+/// ```text
+/// failed to load config
+///
+/// Caused by:
+///     0: failed to read /etc/app.toml
+///     1: No such file or directory (os error 2)
+/// ```
+pub fn cause_chain<E>(error: &E) -> String
+where
+    E: Error + ?Sized,
+{
+    let mut chain = error.to_string();
+    let mut source = error.source();
+    if source.is_some() {
+        chain.push_str("\n\nCaused by:");
+    }
+    let mut index = 0;
+    while let Some(cause) = source {
+        let _ = write!(chain, "\n    {index}: {cause}");
+        source = cause.source();
+        index += 1;
+    }
+    chain
+}
+
+fn record_exception(span: &tracing::Span, error_type: &str, message: String, stacktrace: String) {
+    span.add_event(
+        EXCEPTION_EVENT,
+        exception_attributes(error_type, message.clone(), stacktrace),
+    );
+    span.set_attribute("error.type", error_type.to_string());
+    span.set_error(message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::test_support::{attribute, export};
+    use opentelemetry::{Value, trace::Status};
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Id, Record};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt as _};
+
+    #[derive(Debug)]
+    struct ReadError(std::io::Error);
+
+    impl std::fmt::Display for ReadError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("failed to read config")
+        }
+    }
+
+    impl Error for ReadError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    fn read_error() -> ReadError {
+        ReadError(std::io::Error::new(std::io::ErrorKind::NotFound, "missing"))
+    }
+
+    /// Collects the values recorded in `otel.status_code`.
+    #[derive(Clone, Default)]
+    struct StatusCodes(Arc<Mutex<Vec<String>>>);
+
+    impl tracing::field::Visit for StatusCodes {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            if field.name() == "otel.status_code" {
+                self.0.lock().unwrap().push(value.to_string());
+            }
+        }
+
+        fn record_debug(&mut self, _field: &tracing::field::Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for StatusCodes {
+        fn on_record(&self, _id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            values.record(&mut self.clone());
+        }
+    }
+
+    #[test]
+    fn test_cause_chain() {
+        assert_eq!(
+            cause_chain(&read_error()),
+            "failed to read config\n\nCaused by:\n    0: missing"
+        );
+        let error = std::io::Error::other("plain");
+        assert_eq!(cause_chain(&error), "plain");
+    }
+
+    #[test]
+    fn test_record_error() {
+        let spans = export(|| record_error(&tracing::info_span!("operation"), &read_error()));
+        let span = &spans[0];
+        let error_type = std::any::type_name::<ReadError>();
+
+        assert_eq!(span.status, Status::error("failed to read config"));
+        assert_eq!(
+            attribute(&span.attributes, "error.type"),
+            Some(&Value::from(error_type))
+        );
+        let event = &span.events[0];
+        assert_eq!(event.name, EXCEPTION_EVENT);
+        assert_eq!(
+            event.attributes,
+            exception_attributes(
+                error_type,
+                "failed to read config",
+                "failed to read config\n\nCaused by:\n    0: missing"
+            )
+        );
+    }
+
+    #[test]
+    fn test_record_error_sets_status_code_field() {
+        let status_codes = StatusCodes::default();
+        let subscriber = tracing_subscriber::registry().with(status_codes.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!(
+                "operation",
+                otel.status_code = tracing::field::Empty,
+                otel.status_description = tracing::field::Empty,
+            );
+            record_error(&span, &read_error());
+        });
+        assert_eq!(*status_codes.0.lock().unwrap(), vec!["ERROR".to_string()]);
+    }
+
+    #[test]
+    fn test_record_anyhow_error() {
+        let error = anyhow::Error::new(read_error()).context("failed to start");
+        let spans = export(|| record_anyhow_error(&tracing::info_span!("operation"), &error));
+        let span = &spans[0];
+
+        assert_eq!(span.status, Status::error("failed to start"));
+        assert_eq!(
+            attribute(&span.attributes, "error.type"),
+            Some(&Value::from("anyhow::Error"))
+        );
+        let stacktrace = attribute(&span.events[0].attributes, "exception.stacktrace")
+            .unwrap()
+            .as_str();
+        assert!(
+            stacktrace.starts_with(
+                "failed to start\n\nCaused by:\n    0: failed to read config\n    1: missing"
+            ),
+            "{stacktrace}"
+        );
+    }
+}
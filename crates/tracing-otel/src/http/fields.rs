@@ -1,12 +1,174 @@
 //! HTTP request field extraction utilities.
 
 use http::{HeaderName, Request};
+use std::borrow::Cow;
 
 pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
 pub const REQUEST_ID: HeaderName = HeaderName::from_static("request-id");
 pub const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
 pub const FORWARDED: HeaderName = HeaderName::from_static("forwarded");
 
+/// Query parameters whose values are redacted unless configured otherwise.
+///
+/// These are the keys listed by the [OpenTelemetry URL semantic conventions](https://opentelemetry.io/docs/specs/semconv/registry/attributes/url/),
+/// plus common token and AWS SigV4 parameters.
+pub const DEFAULT_SENSITIVE_QUERY_PARAMS: &[&str] = &[
+    "AWSAccessKeyId",
+    "Signature",
+    "sig",
+    "X-Goog-Signature",
+    "X-Amz-Signature",
+    "X-Amz-Credential",
+    "X-Amz-Security-Token",
+    "token",
+    "access_token",
+];
+
+/// Replacement for the values of sensitive query parameters, as recommended by semconv.
+pub const DEFAULT_QUERY_MASK: &str = "REDACTED";
+
+/// Which query parameter values are redacted in `url.query` and `url.full`.
+///
+/// Keys are matched case-insensitively and only the value is replaced, so
+/// `?token=abc&page=2` becomes `?token=REDACTED&page=2`.
+///
+/// # Example
+///
+/// ```rust
+/// use tracing_otel_extra::extract::fields::QueryRedaction;
+///
+/// let redaction = QueryRedaction::new().with_param("session");
+/// assert_eq!(redaction.redact("session=abc&page=2"), "session=REDACTED&page=2");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryRedaction {
+    sensitive: Vec<String>,
+    mask: String,
+}
+
+impl Default for QueryRedaction {
+    fn default() -> Self {
+        Self {
+            sensitive: DEFAULT_SENSITIVE_QUERY_PARAMS
+                .iter()
+                .map(|key| key.to_string())
+                .collect(),
+            mask: DEFAULT_QUERY_MASK.to_string(),
+        }
+    }
+}
+
+impl QueryRedaction {
+    /// Create a redaction of [`DEFAULT_SENSITIVE_QUERY_PARAMS`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a redaction that records queries as is.
+    pub fn disabled() -> Self {
+        Self::default().with_sensitive_params(Vec::<String>::new())
+    }
+
+    /// Add a sensitive query parameter.
+    pub fn with_param(mut self, key: impl Into<String>) -> Self {
+        self.sensitive.push(key.into());
+        self
+    }
+
+    /// Replace the list of sensitive query parameters.
+    pub fn with_sensitive_params<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.sensitive = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the replacement for the values of sensitive query parameters.
+    pub fn with_mask(mut self, mask: impl Into<String>) -> Self {
+        self.mask = mask.into();
+        self
+    }
+
+    /// Whether values of `key` are redacted.
+    pub fn is_sensitive(&self, key: &str) -> bool {
+        self.sensitive
+            .iter()
+            .any(|sensitive| sensitive.eq_ignore_ascii_case(key))
+    }
+
+    /// Returns `query` with the values of sensitive parameters replaced.
+    ///
+    /// Keys are percent-decoded before matching, so `%74oken` is redacted like `token`.
+    pub fn redact<'a>(&self, query: &'a str) -> Cow<'a, str> {
+        let is_sensitive_key = |key: &str| self.is_sensitive(&percent_decode(key));
+        let is_sensitive_pair = |pair: &str| {
+            pair.split_once('=')
+                .is_some_and(|(key, _)| is_sensitive_key(key))
+        };
+        if !query.split('&').any(is_sensitive_pair) {
+            return Cow::Borrowed(query);
+        }
+        let pairs: Vec<Cow<'_, str>> = query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, _)) if is_sensitive_key(key) => {
+                    Cow::Owned(format!("{key}={}", self.mask))
+                }
+                _ => Cow::Borrowed(pair),
+            })
+            .collect();
+        Cow::Owned(pairs.join("&"))
+    }
+
+    /// Returns `url` with the values of sensitive query parameters replaced,
+    /// for `url.full` on client spans.
+    pub fn redact_url<'a>(&self, url: &'a str) -> Cow<'a, str> {
+        let (without_fragment, fragment) = match url.split_once('#') {
+            Some((without_fragment, fragment)) => (without_fragment, Some(fragment)),
+            None => (url, None),
+        };
+        let Some((base, query)) = without_fragment.split_once('?') else {
+            return Cow::Borrowed(url);
+        };
+        match self.redact(query) {
+            Cow::Borrowed(_) => Cow::Borrowed(url),
+            Cow::Owned(query) => Cow::Owned(match fragment {
+                Some(fragment) => format!("{base}?{query}#{fragment}"),
+                None => format!("{base}?{query}"),
+            }),
+        }
+    }
+}
+
+/// Percent-decodes `value`, leaving malformed escapes as they are.
+fn percent_decode(value: &str) -> Cow<'_, str> {
+    if !value.contains('%') {
+        return Cow::Borrowed(value);
+    }
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    Cow::Owned(String::from_utf8_lossy(&decoded).into_owned())
+}
+
 /// Extract the http method from the request
 pub fn extract_http_method<T>(request: &Request<T>) -> &str {
     request.method().as_str()
@@ -72,6 +234,14 @@ pub fn extract_url_query<T>(request: &http::Request<T>) -> Option<&str> {
     request.uri().query()
 }
 
+/// Extract the URL query from the request, redacting sensitive parameters.
+pub fn extract_redacted_url_query<'a, T>(
+    request: &'a http::Request<T>,
+    redaction: &QueryRedaction,
+) -> Option<Cow<'a, str>> {
+    extract_url_query(request).map(|query| redaction.redact(query))
+}
+
 /// Extract the user agent from the request headers
 pub fn extract_user_agent<T>(request: &http::Request<T>) -> Option<&str> {
     extract_field_from_headers(request.headers(), &http::header::USER_AGENT)
@@ -194,6 +364,55 @@ mod tests {
         assert_eq!(query, Some("kind=test"));
     }
 
+    #[test]
+    fn test_redact_url_query() {
+        let request = Request::builder()
+            .uri("/download?X-Amz-Signature=abc&token=t0k&page=2&flag")
+            .body(())
+            .unwrap();
+        let query = extract_redacted_url_query(&request, &QueryRedaction::default());
+        assert_eq!(
+            query.as_deref(),
+            Some("X-Amz-Signature=REDACTED&token=REDACTED&page=2&flag")
+        );
+        let query = extract_redacted_url_query(&request, &QueryRedaction::disabled());
+        assert!(matches!(query, Some(Cow::Borrowed(_))));
+
+        let redaction = QueryRedaction::new().with_mask("***");
+        assert!(matches!(
+            redaction.redact("page=2"),
+            Cow::Borrowed("page=2")
+        ));
+        assert_eq!(redaction.redact("SIG=1"), "SIG=***");
+    }
+
+    #[test]
+    fn test_redact_percent_encoded_keys() {
+        let redaction = QueryRedaction::default();
+        assert_eq!(
+            redaction.redact("%74oken=t0k&X%2DAmz%2DSignature=abc&X%2damz%2dsignature=def&page=2"),
+            "%74oken=REDACTED&X%2DAmz%2DSignature=REDACTED&X%2damz%2dsignature=REDACTED&page=2"
+        );
+        assert_eq!(redaction.redact("%zztoken=1&100%=2"), "%zztoken=1&100%=2");
+        assert_eq!(
+            redaction.redact_url("https://example.com/a?%73ig=1"),
+            "https://example.com/a?%73ig=REDACTED"
+        );
+    }
+
+    #[test]
+    fn test_redact_url_full() {
+        let redaction = QueryRedaction::default();
+        assert_eq!(
+            redaction.redact_url("https://example.com/a?sig=1&b=2#top"),
+            "https://example.com/a?sig=REDACTED&b=2#top"
+        );
+        assert_eq!(
+            redaction.redact_url("https://example.com/a#b?sig=1"),
+            "https://example.com/a#b?sig=1"
+        );
+    }
+
     #[test]
     fn test_extract_url_scheme_from_x_forwarded_proto() {
         let request = Request::builder()
@@ -0,0 +1,68 @@
+//! The response future shared by the client and server tower layers.
+
+use crate::http::{headers::HeaderCapture, span_ext::OtelSpanExt as _};
+use http::{Response, StatusCode};
+use pin_project_lite::pin_project;
+use std::{
+    fmt::Display,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+use tracing::Span;
+
+pin_project! {
+    /// The response future of [`OtelClientService`](super::client::OtelClientService)
+    /// and [`OtelServerService`](super::server::OtelServerService).
+    #[derive(Debug)]
+    pub struct ResponseFuture<F> {
+        #[pin]
+        inner: F,
+        span: Span,
+        headers: HeaderCapture,
+        record_status: fn(&Span, StatusCode),
+    }
+}
+
+impl<F> ResponseFuture<F> {
+    /// Wrap `inner`, recording the response status on `span` with
+    /// `record_status` and the response headers allow-listed in `headers`.
+    pub(crate) fn new(
+        inner: F,
+        span: Span,
+        headers: HeaderCapture,
+        record_status: fn(&Span, StatusCode),
+    ) -> Self {
+        Self {
+            inner,
+            span,
+            headers,
+            record_status,
+        }
+    }
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+    E: Display,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _entered = this.span.enter();
+        let result = ready!(this.inner.poll(cx));
+        match &result {
+            Ok(response) => {
+                (this.record_status)(this.span, response.status());
+                this.headers.record_response(this.span, response.headers());
+            }
+            Err(error) => {
+                this.span.record("error.type", "_OTHER");
+                this.span.set_error(error);
+            }
+        }
+        Poll::Ready(result)
+    }
+}
@@ -0,0 +1,223 @@
+//! Capture of allow-listed HTTP headers as span attributes.
+//!
+//! Following the [OpenTelemetry HTTP semantic conventions](https://opentelemetry.io/docs/specs/semconv/http/http-spans/),
+//! each captured header is recorded as an `http.request.header.<name>` or
+//! `http.response.header.<name>` attribute holding all of its values. The same
+//! [`HeaderCapture`] works for server spans and outgoing client spans.
+
+use http::HeaderMap;
+use opentelemetry::{Array, KeyValue, StringValue, Value};
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+
+/// Headers whose values are masked unless configured otherwise.
+pub const DEFAULT_SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-auth-token",
+];
+
+/// Replacement for the values of sensitive headers.
+pub const DEFAULT_HEADER_MASK: &str = "[REDACTED]";
+
+/// Which request and response headers are recorded on a span.
+///
+/// Nothing is captured by default. Header names are case-insensitive, and the
+/// values of sensitive headers are replaced with a mask.
+///
+/// # Example
+///
+/// ```rust
+/// use tracing_otel_extra::extract::headers::HeaderCapture;
+///
+/// let capture = HeaderCapture::new()
+///     .with_request_headers(["content-type", "x-tenant-id", "authorization"])
+///     .with_response_headers(["content-type"]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderCapture {
+    request: Vec<String>,
+    response: Vec<String>,
+    sensitive: Vec<String>,
+    mask: String,
+}
+
+impl Default for HeaderCapture {
+    fn default() -> Self {
+        Self {
+            request: Vec::new(),
+            response: Vec::new(),
+            sensitive: DEFAULT_SENSITIVE_HEADERS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            mask: DEFAULT_HEADER_MASK.to_string(),
+        }
+    }
+}
+
+impl HeaderCapture {
+    /// Create a capture that records no headers and masks [`DEFAULT_SENSITIVE_HEADERS`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add request headers to record.
+    pub fn with_request_headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.request.extend(headers.into_iter().map(normalize));
+        self
+    }
+
+    /// Add response headers to record.
+    pub fn with_response_headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.response.extend(headers.into_iter().map(normalize));
+        self
+    }
+
+    /// Replace the list of headers whose values are masked.
+    ///
+    /// Pass an empty list to record every allow-listed header as is.
+    pub fn with_sensitive_headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.sensitive = headers.into_iter().map(normalize).collect();
+        self
+    }
+
+    /// Set the replacement for the values of sensitive headers.
+    pub fn with_mask(mut self, mask: impl Into<String>) -> Self {
+        self.mask = mask.into();
+        self
+    }
+
+    /// Whether no header is captured.
+    pub fn is_empty(&self) -> bool {
+        self.request.is_empty() && self.response.is_empty()
+    }
+
+    /// Returns the `http.request.header.*` attributes for `headers`.
+    pub fn request_attributes(&self, headers: &HeaderMap) -> Vec<KeyValue> {
+        self.attributes("http.request.header", &self.request, headers)
+    }
+
+    /// Returns the `http.response.header.*` attributes for `headers`.
+    pub fn response_attributes(&self, headers: &HeaderMap) -> Vec<KeyValue> {
+        self.attributes("http.response.header", &self.response, headers)
+    }
+
+    /// Record the allow-listed request headers on `span`.
+    pub fn record_request(&self, span: &tracing::Span, headers: &HeaderMap) {
+        for kv in self.request_attributes(headers) {
+            span.set_attribute(kv.key, kv.value);
+        }
+    }
+
+    /// Record the allow-listed response headers on `span`.
+    pub fn record_response(&self, span: &tracing::Span, headers: &HeaderMap) {
+        for kv in self.response_attributes(headers) {
+            span.set_attribute(kv.key, kv.value);
+        }
+    }
+
+    fn attributes(&self, prefix: &str, names: &[String], headers: &HeaderMap) -> Vec<KeyValue> {
+        names
+            .iter()
+            .filter_map(|name| {
+                let values: Vec<StringValue> = headers
+                    .get_all(name.as_str())
+                    .iter()
+                    .map(|value| match self.sensitive.contains(name) {
+                        true => self.mask.clone().into(),
+                        false => String::from_utf8_lossy(value.as_bytes())
+                            .into_owned()
+                            .into(),
+                    })
+                    .collect();
+                (!values.is_empty()).then(|| {
+                    KeyValue::new(
+                        format!("{prefix}.{name}"),
+                        Value::Array(Array::String(values)),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+fn normalize(name: impl AsRef<str>) -> String {
+    name.as_ref().trim().to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    fn headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        headers.append("x-tenant-id", HeaderValue::from_static("a"));
+        headers.append("x-tenant-id", HeaderValue::from_static("b"));
+        headers.insert("authorization", HeaderValue::from_static("Bearer secret"));
+        headers
+    }
+
+    fn strings(values: &[&str]) -> Value {
+        Value::Array(Array::String(
+            values.iter().map(|v| v.to_string().into()).collect(),
+        ))
+    }
+
+    #[test]
+    fn test_request_attributes() {
+        let capture = HeaderCapture::new().with_request_headers([
+            "Content-Type",
+            "x-tenant-id",
+            "authorization",
+            "x-missing",
+        ]);
+        let attributes = capture.request_attributes(&headers());
+        assert_eq!(
+            attributes,
+            vec![
+                KeyValue::new(
+                    "http.request.header.content-type",
+                    strings(&["application/json"])
+                ),
+                KeyValue::new("http.request.header.x-tenant-id", strings(&["a", "b"])),
+                KeyValue::new(
+                    "http.request.header.authorization",
+                    strings(&["[REDACTED]"])
+                ),
+            ]
+        );
+        assert!(capture.response_attributes(&headers()).is_empty());
+    }
+
+    #[test]
+    fn test_unmasked_response_attributes() {
+        let capture = HeaderCapture::new()
+            .with_response_headers(["authorization"])
+            .with_sensitive_headers(Vec::<String>::new());
+        assert_eq!(
+            capture.response_attributes(&headers()),
+            vec![KeyValue::new(
+                "http.response.header.authorization",
+                strings(&["Bearer secret"])
+            )]
+        );
+        assert!(HeaderCapture::new().is_empty());
+    }
+}
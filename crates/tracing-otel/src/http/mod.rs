@@ -3,11 +3,75 @@
 //! This module provides utilities for extracting and managing trace context
 //! in HTTP requests and responses.
 
+#[cfg(feature = "tower")]
+pub mod client;
 #[cfg(feature = "context")]
 pub mod context;
+#[cfg(feature = "context")]
+pub mod error;
 #[cfg(feature = "fields")]
 pub mod fields;
+#[cfg(feature = "tower")]
+mod future;
+#[cfg(feature = "http")]
+pub mod headers;
 #[cfg(feature = "http")]
 pub mod propagation;
+#[cfg(feature = "tower")]
+pub mod server;
 #[cfg(feature = "span")]
 pub mod span;
+#[cfg(feature = "context")]
+pub mod span_ext;
+
+#[cfg(all(test, feature = "context"))]
+pub(crate) mod test_support {
+    use opentelemetry::{KeyValue, Value, trace::TracerProvider as _};
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+    use tracing_subscriber::{Registry, layer::Layered, layer::SubscriberExt as _};
+
+    type OtelSubscriber = Layered<
+        tracing_opentelemetry::OpenTelemetryLayer<Registry, opentelemetry_sdk::trace::Tracer>,
+        Registry,
+    >;
+
+    /// A subscriber exporting spans to the returned in-memory exporter.
+    ///
+    /// Dropping the provider shuts the exporter down, so callers keep it alive.
+    fn subscriber() -> (SdkTracerProvider, InMemorySpanExporter, OtelSubscriber) {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        (provider, exporter, subscriber)
+    }
+
+    /// Runs `f` and returns the spans it finished.
+    pub fn export(f: impl FnOnce()) -> Vec<SpanData> {
+        let (_provider, exporter, subscriber) = subscriber();
+        tracing::subscriber::with_default(subscriber, f);
+        exporter.get_finished_spans().unwrap()
+    }
+
+    /// Awaits `f` with the W3C propagator set, returning its output and the spans it finished.
+    #[cfg(feature = "tower")]
+    pub async fn export_future<F: Future>(f: F) -> (F::Output, Vec<SpanData>) {
+        opentelemetry::global::set_text_map_propagator(
+            opentelemetry_sdk::propagation::TraceContextPropagator::new(),
+        );
+        let (_provider, exporter, subscriber) = subscriber();
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let output = f.await;
+        (output, exporter.get_finished_spans().unwrap())
+    }
+
+    /// The value of the attribute `key`.
+    pub fn attribute<'a>(attributes: &'a [KeyValue], key: &str) -> Option<&'a Value> {
+        attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| &kv.value)
+    }
+}
@@ -1,7 +1,15 @@
-//! OpenTelemetry context propagation for HTTP.
+//! OpenTelemetry context propagation for HTTP and other transports.
+//!
+//! Besides HTTP headers, contexts can be propagated through string maps
+//! ([`HashMap<String, String>`]), byte headers as used by message queues
+//! (`Vec<(String, Vec<u8>)>`), and any carrier implementing [`Extractor`] and
+//! [`Injector`] for custom protocols.
 
 use opentelemetry::{Context, global};
 use opentelemetry_http::{HeaderExtractor, HeaderInjector, Request, Response};
+use std::collections::HashMap;
+
+pub use opentelemetry::propagation::{Extractor, Injector};
 
 /// Extract the context from the incoming request headers
 pub fn extract_context_from_headers(headers: &http::HeaderMap) -> Context {
@@ -15,18 +23,101 @@ pub fn extract_context_from_request<T>(request: &Request<T>) -> Context {
     })
 }
 
-/// Inject specific context into a request for distributed tracing
-pub fn inject_context_into_request<T>(context: &Context, request: &mut Request<T>) {
+/// Inject specific context into outgoing headers for distributed tracing
+pub fn inject_context_into_headers(context: &Context, headers: &mut http::HeaderMap) {
     global::get_text_map_propagator(|propagator| {
-        propagator.inject_context(context, &mut HeaderInjector(request.headers_mut()));
+        propagator.inject_context(context, &mut HeaderInjector(headers));
     });
 }
 
+/// Inject specific context into a request for distributed tracing
+pub fn inject_context_into_request<T>(context: &Context, request: &mut Request<T>) {
+    inject_context_into_headers(context, request.headers_mut());
+}
+
 /// Inject specific context into a response for distributed tracing
 pub fn inject_context_into_response<T>(context: &Context, response: &mut Response<T>) {
-    global::get_text_map_propagator(|propagator| {
-        propagator.inject_context(context, &mut HeaderInjector(response.headers_mut()));
-    });
+    inject_context_into_headers(context, response.headers_mut());
+}
+
+/// Extract the context from any carrier with the global propagator
+pub fn extract_context<E>(carrier: &E) -> Context
+where
+    E: Extractor,
+{
+    global::get_text_map_propagator(|propagator| propagator.extract(carrier))
+}
+
+/// Inject specific context into any carrier with the global propagator
+pub fn inject_context<I>(context: &Context, carrier: &mut I)
+where
+    I: Injector,
+{
+    global::get_text_map_propagator(|propagator| propagator.inject_context(context, carrier));
+}
+
+/// Extract the context from a string map, e.g. message attributes
+pub fn extract_context_from_map(map: &HashMap<String, String>) -> Context {
+    extract_context(map)
+}
+
+/// Inject specific context into a string map, e.g. message attributes
+pub fn inject_context_into_map(context: &Context, map: &mut HashMap<String, String>) {
+    inject_context(context, map);
+}
+
+/// Extract the context from byte headers, e.g. Kafka record headers
+pub fn extract_context_from_byte_headers(headers: &[(String, Vec<u8>)]) -> Context {
+    extract_context(&ByteHeaderExtractor(headers))
+}
+
+/// Inject specific context into byte headers, e.g. Kafka record headers
+pub fn inject_context_into_byte_headers(context: &Context, headers: &mut Vec<(String, Vec<u8>)>) {
+    inject_context(context, &mut ByteHeaderInjector(headers));
+}
+
+/// Helper for extracting a context from byte headers.
+///
+/// Header names are matched case-insensitively, the first header with a
+/// name wins, and values that are not valid UTF-8 are ignored.
+#[derive(Debug)]
+pub struct ByteHeaderExtractor<'a>(pub &'a [(String, Vec<u8>)]);
+
+impl Extractor for ByteHeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .and_then(|(_, value)| std::str::from_utf8(value).ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    fn get_all(&self, key: &str) -> Option<Vec<&str>> {
+        let values: Vec<&str> = self
+            .0
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case(key))
+            .filter_map(|(_, value)| std::str::from_utf8(value).ok())
+            .collect();
+        (!values.is_empty()).then_some(values)
+    }
+}
+
+/// Helper for injecting a context into byte headers.
+///
+/// Existing headers with the same name, compared case-insensitively, are
+/// replaced.
+#[derive(Debug)]
+pub struct ByteHeaderInjector<'a>(pub &'a mut Vec<(String, Vec<u8>)>);
+
+impl Injector for ByteHeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.retain(|(name, _)| !name.eq_ignore_ascii_case(key));
+        self.0.push((key.to_string(), value.into_bytes()));
+    }
 }
 
 #[cfg(test)]
@@ -104,4 +195,66 @@ mod tests {
 
         assert!(!request.headers().contains_key("traceparent"));
     }
+
+    fn sampled_context() -> Context {
+        let span_context = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::default(),
+        );
+        Context::current().with_remote_span_context(span_context)
+    }
+
+    #[test]
+    fn test_map_round_trip() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let context = sampled_context();
+        let mut map = HashMap::new();
+        inject_context_into_map(&context, &mut map);
+        assert_eq!(
+            map.get("traceparent").map(String::as_str),
+            Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        );
+
+        let extracted = extract_context_from_map(&map);
+        assert_eq!(
+            extracted.span().span_context().trace_id(),
+            context.span().span_context().trace_id()
+        );
+        assert!(extracted.span().span_context().is_remote());
+    }
+
+    #[test]
+    fn test_byte_headers_round_trip() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let context = sampled_context();
+        let mut headers = vec![
+            ("TraceParent".to_string(), b"stale".to_vec()),
+            ("content-type".to_string(), b"application/json".to_vec()),
+        ];
+        inject_context_into_byte_headers(&context, &mut headers);
+        assert_eq!(
+            headers
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("traceparent"))
+                .count(),
+            1
+        );
+
+        let extracted = extract_context_from_byte_headers(&headers);
+        assert_eq!(
+            extracted.span().span_context().span_id(),
+            context.span().span_context().span_id()
+        );
+    }
+
+    #[test]
+    fn test_extract_from_invalid_byte_headers() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let headers = vec![("traceparent".to_string(), vec![0xff, 0xfe])];
+        let extracted = extract_context_from_byte_headers(&headers);
+        assert!(!extracted.span().span_context().is_valid());
+    }
 }
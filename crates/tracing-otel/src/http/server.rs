@@ -0,0 +1,344 @@
+//! Tower layer creating server spans for incoming HTTP requests.
+//!
+//! [`OtelServerLayer`] wires [`make_request_span_with_route`]
+//! into any tower server, so hyper, tonic and other servers get the same
+//! spans as `axum-otel` without depending on axum. The route and the peer
+//! address are framework specific, so they are read by a [`RouteExtractor`]
+//! and a [`PeerAddrExtractor`]; closures over the request extensions
+//! implement both.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::net::SocketAddr;
+//! use tower::{Layer as _, service_fn};
+//! use tracing::Level;
+//! use tracing_otel_extra::http::server::{OtelServerLayer, UriPathRoute};
+//!
+//! let layer = OtelServerLayer::new()
+//!     .level(Level::INFO)
+//!     .route_extractor(UriPathRoute)
+//!     .peer_addr_extractor(|extensions: &http::Extensions| {
+//!         extensions.get::<SocketAddr>().copied()
+//!     });
+//! let service = layer.layer(service_fn(|_request: http::Request<()>| async {
+//!     Ok::<_, std::io::Error>(http::Response::new(()))
+//! }));
+//! ```
+
+use crate::http::{
+    fields::QueryRedaction,
+    headers::HeaderCapture,
+    span::{RequestSpanOptions, make_request_span_with_route},
+    span_ext::OtelSpanExt as _,
+};
+use http::{Extensions, Request, Response, StatusCode};
+use std::{
+    fmt::Display,
+    net::SocketAddr,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+use tracing::{Level, Span};
+
+pub use crate::http::future::ResponseFuture;
+
+/// Extracts the `http.route` of a request, i.e. the matched route template.
+///
+/// Routes should have a low cardinality: return the template, such as
+/// `/users/{id}`, rather than the path.
+pub trait RouteExtractor {
+    /// Returns the route of `request`, or `None` when no route matched.
+    fn extract_route<B>(&self, request: &Request<B>) -> Option<String>;
+}
+
+impl<F> RouteExtractor for F
+where
+    F: Fn(&Extensions) -> Option<String>,
+{
+    fn extract_route<B>(&self, request: &Request<B>) -> Option<String> {
+        self(request.extensions())
+    }
+}
+
+/// A [`RouteExtractor`] which records no route.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoRoute;
+
+impl RouteExtractor for NoRoute {
+    fn extract_route<B>(&self, _request: &Request<B>) -> Option<String> {
+        None
+    }
+}
+
+/// A [`RouteExtractor`] which uses the request path as the route.
+///
+/// Only suitable for servers with a fixed set of paths, such as gRPC servers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UriPathRoute;
+
+impl RouteExtractor for UriPathRoute {
+    fn extract_route<B>(&self, request: &Request<B>) -> Option<String> {
+        Some(request.uri().path().to_string())
+    }
+}
+
+/// Extracts the address of the peer that sent a request, for `client.address`.
+pub trait PeerAddrExtractor {
+    /// Returns the peer address of `request`, if known.
+    fn extract_peer_addr<B>(&self, request: &Request<B>) -> Option<SocketAddr>;
+}
+
+impl<F> PeerAddrExtractor for F
+where
+    F: Fn(&Extensions) -> Option<SocketAddr>,
+{
+    fn extract_peer_addr<B>(&self, request: &Request<B>) -> Option<SocketAddr> {
+        self(request.extensions())
+    }
+}
+
+/// A [`PeerAddrExtractor`] which records no peer address.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoPeerAddr;
+
+impl PeerAddrExtractor for NoPeerAddr {
+    fn extract_peer_addr<B>(&self, _request: &Request<B>) -> Option<SocketAddr> {
+        None
+    }
+}
+
+/// A tower [`Layer`] which creates a server span per incoming HTTP request.
+///
+/// Spans are made by [`make_request_span_with_route`], so they are parented
+/// on the propagated context and carry the same attributes as `axum-otel`
+/// spans, plus `http.route` and `client.address` when the extractors find
+/// them. `5xx` responses and service errors set the span status to `ERROR`
+/// and `error.type`; other responses leave the status unset.
+#[derive(Clone, Debug)]
+pub struct OtelServerLayer<R = NoRoute, P = NoPeerAddr> {
+    level: Level,
+    options: RequestSpanOptions,
+    route: R,
+    peer_addr: P,
+}
+
+impl Default for OtelServerLayer {
+    fn default() -> Self {
+        Self {
+            level: Level::TRACE,
+            options: RequestSpanOptions::default(),
+            route: NoRoute,
+            peer_addr: NoPeerAddr,
+        }
+    }
+}
+
+impl OtelServerLayer {
+    /// Create a new layer with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<R, P> OtelServerLayer<R, P> {
+    /// Set the [`Level`] of the server spans.
+    ///
+    /// Defaults to [`Level::TRACE`].
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set the request and response headers recorded on the spans.
+    pub fn headers(mut self, headers: HeaderCapture) -> Self {
+        self.options = self.options.with_headers(headers);
+        self
+    }
+
+    /// Set how sensitive query parameters are redacted in `url.query`.
+    ///
+    /// Defaults to [`QueryRedaction::default`].
+    pub fn query_redaction(mut self, query: QueryRedaction) -> Self {
+        self.options = self.options.with_query_redaction(query);
+        self
+    }
+
+    /// Set how the `http.route` of a request is extracted.
+    ///
+    /// Defaults to [`NoRoute`].
+    pub fn route_extractor<R2>(self, route: R2) -> OtelServerLayer<R2, P> {
+        OtelServerLayer {
+            level: self.level,
+            options: self.options,
+            route,
+            peer_addr: self.peer_addr,
+        }
+    }
+
+    /// Set how the peer address of a request is extracted.
+    ///
+    /// Defaults to [`NoPeerAddr`].
+    pub fn peer_addr_extractor<P2>(self, peer_addr: P2) -> OtelServerLayer<R, P2> {
+        OtelServerLayer {
+            level: self.level,
+            options: self.options,
+            route: self.route,
+            peer_addr,
+        }
+    }
+}
+
+impl<S, R, P> Layer<S> for OtelServerLayer<R, P>
+where
+    R: Clone,
+    P: Clone,
+{
+    type Service = OtelServerService<S, R, P>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        OtelServerService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// The service created by [`OtelServerLayer`].
+#[derive(Clone, Debug)]
+pub struct OtelServerService<S, R = NoRoute, P = NoPeerAddr> {
+    inner: S,
+    layer: OtelServerLayer<R, P>,
+}
+
+impl<S, R, P> OtelServerService<S, R, P>
+where
+    R: RouteExtractor,
+    P: PeerAddrExtractor,
+{
+    fn make_span<B>(&self, request: &Request<B>) -> Span {
+        let route = self.layer.route.extract_route(request);
+        let span = make_request_span_with_route(
+            self.layer.level,
+            request,
+            route.as_deref(),
+            &self.layer.options,
+        );
+        if let Some(peer_addr) = self.layer.peer_addr.extract_peer_addr(request) {
+            span.record("client.address", tracing::field::display(peer_addr.ip()));
+        }
+        span
+    }
+}
+
+/// Record the response status on a server span, where only `5xx` are errors.
+fn record_response_status(span: &Span, status: StatusCode) {
+    span.record("http.response.status_code", i64::from(status.as_u16()));
+    if status.is_server_error() {
+        span.record("error.type", status.as_str());
+        span.set_error(status);
+    }
+}
+
+impl<S, R, P, ReqBody, ResBody> Service<Request<ReqBody>> for OtelServerService<S, R, P>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Error: Display,
+    R: RouteExtractor,
+    P: PeerAddrExtractor,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let span = self.make_span(&request);
+        let inner = {
+            let _entered = span.enter();
+            self.inner.call(request)
+        };
+        ResponseFuture::new(
+            inner,
+            span,
+            self.layer.options.headers.clone(),
+            record_response_status,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::test_support::{attribute, export_future};
+    use opentelemetry::{
+        Value,
+        trace::{SpanKind, Status},
+    };
+    use tower::{ServiceExt as _, service_fn};
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+    #[derive(Clone)]
+    struct Route(&'static str);
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_server_span_with_extractors() {
+        let layer = OtelServerLayer::new()
+            .route_extractor(|extensions: &Extensions| {
+                extensions.get::<Route>().map(|route| route.0.to_string())
+            })
+            .peer_addr_extractor(|extensions: &Extensions| extensions.get::<SocketAddr>().copied());
+        let service = layer.layer(service_fn(|_request: Request<()>| async {
+            Ok::<_, std::io::Error>(Response::builder().status(503).body(()).unwrap())
+        }));
+        let mut request = Request::builder()
+            .uri("/users/42?token=abc")
+            .header("host", "api.example.com")
+            .header("traceparent", format!("00-{TRACE_ID}-00f067aa0ba902b7-01"))
+            .body(())
+            .unwrap();
+        request.extensions_mut().insert(Route("/users/{id}"));
+        request
+            .extensions_mut()
+            .insert(SocketAddr::from(([10, 0, 0, 7], 51234)));
+
+        let (result, spans) = export_future(service.oneshot(request)).await;
+        assert_eq!(result.unwrap().status(), 503);
+        let span = &spans[0];
+        assert_eq!(span.name, "GET /users/{id}");
+        assert_eq!(span.span_kind, SpanKind::Server);
+        assert_eq!(span.status, Status::error("503 Service Unavailable"));
+        assert_eq!(span.span_context.trace_id().to_string(), TRACE_ID);
+        for (key, value) in [
+            ("http.route", Value::from("/users/{id}")),
+            ("http.response.status_code", Value::from(503_i64)),
+            ("client.address", Value::from("10.0.0.7")),
+            ("server.address", Value::from("api.example.com")),
+            ("url.path", Value::from("/users/42")),
+            ("url.query", Value::from("token=REDACTED")),
+            ("error.type", Value::from("503")),
+        ] {
+            assert_eq!(attribute(&span.attributes, key), Some(&value), "{key}");
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_server_span_defaults() {
+        let service = OtelServerLayer::new().layer(service_fn(|_request: Request<()>| async {
+            Ok::<_, std::io::Error>(Response::builder().status(404).body(()).unwrap())
+        }));
+        let request = Request::builder().uri("/missing").body(()).unwrap();
+
+        let (_, spans) = export_future(service.oneshot(request)).await;
+        let span = &spans[0];
+        assert_eq!(span.name, "GET");
+        assert_eq!(span.status, Status::Unset);
+        assert_eq!(attribute(&span.attributes, "http.route"), None);
+        assert_eq!(attribute(&span.attributes, "client.address"), None);
+        assert_eq!(attribute(&span.attributes, "error.type"), None);
+    }
+}
@@ -3,28 +3,86 @@
 //! [`make_request_span`] sets attributes that match [OpenTelemetry HTTP server spans](https://opentelemetry.io/docs/specs/semconv/http/http-spans/)
 //! (for example `server.address`, `user_agent.original`, `url.path`, `url.scheme`, `network.protocol.*`).
 //! See the [`axum_otel` crate](https://docs.rs/axum-otel) documentation for a migration table from older attribute names.
+//!
+//! Sensitive query parameters are redacted in `url.query` before it is recorded;
+//! see [`QueryRedaction`].
 
 use crate::{
     dyn_span,
-    http::{context, fields},
+    http::{
+        context,
+        fields::{self, QueryRedaction},
+        headers::HeaderCapture,
+    },
 };
 use http::Request;
+use opentelemetry::trace::SpanKind;
 use tracing::{Level, Span, field::Empty};
 
+/// Settings of the attributes recorded by [`make_request_span_with_options`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestSpanOptions {
+    /// Request headers recorded as `http.request.header.*` attributes.
+    pub headers: HeaderCapture,
+    /// Query parameters redacted in `url.query`.
+    pub query: QueryRedaction,
+}
+
+impl RequestSpanOptions {
+    /// Set the request headers to record.
+    pub fn with_headers(mut self, headers: HeaderCapture) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Set the query parameters to redact.
+    pub fn with_query_redaction(mut self, query: QueryRedaction) -> Self {
+        self.query = query;
+        self
+    }
+}
+
 /// Creates a new [`Span`] for the given request.
 pub fn make_request_span<B>(level: Level, request: &Request<B>) -> Span {
+    make_request_span_with_options(level, request, &RequestSpanOptions::default())
+}
+
+/// Creates a new [`Span`] for the given request with the given options.
+pub fn make_request_span_with_options<B>(
+    level: Level,
+    request: &Request<B>,
+    options: &RequestSpanOptions,
+) -> Span {
+    make_request_span_with_route(level, request, None, options)
+}
+
+/// Creates a new [`Span`] for the given request matched to `route`, e.g.
+/// `/users/{id}`.
+///
+/// The route is recorded as `http.route` and names the span
+/// `{method} {route}`; without a route the span is named after the method.
+pub fn make_request_span_with_route<B>(
+    level: Level,
+    request: &Request<B>,
+    route: Option<&str>,
+    options: &RequestSpanOptions,
+) -> Span {
+    let method = fields::extract_http_method(request);
+    let span_name = route.map_or_else(|| method.to_string(), |route| format!("{method} {route}"));
     let span = dyn_span!(
         level,
         "request",
+        client.address = Empty,
+        error.type = Empty,
         // HTTP fields
-        http.request.method = %fields::extract_http_method(request),
-        http.route = Empty,
+        http.request.method = %method,
+        http.route = route,
         http.response.status_code = Empty,
         network.protocol.name = fields::extract_network_protocol_name(request),
         network.protocol.version = Empty,
         // OpenTelemetry fields
-        otel.name = Empty,
-        otel.kind = ?Empty,
+        otel.name = span_name,
+        otel.kind = ?SpanKind::Server,
         otel.status_code = Empty,
         otel.status_description = Empty,
         // Request tracking
@@ -49,13 +107,14 @@ pub fn make_request_span<B>(level: Level, request: &Request<B>) -> Span {
     if let Some(request_id) = fields::extract_request_id(request) {
         span.record("request_id", request_id);
     }
-    if let Some(query) = fields::extract_url_query(request) {
-        span.record("url.query", query);
+    if let Some(query) = fields::extract_redacted_url_query(request, &options.query) {
+        span.record("url.query", query.as_ref());
     }
     if let Some(scheme) = fields::extract_url_scheme(request) {
         span.record("url.scheme", scheme);
     }
 
     context::set_otel_parent(request.headers(), &span);
+    options.headers.record_request(&span, request.headers());
     span
 }
@@ -0,0 +1,155 @@
+//! Typed OpenTelemetry operations on `tracing` spans.
+//!
+//! [`OtelSpanExt`] replaces string-keyed `span.record("otel.status_code", ...)`
+//! calls with typed methods. Status and kind are both recorded on the
+//! `otel.*` fields, so they show in formatted logs when the span declares them,
+//! and applied to the OpenTelemetry span, so they work when it does not.
+//!
+//! # Example
+//!
+//! ```rust
+//! use tracing_otel_extra::OtelSpanExt as _;
+//!
+//! let span = tracing::info_span!("charge", otel.status_code = tracing::field::Empty);
+//! span.set_attribute("payment.amount", 42_i64);
+//! span.add_event("card.authorized", vec![]);
+//! span.set_ok();
+//! ```
+
+use crate::http::error;
+use opentelemetry::{
+    Key, KeyValue, Value,
+    trace::{SpanContext, SpanKind, Status},
+};
+use std::{borrow::Cow, error::Error, fmt::Display};
+
+/// Extension trait for common OpenTelemetry operations on a [`tracing::Span`].
+///
+/// Some method names match [`OpenTelemetrySpanExt`]; import only one of the
+/// two traits where both are used to avoid ambiguous calls.
+///
+/// [`OpenTelemetrySpanExt`]: tracing_opentelemetry::OpenTelemetrySpanExt
+pub trait OtelSpanExt {
+    /// Set the span status to `OK`.
+    ///
+    /// An `OK` status is final: a later [`set_error`](Self::set_error) does not
+    /// replace it. Only set it once the operation is known to have succeeded,
+    /// and leave the status unset otherwise.
+    fn set_ok(&self);
+
+    /// Set the span status to `ERROR` with `description`.
+    fn set_error(&self, description: impl Display);
+
+    /// Record `error` as an `exception` event and set the `ERROR` status and
+    /// `error.type`, see [`record_error`](error::record_error).
+    fn record_error<E>(&self, error: &E)
+    where
+        E: Error + ?Sized;
+
+    /// Set the span kind.
+    ///
+    /// The kind can only be changed through the `otel.kind` field, so the span
+    /// must declare it, e.g. `otel.kind = tracing::field::Empty`.
+    fn set_kind(&self, kind: SpanKind);
+
+    /// Link the span to another span, e.g. the producer of a consumed message.
+    fn add_link(&self, context: SpanContext);
+
+    /// Set a typed attribute on the span.
+    fn set_attribute(&self, key: impl Into<Key>, value: impl Into<Value>);
+
+    /// Add an event with typed attributes to the span.
+    fn add_event(&self, name: impl Into<Cow<'static, str>>, attributes: Vec<KeyValue>);
+}
+
+impl OtelSpanExt for tracing::Span {
+    fn set_ok(&self) {
+        self.record("otel.status_code", "OK");
+        tracing_opentelemetry::OpenTelemetrySpanExt::set_status(self, Status::Ok);
+    }
+
+    fn set_error(&self, description: impl Display) {
+        let description = description.to_string();
+        self.record("otel.status_code", "ERROR");
+        self.record("otel.status_description", description.as_str());
+        tracing_opentelemetry::OpenTelemetrySpanExt::set_status(self, Status::error(description));
+    }
+
+    fn record_error<E>(&self, error: &E)
+    where
+        E: Error + ?Sized,
+    {
+        error::record_error(self, error);
+    }
+
+    fn set_kind(&self, kind: SpanKind) {
+        let kind = match kind {
+            SpanKind::Client => "client",
+            SpanKind::Server => "server",
+            SpanKind::Producer => "producer",
+            SpanKind::Consumer => "consumer",
+            SpanKind::Internal => "internal",
+        };
+        self.record("otel.kind", kind);
+    }
+
+    fn add_link(&self, context: SpanContext) {
+        tracing_opentelemetry::OpenTelemetrySpanExt::add_link(self, context);
+    }
+
+    fn set_attribute(&self, key: impl Into<Key>, value: impl Into<Value>) {
+        tracing_opentelemetry::OpenTelemetrySpanExt::set_attribute(self, key, value);
+    }
+
+    fn add_event(&self, name: impl Into<Cow<'static, str>>, attributes: Vec<KeyValue>) {
+        tracing_opentelemetry::OpenTelemetrySpanExt::add_event(self, name, attributes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::test_support::export;
+    use opentelemetry::trace::{SpanId, TraceFlags, TraceId, TraceState};
+    use tracing::field::Empty;
+
+    #[test]
+    fn test_status_without_declared_fields() {
+        let spans = export(|| {
+            tracing::info_span!("ok").set_ok();
+            tracing::info_span!("failed").set_error("upstream timed out");
+        });
+        assert_eq!(spans[0].status, Status::Ok);
+        assert_eq!(spans[1].status, Status::error("upstream timed out"));
+    }
+
+    #[test]
+    fn test_kind_attributes_events_and_links() {
+        let linked = SpanContext::new(
+            TraceId::from(1),
+            SpanId::from(2),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let spans = export(|| {
+            let span = tracing::info_span!("consume", otel.kind = Empty);
+            span.set_kind(SpanKind::Consumer);
+            span.set_attribute("messaging.batch.message_count", 3_i64);
+            span.add_event("retry", vec![KeyValue::new("attempt", 2_i64)]);
+            span.add_link(linked.clone());
+        });
+        let span = &spans[0];
+        assert_eq!(span.span_kind, SpanKind::Consumer);
+        assert!(
+            span.attributes
+                .contains(&KeyValue::new("messaging.batch.message_count", 3_i64))
+        );
+        assert_eq!(span.events[0].name, "retry");
+        assert_eq!(
+            span.events[0].attributes,
+            vec![KeyValue::new("attempt", 2_i64)]
+        );
+        assert_eq!(span.links[0].span_context, linked);
+    }
+}
@@ -9,6 +9,7 @@
 //! - `otel`: OpenTelemetry integration for distributed tracing
 //! - `logger`: Basic logging functionality with configurable formats
 //! - `env`: Environment-based logging configuration
+//! - `journald`: journald output sink
 //! - `context`: Trace context utilities
 //! - `fields`: Common tracing fields and attributes
 //! - `http`: HTTP request/response tracing
@@ -84,9 +85,20 @@ pub use otel::*;
 // Logger module exports
 #[cfg(feature = "logger")]
 pub use logger::{
-    FmtSpan, LogFormat, LogRollingRotation, Logger, LoggerFileAppender, init_logging,
+    FilterPrecedence, FmtSpan, JsonSpans, LogFormat, LogRollingRotation, Logger,
+    LoggerFileAppender, LoggerJournald, LoggerJson, LoggerRateLimit, LoggerRedaction,
+    LoggerSampledOnly, LoggerSyslog, LoggerTimestamp, LoggerTraceIds, RateLimitKey, RedactMode,
+    Redactor, TimestampFormat, TimestampPrecision, init_logging,
 };
 
+// Span extension exports
+#[cfg(feature = "context")]
+pub use http::span_ext::OtelSpanExt;
+
+// Tower layer exports
+#[cfg(feature = "tower")]
+pub use http::{client::OtelClientLayer, server::OtelServerLayer};
+
 // Logger module exports
 #[cfg(feature = "env")]
 pub use logger::{init_logger_from_env, init_logging_from_env};
@@ -100,12 +112,21 @@ pub mod extract {
     #[cfg(feature = "context")]
     pub use crate::http::context;
 
+    #[cfg(feature = "context")]
+    pub use crate::http::error;
+
     #[cfg(feature = "fields")]
     pub use crate::http::fields;
 
+    #[cfg(feature = "http")]
+    pub use crate::http::headers;
+
     #[cfg(feature = "http")]
     pub use crate::http::propagation as http;
 
     #[cfg(feature = "span")]
     pub use crate::http::span;
+
+    #[cfg(feature = "context")]
+    pub use crate::http::span_ext;
 }
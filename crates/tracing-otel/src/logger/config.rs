@@ -3,15 +3,20 @@
 use anyhow::Result;
 use opentelemetry::KeyValue;
 use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
 use tracing::Level;
 use tracing_appender::rolling::Rotation;
 use tracing_subscriber::fmt::format::FmtSpan;
 
 use super::deserialize::{
-    default, deserialize_attributes, deserialize_level_optional, deserialize_level_required,
-    deserialize_log_format, deserialize_log_format_optional, deserialize_span_events,
+    default, deserialize_attributes, deserialize_byte_size_optional, deserialize_level_optional,
+    deserialize_level_required, deserialize_log_format, deserialize_log_format_optional,
+    deserialize_one_or_many, deserialize_span_events, deserialize_string_list,
+    deserialize_string_map,
 };
 use super::init::init_tracing_from_logger;
+use super::rolling::RollingPolicy;
+use super::syslog::{SyslogFacility, SyslogTransport};
 use crate::otel::OtelGuard;
 
 #[cfg(feature = "env")]
@@ -34,6 +39,15 @@ pub enum LogFormat {
     /// JSON format for structured logging
     #[serde(rename = "json")]
     Json,
+    /// logfmt `key=value` format
+    #[serde(rename = "logfmt")]
+    Logfmt,
+    /// Elastic Common Schema JSON
+    #[serde(rename = "ecs")]
+    Ecs,
+    /// Graylog Extended Log Format (GELF 1.1) JSON
+    #[serde(rename = "gelf")]
+    Gelf,
 }
 
 /// Log file rotation strategy
@@ -54,6 +68,97 @@ pub enum LogRollingRotation {
     Never,
 }
 
+/// Which spans the JSON format includes with each event
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub enum JsonSpans {
+    /// Leave spans out
+    #[serde(rename = "none")]
+    None,
+    /// Only the current span, under `span`
+    #[serde(rename = "current")]
+    Current,
+    /// Only the list of spans from the root, under `spans`
+    #[serde(rename = "list")]
+    List,
+    /// Both the current span and the span list (default)
+    #[serde(rename = "all")]
+    #[default]
+    All,
+}
+
+/// How event timestamps are written
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub enum TimestampFormat {
+    /// RFC 3339, e.g. `2024-01-01T12:00:00.000000Z` (default)
+    #[serde(rename = "rfc3339")]
+    #[default]
+    Rfc3339,
+    /// Milliseconds since the unix epoch
+    #[serde(rename = "unix_millis")]
+    UnixMillis,
+    /// Nanoseconds since the unix epoch
+    #[serde(rename = "unix_nanos")]
+    UnixNanos,
+    /// No timestamp, e.g. when systemd or the container runtime adds its own
+    #[serde(rename = "none")]
+    None,
+}
+
+/// Fractional second digits of RFC 3339 timestamps
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub enum TimestampPrecision {
+    /// Whole seconds
+    #[serde(rename = "seconds")]
+    Seconds,
+    /// 3 digits
+    #[serde(rename = "millis")]
+    Millis,
+    /// 6 digits (default)
+    #[serde(rename = "micros")]
+    #[default]
+    Micros,
+    /// 9 digits
+    #[serde(rename = "nanos")]
+    Nanos,
+}
+
+/// How redacted values are replaced
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub enum RedactMode {
+    /// Replace the value with the configured mask (default)
+    #[serde(rename = "mask")]
+    #[default]
+    Mask,
+    /// Replace the value with a short HMAC-SHA256 of it keyed with the configured
+    /// `hash_secret`, so equal values can still be correlated
+    #[serde(rename = "hash")]
+    Hash,
+}
+
+/// What events are grouped by when rate limiting
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub enum RateLimitKey {
+    /// Events from the same `tracing` macro call share a budget (default)
+    #[serde(rename = "callsite")]
+    #[default]
+    Callsite,
+    /// Events with the same level, target and message share a budget
+    #[serde(rename = "message")]
+    Message,
+}
+
+/// Precedence between the filter environment variable and the configured filter
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq)]
+pub enum FilterPrecedence {
+    /// The environment variable (e.g. `RUST_LOG`) wins over the configured filter (default)
+    #[serde(rename = "env")]
+    #[default]
+    Env,
+    /// The configured filter wins over the environment variable
+    #[serde(rename = "config")]
+    Config,
+}
+
 // ============================================================================
 // Logger configuration
 // ============================================================================
@@ -90,6 +195,23 @@ pub struct Logger {
     )]
     pub level: Level,
 
+    /// Filter directives in `EnvFilter` syntax, e.g. `hyper=warn,my_app=debug`.
+    ///
+    /// When set, this takes the place of `level` as the configured filter.
+    #[serde(default)]
+    pub filter: Option<String>,
+
+    /// The environment variable to read filter directives from.
+    ///
+    /// It only replaces `level`/`filter`, the base filter shared by sinks
+    /// without a filter of their own.
+    #[serde(default = "default::filter_env_var")]
+    pub filter_env_var: String,
+
+    /// Whether the environment variable or the configured filter wins.
+    #[serde(default)]
+    pub filter_precedence: FilterPrecedence,
+
     /// The ratio of traces to sample (0.0 to 1.0).
     #[serde(default = "default::sample_ratio")]
     pub sample_ratio: f64,
@@ -106,13 +228,67 @@ pub struct Logger {
     #[serde(default = "default::console_enabled")]
     pub console_enabled: bool,
 
+    /// Level or filter directives for console output, falling back to `filter`/`level`.
+    #[serde(default)]
+    pub console_filter: Option<String>,
+
+    /// Timestamp format and timezone.
+    #[serde(default)]
+    pub timestamp: LoggerTimestamp,
+
+    /// Layout of the JSON format.
+    #[serde(default)]
+    pub json: LoggerJson,
+
+    /// Trace id, span id and sampled flag added to Compact, Pretty, Json and logfmt lines.
+    #[serde(default)]
+    pub trace_ids: LoggerTraceIds,
+
+    /// Redaction of sensitive field values in every output and in exported spans and logs.
+    #[serde(default)]
+    pub redaction: LoggerRedaction,
+
+    /// Rate limiting of repeated events in every output, including OpenTelemetry logs.
+    #[serde(default)]
+    pub rate_limit: LoggerRateLimit,
+
+    /// Dropping of events of unsampled traces, for every output that does not set its own.
+    #[serde(default)]
+    pub sampled_only: LoggerSampledOnly,
+
+    /// Dropping of events of unsampled traces for console output, falling back to `sampled_only`.
+    #[serde(default)]
+    pub console_sampled_only: Option<LoggerSampledOnly>,
+
     /// Set this if you want to write log to file
     #[serde(default)]
     pub file_appender: Option<LoggerFileAppender>,
 
+    /// Named file sinks, e.g. `errors` and `all`, each with its own settings.
+    ///
+    /// A sink without `filename_prefix` uses its name as the prefix.
+    #[serde(default)]
+    pub sinks: BTreeMap<String, LoggerFileAppender>,
+
+    /// Set this if you want to send logs to syslog
+    #[serde(default)]
+    pub syslog: Option<LoggerSyslog>,
+
+    /// Set this if you want to send logs to journald (requires the `journald` feature)
+    #[serde(default)]
+    pub journald: Option<LoggerJournald>,
+
     /// Set this if you want to write log to OpenTelemetry
     #[serde(default)]
     pub otel_logs_enabled: bool,
+
+    /// Level or filter directives for OpenTelemetry logs, falling back to `filter`/`level`.
+    #[serde(default)]
+    pub otel_logs_filter: Option<String>,
+
+    /// Dropping of events of unsampled traces for OpenTelemetry logs, falling back to `sampled_only`.
+    #[serde(default)]
+    pub otel_logs_sampled_only: Option<LoggerSampledOnly>,
 }
 
 // ============================================================================
@@ -133,6 +309,10 @@ pub struct LoggerFileAppender {
     #[serde(default, deserialize_with = "deserialize_level_optional")]
     pub level: Option<Level>,
 
+    /// Filter directives for this file, e.g. `hyper=warn,my_app=debug`. Wins over `level`.
+    #[serde(default)]
+    pub filter: Option<String>,
+
     /// Set the logger file appender ansi.
     #[serde(default)]
     pub ansi: bool,
@@ -141,6 +321,18 @@ pub struct LoggerFileAppender {
     #[serde(default, deserialize_with = "deserialize_log_format_optional")]
     pub format: Option<LogFormat>,
 
+    /// Timestamp format and timezone, falling back to the Logger's.
+    #[serde(default)]
+    pub timestamp: Option<LoggerTimestamp>,
+
+    /// Layout of the JSON format, falling back to the Logger's.
+    #[serde(default)]
+    pub json: Option<LoggerJson>,
+
+    /// Dropping of events of unsampled traces, falling back to the Logger's.
+    #[serde(default)]
+    pub sampled_only: Option<LoggerSampledOnly>,
+
     /// Set the logger file appender rotation.
     #[serde(default = "default::rotation")]
     pub rotation: LogRollingRotation,
@@ -160,14 +352,64 @@ pub struct LoggerFileAppender {
     /// Set the logger file appender keep max log files.
     #[serde(default = "default::max_log_files")]
     pub max_log_files: usize,
+
+    /// Rotate the file once it would grow beyond this size, e.g. `10MB` or `1048576`.
+    #[serde(default, deserialize_with = "deserialize_byte_size_optional")]
+    pub max_file_size: Option<u64>,
+
+    /// Delete the oldest files once all files together exceed this size.
+    #[serde(default, deserialize_with = "deserialize_byte_size_optional")]
+    pub max_total_size: Option<u64>,
+
+    /// Delete rotated files older than this many seconds.
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+
+    /// Gzip rotated files in the background.
+    #[serde(default)]
+    pub compress: bool,
+}
+
+impl Default for LoggerFileAppender {
+    /// An enabled file appender with default settings.
+    fn default() -> Self {
+        Self {
+            enable: true,
+            non_blocking: false,
+            level: None,
+            filter: None,
+            ansi: false,
+            format: None,
+            timestamp: None,
+            json: None,
+            sampled_only: None,
+            rotation: default::rotation(),
+            dir: None,
+            filename_prefix: None,
+            filename_suffix: None,
+            max_log_files: default::max_log_files(),
+            max_file_size: None,
+            max_total_size: None,
+            max_age_secs: None,
+            compress: false,
+        }
+    }
 }
 
 impl LoggerFileAppender {
-    /// Inherit `level` and `format` from Logger if not set in FileAppender.
+    /// Inherit `format`, `timestamp`, `json` and `sampled_only` from Logger if not set in FileAppender.
+    ///
+    /// `level` and `filter` are left as they are, so a file without its own
+    /// directives keeps following the Logger's filter and `filter_env_var`.
     pub fn merge_with_logger(&self, logger: &Logger) -> LoggerFileAppender {
         LoggerFileAppender {
-            level: self.level.or(Some(logger.level)),
             format: self.format.clone().or(Some(logger.format.clone())),
+            timestamp: self.timestamp.clone().or(Some(logger.timestamp.clone())),
+            json: self.json.clone().or(Some(logger.json.clone())),
+            sampled_only: self
+                .sampled_only
+                .clone()
+                .or(Some(logger.sampled_only.clone())),
             ..self.clone()
         }
     }
@@ -191,6 +433,18 @@ impl LoggerFileAppender {
             .unwrap_or_else(default::filename_suffix)
     }
 
+    /// Get the filter directives set for this file itself, from `filter` or `level`.
+    pub fn filter_directives(&self) -> Option<String> {
+        non_empty(&self.filter).or_else(|| self.level.map(|level| level.to_string()))
+    }
+
+    /// Get the dropping of events of unsampled traces, falling back to the Logger's.
+    pub fn sampled_only_or_default(&self, logger: &Logger) -> LoggerSampledOnly {
+        self.sampled_only
+            .clone()
+            .unwrap_or_else(|| logger.sampled_only.clone())
+    }
+
     /// Get format or default value
     pub fn format_or_default(&self) -> LogFormat {
         self.format.clone().unwrap_or(LogFormat::Compact)
@@ -205,6 +459,567 @@ impl LoggerFileAppender {
             LogRollingRotation::Never => Rotation::NEVER,
         }
     }
+
+    /// Whether size rotation, compression or size/age retention is configured.
+    ///
+    /// These need [`RollingFileWriter`](super::RollingFileWriter) instead of
+    /// `tracing_appender::rolling`.
+    pub fn uses_rolling_policy(&self) -> bool {
+        self.max_file_size.is_some()
+            || self.max_total_size.is_some()
+            || self.max_age_secs.is_some()
+            || self.compress
+    }
+
+    /// Get the rotation and retention policy for this file appender.
+    pub fn rolling_policy(&self) -> RollingPolicy {
+        let period_secs = match self.rotation {
+            LogRollingRotation::Minutely => Some(60),
+            LogRollingRotation::Hourly => Some(60 * 60),
+            LogRollingRotation::Daily => Some(24 * 60 * 60),
+            LogRollingRotation::Never => None,
+        };
+        RollingPolicy {
+            period_secs,
+            max_file_size: self.max_file_size,
+            max_files: self.max_log_files,
+            max_total_size: self.max_total_size,
+            max_age: self.max_age_secs.map(Duration::from_secs),
+            compress: self.compress,
+        }
+    }
+}
+
+// ============================================================================
+// Timestamp configuration
+// ============================================================================
+
+/// Format and timezone of event timestamps.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+pub struct LoggerTimestamp {
+    /// RFC 3339, unix millis/nanos, or none.
+    #[serde(default)]
+    pub format: TimestampFormat,
+
+    /// `utc` (default), `local`, or a fixed offset such as `+02:00`.
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    /// Fractional second digits of RFC 3339 timestamps.
+    #[serde(default)]
+    pub precision: TimestampPrecision,
+}
+
+impl LoggerTimestamp {
+    /// Set the timestamp format.
+    pub fn with_format(mut self, format: TimestampFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the timezone: `utc`, `local`, or a fixed offset such as `+02:00`.
+    pub fn with_timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Set the fractional second digits of RFC 3339 timestamps.
+    pub fn with_precision(mut self, precision: TimestampPrecision) -> Self {
+        self.precision = precision;
+        self
+    }
+}
+
+// ============================================================================
+// JSON layout configuration
+// ============================================================================
+
+/// Layout of the JSON log format.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct LoggerJson {
+    /// Key of the event timestamp.
+    #[serde(default = "default::json_timestamp_key")]
+    pub timestamp_key: String,
+
+    /// Key of the event level.
+    #[serde(default = "default::json_level_key")]
+    pub level_key: String,
+
+    /// Key of the event message.
+    #[serde(default = "default::json_message_key")]
+    pub message_key: String,
+
+    /// Key to add the service name under, e.g. `service`.
+    #[serde(default)]
+    pub service_name_key: Option<String>,
+
+    /// Fields added to every line, e.g. `environment=production,region=eu-west-1`.
+    #[serde(default, deserialize_with = "deserialize_string_map")]
+    pub static_fields: BTreeMap<String, String>,
+
+    /// Whether to include the current span, the span list, both or neither.
+    #[serde(default)]
+    pub spans: JsonSpans,
+
+    /// Objects and arrays nested more than this many levels inside the line
+    /// are written as JSON strings, e.g. `1` keeps `spans` but stringifies its entries.
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+}
+
+impl Default for LoggerJson {
+    fn default() -> Self {
+        Self {
+            timestamp_key: default::json_timestamp_key(),
+            level_key: default::json_level_key(),
+            message_key: default::json_message_key(),
+            service_name_key: None,
+            static_fields: BTreeMap::new(),
+            spans: JsonSpans::default(),
+            max_depth: None,
+        }
+    }
+}
+
+impl LoggerJson {
+    /// Set the keys of the timestamp, level and message.
+    pub fn with_keys(
+        mut self,
+        timestamp_key: impl Into<String>,
+        level_key: impl Into<String>,
+        message_key: impl Into<String>,
+    ) -> Self {
+        self.timestamp_key = timestamp_key.into();
+        self.level_key = level_key.into();
+        self.message_key = message_key.into();
+        self
+    }
+
+    /// Add the service name to every line under `key`.
+    pub fn with_service_name_key(mut self, key: impl Into<String>) -> Self {
+        self.service_name_key = Some(key.into());
+        self
+    }
+
+    /// Add a field to every line.
+    pub fn with_static_field(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.static_fields.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set which spans are included.
+    pub fn with_spans(mut self, spans: JsonSpans) -> Self {
+        self.spans = spans;
+        self
+    }
+
+    /// Set how many levels of objects and arrays are kept before values are written as JSON strings.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+}
+
+// ============================================================================
+// Trace id injection configuration
+// ============================================================================
+
+/// Configuration for adding the current OpenTelemetry trace context to log lines.
+///
+/// Events outside of a sampled or unsampled OpenTelemetry span are left untouched.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct LoggerTraceIds {
+    /// Add the trace id, span id and sampled flag to every event.
+    #[serde(default = "default::trace_ids_enabled")]
+    pub enable: bool,
+
+    /// Key of the trace id.
+    #[serde(default = "default::trace_id_key")]
+    pub trace_id_key: String,
+
+    /// Key of the span id.
+    #[serde(default = "default::span_id_key")]
+    pub span_id_key: String,
+
+    /// Key of the sampled flag.
+    #[serde(default = "default::sampled_key")]
+    pub sampled_key: String,
+}
+
+impl Default for LoggerTraceIds {
+    fn default() -> Self {
+        Self {
+            enable: default::trace_ids_enabled(),
+            trace_id_key: default::trace_id_key(),
+            span_id_key: default::span_id_key(),
+            sampled_key: default::sampled_key(),
+        }
+    }
+}
+
+impl LoggerTraceIds {
+    /// Trace id injection turned off.
+    pub fn disabled() -> Self {
+        Self {
+            enable: false,
+            ..Default::default()
+        }
+    }
+
+    /// Set the key names of the trace id, span id and sampled flag.
+    pub fn with_keys(
+        mut self,
+        trace_id_key: impl Into<String>,
+        span_id_key: impl Into<String>,
+        sampled_key: impl Into<String>,
+    ) -> Self {
+        self.trace_id_key = trace_id_key.into();
+        self.span_id_key = span_id_key.into();
+        self.sampled_key = sampled_key.into();
+        self
+    }
+}
+
+// ============================================================================
+// Redaction configuration
+// ============================================================================
+
+/// Configuration for redacting sensitive values from log lines, span
+/// attributes and OpenTelemetry log records.
+///
+/// A field is redacted when its key, or the last dot-separated segment of it,
+/// is in `keys` (case-insensitive, `-` and `_` are equivalent). Parts of other
+/// values matching one of `patterns` are replaced on their own.
+#[derive(Clone, PartialEq, serde::Deserialize)]
+pub struct LoggerRedaction {
+    /// Enable redaction.
+    #[serde(default)]
+    pub enable: bool,
+
+    /// Field keys whose values are always redacted.
+    #[serde(
+        default = "default::redaction_keys",
+        deserialize_with = "deserialize_string_list"
+    )]
+    pub keys: Vec<String>,
+
+    /// Regular expressions matching sensitive parts of any value, e.g. card numbers.
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub patterns: Vec<String>,
+
+    /// How redacted values are replaced.
+    #[serde(default)]
+    pub mode: RedactMode,
+
+    /// Replacement used by [`RedactMode::Mask`].
+    #[serde(default = "default::redaction_mask")]
+    pub mask: String,
+
+    /// Key of the HMAC used by [`RedactMode::Hash`], required in that mode.
+    #[serde(default)]
+    pub hash_secret: Option<String>,
+}
+
+impl std::fmt::Debug for LoggerRedaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoggerRedaction")
+            .field("enable", &self.enable)
+            .field("keys", &self.keys)
+            .field("patterns", &self.patterns)
+            .field("mode", &self.mode)
+            .field("mask", &self.mask)
+            .field(
+                "hash_secret",
+                &self.hash_secret.as_ref().map(|_| "[REDACTED]"),
+            )
+            .finish()
+    }
+}
+
+impl Default for LoggerRedaction {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            keys: default::redaction_keys(),
+            patterns: vec![],
+            mode: RedactMode::default(),
+            mask: default::redaction_mask(),
+            hash_secret: None,
+        }
+    }
+}
+
+impl LoggerRedaction {
+    /// Redaction turned on with the default key deny-list.
+    pub fn enabled() -> Self {
+        Self {
+            enable: true,
+            ..Default::default()
+        }
+    }
+
+    /// Add a key to the deny-list.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.keys.push(key.into());
+        self
+    }
+
+    /// Add a regular expression matching sensitive values.
+    pub fn with_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.patterns.push(pattern.into());
+        self
+    }
+
+    /// Set how redacted values are replaced.
+    pub fn with_mode(mut self, mode: RedactMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the replacement used by [`RedactMode::Mask`].
+    pub fn with_mask(mut self, mask: impl Into<String>) -> Self {
+        self.mask = mask.into();
+        self
+    }
+
+    /// Set the key of the HMAC used by [`RedactMode::Hash`].
+    pub fn with_hash_secret(mut self, secret: impl Into<String>) -> Self {
+        self.hash_secret = Some(secret.into());
+        self
+    }
+}
+
+// ============================================================================
+// Rate limit configuration
+// ============================================================================
+
+/// Configuration for rate limiting repeated events.
+///
+/// At most `max_events` events per key are recorded in each window of
+/// `window_secs` seconds. The others are dropped from every output, and a
+/// summary event with the number of suppressed events is emitted once the
+/// window is over.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct LoggerRateLimit {
+    /// Enable rate limiting.
+    #[serde(default)]
+    pub enable: bool,
+
+    /// What events are grouped by.
+    #[serde(default)]
+    pub key: RateLimitKey,
+
+    /// Maximum number of events per key in a window.
+    #[serde(default = "default::rate_limit_max_events")]
+    pub max_events: u32,
+
+    /// Length of a window in seconds.
+    #[serde(default = "default::rate_limit_window_secs")]
+    pub window_secs: u64,
+}
+
+impl Default for LoggerRateLimit {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            key: RateLimitKey::default(),
+            max_events: default::rate_limit_max_events(),
+            window_secs: default::rate_limit_window_secs(),
+        }
+    }
+}
+
+impl LoggerRateLimit {
+    /// Rate limiting turned on with the default budget.
+    pub fn enabled() -> Self {
+        Self {
+            enable: true,
+            ..Default::default()
+        }
+    }
+
+    /// Set what events are grouped by.
+    pub fn with_key(mut self, key: RateLimitKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Set the maximum number of events per key in a window.
+    pub fn with_max_events(mut self, max_events: u32) -> Self {
+        self.max_events = max_events;
+        self
+    }
+
+    /// Set the length of a window in seconds.
+    pub fn with_window_secs(mut self, window_secs: u64) -> Self {
+        self.window_secs = window_secs;
+        self
+    }
+}
+
+// ============================================================================
+// Sampled-only configuration
+// ============================================================================
+
+/// Configuration for dropping events that belong to unsampled traces.
+///
+/// When enabled, events inside a span whose OpenTelemetry trace is not sampled
+/// are dropped, so log lines do not point at traces that were never exported.
+/// Events outside any trace, and events at `always_level` or more severe,
+/// always pass.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct LoggerSampledOnly {
+    /// Enable dropping events of unsampled traces.
+    #[serde(default)]
+    pub enable: bool,
+
+    /// Events at this level or more severe are kept even if their trace is not sampled.
+    #[serde(
+        default = "default::sampled_only_always_level",
+        deserialize_with = "deserialize_level_required"
+    )]
+    pub always_level: Level,
+}
+
+impl Default for LoggerSampledOnly {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            always_level: default::sampled_only_always_level(),
+        }
+    }
+}
+
+impl LoggerSampledOnly {
+    /// Dropping turned on, keeping warnings and errors.
+    pub fn enabled() -> Self {
+        Self {
+            enable: true,
+            ..Default::default()
+        }
+    }
+
+    /// Dropping turned off.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Set the level from which events are kept even if their trace is not sampled.
+    pub fn with_always_level(mut self, level: Level) -> Self {
+        self.always_level = level;
+        self
+    }
+}
+
+// ============================================================================
+// Syslog and journald configuration
+// ============================================================================
+
+/// Configuration for the RFC 5424 syslog output sink.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct LoggerSyslog {
+    /// Enable syslog output
+    pub enable: bool,
+
+    /// Transport used to reach the syslog daemon (unix, udp or tcp).
+    #[serde(default)]
+    pub transport: SyslogTransport,
+
+    /// Socket path (unix) or `host:port` (udp/tcp); defaults per transport.
+    #[serde(default)]
+    pub address: Option<String>,
+
+    /// Syslog facility, e.g. `user` or `local0`.
+    #[serde(default)]
+    pub facility: SyslogFacility,
+
+    /// APP-NAME header, defaults to the service name.
+    #[serde(default)]
+    pub app_name: Option<String>,
+
+    /// HOSTNAME header, defaults to `$HOSTNAME`.
+    #[serde(default)]
+    pub hostname: Option<String>,
+
+    /// The minimum log level to record.
+    #[serde(default, deserialize_with = "deserialize_level_optional")]
+    pub level: Option<Level>,
+
+    /// Filter directives for syslog. Wins over `level`.
+    #[serde(default)]
+    pub filter: Option<String>,
+
+    /// Dropping of events of unsampled traces, falling back to the Logger's.
+    #[serde(default)]
+    pub sampled_only: Option<LoggerSampledOnly>,
+}
+
+impl LoggerSyslog {
+    /// Get address or the default for the transport
+    pub fn address_or_default(&self) -> String {
+        non_empty(&self.address).unwrap_or_else(|| self.transport.default_address().to_string())
+    }
+
+    /// Get the filter directives set for syslog itself, from `filter` or `level`.
+    pub fn filter_directives(&self) -> Option<String> {
+        non_empty(&self.filter).or_else(|| self.level.map(|level| level.to_string()))
+    }
+
+    /// Get the dropping of events of unsampled traces, falling back to the Logger's.
+    pub fn sampled_only_or_default(&self, logger: &Logger) -> LoggerSampledOnly {
+        self.sampled_only
+            .clone()
+            .unwrap_or_else(|| logger.sampled_only.clone())
+    }
+}
+
+/// Configuration for the journald output sink.
+///
+/// Journald fields are not redacted, so journald cannot be enabled together
+/// with [`LoggerRedaction`].
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct LoggerJournald {
+    /// Enable journald output
+    pub enable: bool,
+
+    /// `SYSLOG_IDENTIFIER` of each entry, defaults to the service name.
+    #[serde(default)]
+    pub syslog_identifier: Option<String>,
+
+    /// Prefix for custom field names, defaults to `F`.
+    #[serde(default)]
+    pub field_prefix: Option<String>,
+
+    /// The minimum log level to record.
+    #[serde(default, deserialize_with = "deserialize_level_optional")]
+    pub level: Option<Level>,
+
+    /// Filter directives for journald. Wins over `level`.
+    #[serde(default)]
+    pub filter: Option<String>,
+
+    /// Dropping of events of unsampled traces, falling back to the Logger's.
+    #[serde(default)]
+    pub sampled_only: Option<LoggerSampledOnly>,
+}
+
+impl LoggerJournald {
+    /// Get the filter directives set for journald itself, from `filter` or `level`.
+    pub fn filter_directives(&self) -> Option<String> {
+        non_empty(&self.filter).or_else(|| self.level.map(|level| level.to_string()))
+    }
+
+    /// Get the dropping of events of unsampled traces, falling back to the Logger's.
+    pub fn sampled_only_or_default(&self, logger: &Logger) -> LoggerSampledOnly {
+        self.sampled_only
+            .clone()
+            .unwrap_or_else(|| logger.sampled_only.clone())
+    }
+}
+
+/// Returns the value if it is set and not blank.
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.clone().filter(|value| !value.trim().is_empty())
 }
 
 // ============================================================================
@@ -219,12 +1034,28 @@ impl Default for Logger {
             span_events: default::span_events(),
             ansi: true,
             level: default::log_level(),
+            filter: None,
+            filter_env_var: default::filter_env_var(),
+            filter_precedence: FilterPrecedence::default(),
             sample_ratio: default::sample_ratio(),
             metrics_interval_secs: default::metrics_interval_secs(),
             attributes: vec![],
             console_enabled: default::console_enabled(),
+            console_filter: None,
+            timestamp: LoggerTimestamp::default(),
+            json: LoggerJson::default(),
+            trace_ids: LoggerTraceIds::default(),
+            redaction: LoggerRedaction::default(),
+            rate_limit: LoggerRateLimit::default(),
+            sampled_only: LoggerSampledOnly::default(),
+            console_sampled_only: None,
             file_appender: None,
+            sinks: BTreeMap::new(),
+            syslog: None,
+            journald: None,
             otel_logs_enabled: false,
+            otel_logs_filter: None,
+            otel_logs_sampled_only: None,
         }
     }
 }
@@ -268,6 +1099,101 @@ impl Logger {
         self
     }
 
+    /// Set the filter directives, e.g. `hyper=warn,my_app=debug`.
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Set the environment variable to read filter directives from.
+    pub fn with_filter_env_var(mut self, env_var: impl Into<String>) -> Self {
+        self.filter_env_var = env_var.into();
+        self
+    }
+
+    /// Set whether the environment variable or the configured filter wins.
+    pub fn with_filter_precedence(mut self, precedence: FilterPrecedence) -> Self {
+        self.filter_precedence = precedence;
+        self
+    }
+
+    /// Get the configured filter directives, falling back to `level`.
+    pub fn filter_or_level(&self) -> String {
+        non_empty(&self.filter).unwrap_or_else(|| self.level.to_string())
+    }
+
+    /// Get the filter directives set for console output itself.
+    pub fn console_filter_directives(&self) -> Option<String> {
+        non_empty(&self.console_filter)
+    }
+
+    /// Get the filter directives set for OpenTelemetry logs themselves.
+    pub fn otel_logs_filter_directives(&self) -> Option<String> {
+        non_empty(&self.otel_logs_filter)
+    }
+
+    /// Get the dropping of events of unsampled traces for console output.
+    pub fn console_sampled_only_or_default(&self) -> LoggerSampledOnly {
+        self.console_sampled_only
+            .clone()
+            .unwrap_or_else(|| self.sampled_only.clone())
+    }
+
+    /// Get the dropping of events of unsampled traces for OpenTelemetry logs.
+    pub fn otel_logs_sampled_only_or_default(&self) -> LoggerSampledOnly {
+        self.otel_logs_sampled_only
+            .clone()
+            .unwrap_or_else(|| self.sampled_only.clone())
+    }
+
+    /// Set the timestamp format and timezone.
+    pub fn with_timestamp(mut self, timestamp: LoggerTimestamp) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Set the layout of the JSON format.
+    pub fn with_json(mut self, json: LoggerJson) -> Self {
+        self.json = json;
+        self
+    }
+
+    /// Set how the trace id, span id and sampled flag are added to log lines.
+    pub fn with_trace_ids(mut self, trace_ids: LoggerTraceIds) -> Self {
+        self.trace_ids = trace_ids;
+        self
+    }
+
+    /// Set the redaction of sensitive field values.
+    pub fn with_redaction(mut self, redaction: LoggerRedaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Set the rate limiting of repeated events.
+    pub fn with_rate_limit(mut self, rate_limit: LoggerRateLimit) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Set the dropping of events of unsampled traces for every output without its own setting.
+    pub fn with_sampled_only(mut self, sampled_only: LoggerSampledOnly) -> Self {
+        self.sampled_only = sampled_only;
+        self
+    }
+
+    /// Set the dropping of events of unsampled traces for console output.
+    pub fn with_console_sampled_only(mut self, sampled_only: LoggerSampledOnly) -> Self {
+        self.console_sampled_only = Some(sampled_only);
+        self
+    }
+
+    /// Set the dropping of events of unsampled traces for OpenTelemetry logs.
+    pub fn with_otel_logs_sampled_only(mut self, sampled_only: LoggerSampledOnly) -> Self {
+        self.otel_logs_sampled_only = Some(sampled_only);
+        self
+    }
+
     /// Set the ratio of traces to sample (0.0 to 1.0).
     pub fn with_sample_ratio(mut self, ratio: f64) -> Self {
         self.sample_ratio = ratio;
@@ -292,12 +1218,54 @@ impl Logger {
         self
     }
 
+    /// Set the level or filter directives for console output.
+    pub fn with_console_filter(mut self, filter: impl Into<String>) -> Self {
+        self.console_filter = Some(filter.into());
+        self
+    }
+
+    /// Set whether to export logs through OpenTelemetry.
+    pub fn with_otel_logs_enabled(mut self, enabled: bool) -> Self {
+        self.otel_logs_enabled = enabled;
+        self
+    }
+
+    /// Set the level or filter directives for OpenTelemetry logs.
+    pub fn with_otel_logs_filter(mut self, filter: impl Into<String>) -> Self {
+        self.otel_logs_filter = Some(filter.into());
+        self
+    }
+
     /// Set file appender configuration.
     pub fn with_file_appender(mut self, file_appender: Option<LoggerFileAppender>) -> Self {
         self.file_appender = file_appender;
         self
     }
 
+    /// Add a named file sink, replacing any sink with the same name.
+    pub fn with_sink(mut self, name: impl Into<String>, sink: LoggerFileAppender) -> Self {
+        self.sinks.insert(name.into(), sink);
+        self
+    }
+
+    /// Set all named file sinks.
+    pub fn with_sinks(mut self, sinks: BTreeMap<String, LoggerFileAppender>) -> Self {
+        self.sinks = sinks;
+        self
+    }
+
+    /// Set syslog output configuration.
+    pub fn with_syslog(mut self, syslog: Option<LoggerSyslog>) -> Self {
+        self.syslog = syslog;
+        self
+    }
+
+    /// Set journald output configuration.
+    pub fn with_journald(mut self, journald: Option<LoggerJournald>) -> Self {
+        self.journald = journald;
+        self
+    }
+
     /// Initialize tracing with this configuration.
     pub fn init(self) -> Result<OtelGuard> {
         init_tracing_from_logger(self)
@@ -2,6 +2,7 @@
 
 use opentelemetry::KeyValue;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use tracing::Level;
 use tracing_subscriber::fmt::format::FmtSpan;
 
@@ -17,6 +18,9 @@ where
         "compact" => Ok(LogFormat::Compact),
         "pretty" => Ok(LogFormat::Pretty),
         "json" => Ok(LogFormat::Json),
+        "logfmt" => Ok(LogFormat::Logfmt),
+        "ecs" => Ok(LogFormat::Ecs),
+        "gelf" => Ok(LogFormat::Gelf),
         _ => Err(serde::de::Error::custom(format!(
             "Invalid log format: '{s}'"
         ))),
@@ -38,6 +42,9 @@ where
         "compact" => Ok(Some(LogFormat::Compact)),
         "pretty" => Ok(Some(LogFormat::Pretty)),
         "json" => Ok(Some(LogFormat::Json)),
+        "logfmt" => Ok(Some(LogFormat::Logfmt)),
+        "ecs" => Ok(Some(LogFormat::Ecs)),
+        "gelf" => Ok(Some(LogFormat::Gelf)),
         _ => Err(serde::de::Error::custom(format!(
             "Invalid log format: '{s}'"
         ))),
@@ -65,6 +72,50 @@ where
     s.parse().map(Some).map_err(serde::de::Error::custom)
 }
 
+/// Deserialize an optional byte size from a number or a string like `10MB`, `512KiB` or `1g`.
+///
+/// Units are binary: `k`/`kb`/`kib` = 1024 bytes, `m`/`mb`/`mib` = 1024², `g`/`gb`/`gib` = 1024³.
+pub fn deserialize_byte_size_optional<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ByteSize {
+        Number(u64),
+        Text(String),
+    }
+
+    match ByteSize::deserialize(deserializer)? {
+        ByteSize::Number(n) => Ok(Some(n)),
+        ByteSize::Text(s) => parse_byte_size(&s).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Parse a byte size like `10MB`; an empty string is `None`.
+fn parse_byte_size(s: &str) -> Result<Option<u64>, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Ok(None);
+    }
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid byte size: '{s}'"))?;
+    let multiplier: u64 = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1024,
+        "m" | "mb" | "mib" => 1024 * 1024,
+        "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+        _ => return Err(format!("Invalid byte size unit: '{s}'")),
+    };
+    number
+        .checked_mul(multiplier)
+        .map(Some)
+        .ok_or_else(|| format!("Byte size too large: '{s}'"))
+}
+
 /// Deserialize attributes from string format "key=value,key2=value2"
 pub fn deserialize_attributes<'de, D>(deserializer: D) -> Result<Vec<KeyValue>, D::Error>
 where
@@ -97,6 +148,82 @@ where
         .collect()
 }
 
+/// Deserialize a string map from "key=value,key2=value2" or a nested map
+pub fn deserialize_string_map<'de, D>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringMap {
+        Pairs(String),
+        Map(BTreeMap<String, String>),
+    }
+
+    match StringMap::deserialize(deserializer)? {
+        StringMap::Map(map) => Ok(map),
+        StringMap::Pairs(s) => s
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| {
+                let (key, value) = s.trim().split_once('=').ok_or_else(|| {
+                    serde::de::Error::custom(format!("Invalid key=value pair: '{s}'"))
+                })?;
+                let key = key.trim();
+                if key.is_empty() {
+                    return Err(serde::de::Error::custom(format!("Empty key: '{s}'")));
+                }
+                Ok((key.to_string(), value.trim().to_string()))
+            })
+            .collect(),
+    }
+}
+
+/// Deserialize a list of strings from "a,b,c" or a sequence
+pub fn deserialize_string_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringList {
+        Joined(String),
+        List(Vec<String>),
+    }
+
+    Ok(match StringList::deserialize(deserializer)? {
+        StringList::List(list) => list,
+        StringList::Joined(s) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+/// Deserialize a list of strings from a single string or a sequence
+///
+/// Unlike [`deserialize_string_list`], a single string is not split, so it
+/// may contain commas, e.g. a regular expression.
+pub fn deserialize_one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::Many(list) => list,
+        OneOrMany::One(s) if s.trim().is_empty() => vec![],
+        OneOrMany::One(s) => vec![s],
+    })
+}
+
 /// Deserialize span events from string format like "FMT::NEW|FMT::CLOSE"
 pub fn deserialize_span_events<'de, D>(deserializer: D) -> Result<FmtSpan, D::Error>
 where
@@ -150,6 +277,10 @@ pub mod default {
         Level::INFO
     }
 
+    pub fn filter_env_var() -> String {
+        tracing_subscriber::EnvFilter::DEFAULT_ENV.to_string()
+    }
+
     pub fn dir() -> String {
         "./logs".to_string()
     }
@@ -181,6 +312,71 @@ pub mod default {
     pub fn console_enabled() -> bool {
         true
     }
+
+    pub fn json_timestamp_key() -> String {
+        "timestamp".to_string()
+    }
+
+    pub fn json_level_key() -> String {
+        "level".to_string()
+    }
+
+    pub fn json_message_key() -> String {
+        "message".to_string()
+    }
+
+    pub fn trace_ids_enabled() -> bool {
+        true
+    }
+
+    pub fn trace_id_key() -> String {
+        "trace_id".to_string()
+    }
+
+    pub fn span_id_key() -> String {
+        "span_id".to_string()
+    }
+
+    pub fn sampled_key() -> String {
+        "trace_sampled".to_string()
+    }
+
+    pub fn redaction_keys() -> Vec<String> {
+        [
+            "password",
+            "passwd",
+            "secret",
+            "token",
+            "access_token",
+            "refresh_token",
+            "client_secret",
+            "api_key",
+            "apikey",
+            "authorization",
+            "proxy_authorization",
+            "cookie",
+            "set_cookie",
+            "private_key",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    pub fn redaction_mask() -> String {
+        "[REDACTED]".to_string()
+    }
+
+    pub fn sampled_only_always_level() -> Level {
+        Level::WARN
+    }
+
+    pub fn rate_limit_max_events() -> u32 {
+        10
+    }
+
+    pub fn rate_limit_window_secs() -> u64 {
+        1
+    }
 }
 
 #[cfg(test)]
@@ -203,6 +399,68 @@ mod tests {
             deserialize_log_format::<StrDeserializer>("json".into_deserializer()).unwrap(),
             LogFormat::Json
         );
+        assert_eq!(
+            deserialize_log_format::<StrDeserializer>("logfmt".into_deserializer()).unwrap(),
+            LogFormat::Logfmt
+        );
+        assert_eq!(
+            deserialize_log_format_optional::<StrDeserializer>("logfmt".into_deserializer())
+                .unwrap(),
+            Some(LogFormat::Logfmt)
+        );
+        assert_eq!(
+            deserialize_log_format::<StrDeserializer>("ECS".into_deserializer()).unwrap(),
+            LogFormat::Ecs
+        );
+        assert_eq!(
+            deserialize_log_format_optional::<StrDeserializer>("gelf".into_deserializer()).unwrap(),
+            Some(LogFormat::Gelf)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_string_map() {
+        let map = deserialize_string_map::<StrDeserializer>(
+            "environment=production, region = eu-west-1".into_deserializer(),
+        )
+        .unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["environment"], "production");
+        assert_eq!(map["region"], "eu-west-1");
+        assert!(
+            deserialize_string_map::<StrDeserializer>("".into_deserializer())
+                .unwrap()
+                .is_empty()
+        );
+        assert!(deserialize_string_map::<StrDeserializer>("novalue".into_deserializer()).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_string_lists() {
+        assert_eq!(
+            deserialize_string_list::<StrDeserializer>("password, x-api-key,".into_deserializer())
+                .unwrap(),
+            vec!["password", "x-api-key"]
+        );
+        assert_eq!(
+            deserialize_one_or_many::<StrDeserializer>(r"\d{13,19}".into_deserializer()).unwrap(),
+            vec![r"\d{13,19}"]
+        );
+        assert!(
+            deserialize_one_or_many::<StrDeserializer>(" ".into_deserializer())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_parse_byte_size() {
+        assert_eq!(parse_byte_size(""), Ok(None));
+        assert_eq!(parse_byte_size("1024"), Ok(Some(1024)));
+        assert_eq!(parse_byte_size("10MB"), Ok(Some(10 * 1024 * 1024)));
+        assert_eq!(parse_byte_size("512 KiB"), Ok(Some(512 * 1024)));
+        assert_eq!(parse_byte_size("1g"), Ok(Some(1024 * 1024 * 1024)));
+        assert!(parse_byte_size("10XB").is_err());
     }
 
     #[test]
@@ -22,6 +22,13 @@ pub fn init_logger_from_env(prefix: Option<&str>) -> Result<Logger> {
         logger = logger.with_file_appender(Some(merged_file_appender));
     }
 
+    let sinks = logger
+        .sinks
+        .iter()
+        .map(|(name, sink)| (name.clone(), sink.merge_with_logger(&logger)))
+        .collect();
+    logger = logger.with_sinks(sinks);
+
     Ok(logger)
 }
 
@@ -31,8 +38,12 @@ pub fn init_logging_from_env(prefix: Option<&str>) -> Result<OtelGuard> {
     init_tracing_from_logger(logger)
 }
 
+/// Nested keys such as `LOG_SINKS__ERRORS__LEVEL` are split on `__`.
 fn build_env_config(prefix: &str) -> Result<Config> {
-    let env_source = Environment::with_prefix(prefix).try_parsing(true);
+    let env_source = Environment::with_prefix(prefix)
+        .prefix_separator("_")
+        .separator("__")
+        .try_parsing(true);
 
     Config::builder()
         .add_source(env_source)
@@ -41,7 +52,10 @@ fn build_env_config(prefix: &str) -> Result<Config> {
 }
 
 fn load_file_appender_from_env(prefix: &str) -> Option<LoggerFileAppender> {
-    let env_source = Environment::with_prefix(prefix).try_parsing(true);
+    let env_source = Environment::with_prefix(prefix)
+        .prefix_separator("_")
+        .separator("__")
+        .try_parsing(true);
 
     let config = match Config::builder().add_source(env_source).build() {
         Ok(c) => c,
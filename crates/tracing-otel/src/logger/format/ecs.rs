@@ -0,0 +1,187 @@
+//! [Elastic Common Schema](https://www.elastic.co/guide/en/ecs/current/index.html) event formatter.
+//!
+//! Each event is written as a single JSON line using ECS field names:
+//!
+//! ```json
+//! {"@timestamp":"2024-01-01T00:00:00.000Z","log.level":"info","message":"user logged in","ecs.version":"8.11.0","service.name":"my-service","log.logger":"my_app","trace.id":"...","span.id":"...","user_id":7}
+//! ```
+//!
+//! Resource attributes with an ECS equivalent (`service.version`,
+//! `deployment.environment`, `host.name`, ...) are mapped to it; the others
+//! are written as `labels.*`.
+
+use super::{FormatOptions, JsonVisitor, current_trace_ids, span_fields};
+use crate::logger::Redactor;
+use serde_json::{Map, Value};
+use std::{fmt, sync::Arc};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::{
+    fmt::{FmtContext, FormatEvent, FormatFields, format::Writer},
+    registry::LookupSpan,
+};
+
+/// The ECS version written to `ecs.version`.
+const ECS_VERSION: &str = "8.11.0";
+
+/// OpenTelemetry resource attributes and their ECS field names.
+const RESOURCE_FIELDS: &[(&str, &str)] = &[
+    ("service.version", "service.version"),
+    ("service.namespace", "service.namespace"),
+    ("service.instance.id", "service.node.name"),
+    ("deployment.environment", "service.environment"),
+    ("deployment.environment.name", "service.environment"),
+    ("host.name", "host.name"),
+    ("host.id", "host.id"),
+    ("host.arch", "host.architecture"),
+    ("os.type", "host.os.type"),
+    ("process.pid", "process.pid"),
+    ("container.id", "container.id"),
+    ("cloud.provider", "cloud.provider"),
+    ("cloud.region", "cloud.region"),
+];
+
+/// Formats events as Elastic Common Schema JSON lines.
+///
+/// Spans must be formatted with `JsonFields` for their fields to be included.
+#[derive(Debug, Clone)]
+pub struct Ecs {
+    /// Fields derived from the service name and resource attributes.
+    service_fields: Map<String, Value>,
+    redactor: Option<Arc<Redactor>>,
+}
+
+impl Ecs {
+    /// Create an ECS formatter for the service described by `options`.
+    pub fn new(options: &FormatOptions) -> Self {
+        let mut service_fields = Map::new();
+        service_fields.insert("ecs.version".into(), ECS_VERSION.into());
+        service_fields.insert("service.name".into(), options.service_name.clone().into());
+        for kv in &options.attributes {
+            let key = kv.key.as_str();
+            if key == "service.name" {
+                continue;
+            }
+            let ecs_key = RESOURCE_FIELDS
+                .iter()
+                .find(|(otel, _)| *otel == key)
+                .map(|(_, ecs)| ecs.to_string())
+                .unwrap_or_else(|| format!("labels.{}", key.replace('.', "_")));
+            service_fields.insert(ecs_key, kv.value.to_string().into());
+        }
+        Self {
+            service_fields,
+            redactor: options.redactor.clone(),
+        }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for Ecs
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let visitor = JsonVisitor::from_event(event, self.redactor.as_ref());
+        let trace_ids = current_trace_ids();
+
+        let mut doc = Map::new();
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .map_err(|_| fmt::Error)?;
+        doc.insert("@timestamp".into(), timestamp.into());
+        doc.insert(
+            "log.level".into(),
+            metadata.level().as_str().to_ascii_lowercase().into(),
+        );
+        doc.insert("message".into(), visitor.message.unwrap_or_default().into());
+        doc.extend(self.service_fields.clone());
+        doc.insert("log.logger".into(), metadata.target().into());
+        if let Some(file) = metadata.file() {
+            doc.insert("log.origin.file.name".into(), file.into());
+        }
+        if let Some(line) = metadata.line() {
+            doc.insert("log.origin.file.line".into(), line.into());
+        }
+        if let Some(ids) = trace_ids {
+            doc.insert("trace.id".into(), ids.trace_id.to_string().into());
+            doc.insert("span.id".into(), ids.span_id.to_string().into());
+        }
+        if let Some(span) = ctx.lookup_current() {
+            doc.insert("span.name".into(), span.name().into());
+        }
+
+        // Span fields first so that event fields win on conflicts
+        for (key, value) in span_fields(ctx, self.redactor.as_deref())
+            .into_iter()
+            .chain(visitor.fields)
+        {
+            if !doc.contains_key(&key) || !is_reserved(&key) {
+                doc.insert(key, value);
+            }
+        }
+
+        let line = serde_json::to_string(&doc).map_err(|_| fmt::Error)?;
+        writeln!(writer, "{line}")
+    }
+}
+
+/// Fields owned by the formatter that event and span fields must not overwrite.
+fn is_reserved(key: &str) -> bool {
+    matches!(
+        key,
+        "@timestamp" | "log.level" | "message" | "ecs.version" | "service.name"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::TestWriter;
+    use super::*;
+    use crate::otel::opentelemetry::KeyValue;
+    use tracing_subscriber::{fmt::format::JsonFields, layer::SubscriberExt};
+
+    #[test]
+    fn test_ecs_event() {
+        let options = FormatOptions::new(
+            "my-service",
+            vec![
+                KeyValue::new("service.version", "1.2.3"),
+                KeyValue::new("deployment.environment", "prod"),
+                KeyValue::new("team", "core"),
+            ],
+        );
+        let writer = TestWriter::default();
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(writer.clone())
+            .event_format(Ecs::new(&options))
+            .fmt_fields(JsonFields::new());
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", request_id = "abc");
+            let _entered = span.enter();
+            tracing::warn!(target: "app", user_id = 7, "user logged in");
+        });
+
+        let doc: Value = serde_json::from_str(writer.output().trim()).unwrap();
+        assert!(doc["@timestamp"].is_string());
+        assert_eq!(doc["log.level"], "warn");
+        assert_eq!(doc["message"], "user logged in");
+        assert_eq!(doc["ecs.version"], ECS_VERSION);
+        assert_eq!(doc["service.name"], "my-service");
+        assert_eq!(doc["service.version"], "1.2.3");
+        assert_eq!(doc["service.environment"], "prod");
+        assert_eq!(doc["labels.team"], "core");
+        assert_eq!(doc["log.logger"], "app");
+        assert_eq!(doc["span.name"], "request");
+        assert_eq!(doc["request_id"], "abc");
+        assert_eq!(doc["user_id"], 7);
+    }
+}
@@ -0,0 +1,212 @@
+//! [GELF 1.1](https://go2docs.graylog.org/current/getting_in_log_data/gelf.html) event formatter.
+//!
+//! Each event is written as a single GELF JSON line, suitable for Graylog's
+//! GELF TCP input (newline delimited) or a file shipper:
+//!
+//! ```json
+//! {"version":"1.1","host":"web-1","short_message":"user logged in","timestamp":1704067200.123,"level":6,"_service.name":"my-service","_logger":"my_app","_user_id":7}
+//! ```
+//!
+//! Resource attributes, span fields and event fields become `_`-prefixed
+//! additional fields.
+
+use super::{FormatOptions, JsonVisitor, current_trace_ids, span_fields};
+use crate::logger::{Redactor, syslog::level_to_severity};
+use serde_json::{Map, Value};
+use std::{
+    fmt,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::{
+    fmt::{FmtContext, FormatEvent, FormatFields, format::Writer},
+    registry::LookupSpan,
+};
+
+/// Formats events as GELF 1.1 JSON lines.
+///
+/// Spans must be formatted with `JsonFields` for their fields to be included.
+#[derive(Debug, Clone)]
+pub struct Gelf {
+    host: String,
+    /// Additional fields derived from the service name and resource attributes.
+    service_fields: Map<String, Value>,
+    redactor: Option<Arc<Redactor>>,
+}
+
+impl Gelf {
+    /// Create a GELF formatter for the service described by `options`.
+    ///
+    /// The `host` field uses the `host.name` resource attribute, falling back
+    /// to `$HOSTNAME` and then to the service name.
+    pub fn new(options: &FormatOptions) -> Self {
+        let host = options
+            .attribute("host.name")
+            .or_else(|| std::env::var("HOSTNAME").ok().filter(|h| !h.is_empty()))
+            .unwrap_or_else(|| options.service_name.clone());
+
+        let mut service_fields = Map::new();
+        service_fields.insert("_service.name".into(), options.service_name.clone().into());
+        for kv in &options.attributes {
+            if kv.key.as_str() != "service.name" {
+                service_fields.insert(field_name(kv.key.as_str()), kv.value.to_string().into());
+            }
+        }
+        Self {
+            host,
+            service_fields,
+            redactor: options.redactor.clone(),
+        }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for Gelf
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let visitor = JsonVisitor::from_event(event, self.redactor.as_ref());
+        let trace_ids = current_trace_ids();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |d| (d.as_millis() as f64) / 1000.0);
+
+        let mut doc = Map::new();
+        doc.insert("version".into(), "1.1".into());
+        doc.insert("host".into(), self.host.clone().into());
+        // Graylog rejects an empty `short_message`, so fall back to the event name
+        let short_message = visitor
+            .message
+            .filter(|message| !message.is_empty())
+            .unwrap_or_else(|| metadata.name().to_string());
+        doc.insert("short_message".into(), short_message.into());
+        doc.insert("timestamp".into(), timestamp.into());
+        doc.insert("level".into(), level_to_severity(metadata.level()).into());
+        doc.extend(self.service_fields.clone());
+        doc.insert("_logger".into(), metadata.target().into());
+        if let Some(file) = metadata.file() {
+            doc.insert("_file".into(), file.into());
+        }
+        if let Some(line) = metadata.line() {
+            doc.insert("_line".into(), line.into());
+        }
+        if let Some(ids) = trace_ids {
+            doc.insert("_trace_id".into(), ids.trace_id.to_string().into());
+            doc.insert("_span_id".into(), ids.span_id.to_string().into());
+        }
+        if let Some(span) = ctx.lookup_current() {
+            doc.insert("_span".into(), span.name().into());
+        }
+
+        // Span fields first so that event fields win on conflicts
+        for (key, value) in span_fields(ctx, self.redactor.as_deref())
+            .into_iter()
+            .chain(visitor.fields)
+        {
+            doc.insert(field_name(&key), value);
+        }
+
+        let line = serde_json::to_string(&doc).map_err(|_| fmt::Error)?;
+        writeln!(writer, "{line}")
+    }
+}
+
+/// Turn a field name into a valid GELF additional field name.
+///
+/// GELF only allows `[\w.-]` in field names and reserves `_id`.
+fn field_name(key: &str) -> String {
+    let sanitized: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match sanitized.as_str() {
+        "id" => "_field_id".to_string(),
+        _ => format!("_{sanitized}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::TestWriter;
+    use super::*;
+    use crate::otel::opentelemetry::KeyValue;
+    use tracing_subscriber::{fmt::format::JsonFields, layer::SubscriberExt};
+
+    #[test]
+    fn test_field_name() {
+        assert_eq!(field_name("user_id"), "_user_id");
+        assert_eq!(field_name("http.method"), "_http.method");
+        assert_eq!(field_name("bad key!"), "_bad_key_");
+        assert_eq!(field_name("id"), "_field_id");
+    }
+
+    #[test]
+    fn test_gelf_event() {
+        let options = FormatOptions::new(
+            "my-service",
+            vec![
+                KeyValue::new("host.name", "web-1"),
+                KeyValue::new("service.version", "1.2.3"),
+            ],
+        );
+        let writer = TestWriter::default();
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(writer.clone())
+            .event_format(Gelf::new(&options))
+            .fmt_fields(JsonFields::new());
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", request_id = "abc");
+            let _entered = span.enter();
+            tracing::error!(target: "app", id = 7, "user logged in");
+        });
+
+        let doc: Value = serde_json::from_str(writer.output().trim()).unwrap();
+        assert_eq!(doc["version"], "1.1");
+        assert_eq!(doc["host"], "web-1");
+        assert_eq!(doc["short_message"], "user logged in");
+        assert!(doc["timestamp"].is_f64());
+        assert_eq!(doc["level"], 3);
+        assert_eq!(doc["_service.name"], "my-service");
+        assert_eq!(doc["_service.version"], "1.2.3");
+        assert_eq!(doc["_host.name"], "web-1");
+        assert_eq!(doc["_logger"], "app");
+        assert_eq!(doc["_span"], "request");
+        assert_eq!(doc["_request_id"], "abc");
+        assert_eq!(doc["_field_id"], 7);
+    }
+
+    #[test]
+    fn test_gelf_event_without_message() {
+        let options = FormatOptions::new("my-service", vec![]);
+        let writer = TestWriter::default();
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(writer.clone())
+            .event_format(Gelf::new(&options))
+            .fmt_fields(JsonFields::new());
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(name: "cache.miss", key = "user:7");
+        });
+
+        let doc: Value = serde_json::from_str(writer.output().trim()).unwrap();
+        assert_eq!(doc["short_message"], "cache.miss");
+        assert_eq!(doc["_key"], "user:7");
+    }
+}
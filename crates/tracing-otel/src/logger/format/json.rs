@@ -0,0 +1,278 @@
+//! Configurable JSON event formatter.
+//!
+//! With the default [`LoggerJson`] the output matches the flattened JSON of
+//! `tracing_subscriber`:
+//!
+//! ```json
+//! {"timestamp":"2024-01-01T00:00:00.000000Z","level":"INFO","message":"user logged in","user_id":7,"target":"my_app","span":{"name":"request","request_id":"abc"},"spans":[{"name":"request","request_id":"abc"}]}
+//! ```
+//!
+//! The timestamp, level and message keys can be renamed, static fields and the
+//! service name added to every line, the span output narrowed, and deeply
+//! nested values collapsed into strings.
+
+use super::{JsonVisitor, scope_spans};
+use crate::logger::{JsonSpans, LoggerJson, Redactor};
+use serde_json::{Map, Value};
+use std::{fmt, sync::Arc};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::{
+    fmt::{
+        FmtContext, FormatEvent, FormatFields,
+        format::Writer,
+        time::{FormatTime, SystemTime},
+    },
+    registry::LookupSpan,
+};
+
+/// Formats events as JSON lines following a [`LoggerJson`] layout.
+///
+/// Spans must be formatted with `JsonFields` for their fields to be included.
+#[derive(Debug, Clone)]
+pub struct JsonLayout<T = SystemTime> {
+    config: LoggerJson,
+    service_name: String,
+    redactor: Option<Arc<Redactor>>,
+    timer: T,
+}
+
+impl JsonLayout {
+    /// Create a JSON formatter for the given layout and service name.
+    pub fn new(config: LoggerJson, service_name: impl Into<String>) -> Self {
+        Self {
+            config,
+            service_name: service_name.into(),
+            redactor: None,
+            timer: SystemTime,
+        }
+    }
+}
+
+impl<T> JsonLayout<T> {
+    /// Redact event and span fields with `redactor` when it is set.
+    pub fn with_redactor(mut self, redactor: Option<Arc<Redactor>>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Use the given timer for the timestamp.
+    pub fn with_timer<T2>(self, timer: T2) -> JsonLayout<T2> {
+        JsonLayout {
+            config: self.config,
+            service_name: self.service_name,
+            redactor: self.redactor,
+            timer,
+        }
+    }
+}
+
+/// Key/value pairs kept in insertion order, later values replacing earlier ones.
+#[derive(Default)]
+struct Entries(Vec<(String, Value)>);
+
+impl Entries {
+    fn insert(&mut self, key: impl Into<String>, value: impl Into<Value>) {
+        let key = key.into();
+        let value = value.into();
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.0.push((key, value)),
+        }
+    }
+
+    fn write(self, writer: &mut Writer<'_>, max_depth: Option<usize>) -> fmt::Result {
+        writer.write_char('{')?;
+        for (i, (key, value)) in self.0.into_iter().enumerate() {
+            if i > 0 {
+                writer.write_char(',')?;
+            }
+            let value = match max_depth {
+                Some(max_depth) => limit_depth(value, 1, max_depth),
+                None => value,
+            };
+            let key = serde_json::to_string(&key).map_err(|_| fmt::Error)?;
+            let value = serde_json::to_string(&value).map_err(|_| fmt::Error)?;
+            write!(writer, "{key}:{value}")?;
+        }
+        writer.write_char('}')
+    }
+}
+
+impl<S, N, T> FormatEvent<S, N> for JsonLayout<T>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    T: FormatTime,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let config = &self.config;
+        let metadata = event.metadata();
+        let visitor = JsonVisitor::from_event(event, self.redactor.as_ref());
+        let mut entries = Entries::default();
+
+        let mut timestamp = String::new();
+        if self
+            .timer
+            .format_time(&mut Writer::new(&mut timestamp))
+            .is_ok()
+            && !timestamp.is_empty()
+        {
+            entries.insert(&config.timestamp_key, timestamp);
+        }
+        entries.insert(&config.level_key, metadata.level().as_str());
+        if let Some(key) = &config.service_name_key {
+            entries.insert(key, self.service_name.as_str());
+        }
+        for (key, value) in &config.static_fields {
+            entries.insert(key, value.as_str());
+        }
+        if let Some(message) = visitor.message {
+            entries.insert(&config.message_key, message);
+        }
+        for (key, value) in visitor.fields {
+            entries.insert(key, value);
+        }
+        entries.insert("target", metadata.target());
+
+        if config.spans != JsonSpans::None {
+            let spans: Vec<Value> = scope_spans(ctx, self.redactor.as_deref())
+                .into_iter()
+                .map(|(name, mut fields)| {
+                    fields.insert("name".into(), name.into());
+                    Value::Object(fields)
+                })
+                .collect();
+            if matches!(config.spans, JsonSpans::Current | JsonSpans::All)
+                && let Some(current) = spans.last()
+            {
+                entries.insert("span", current.clone());
+            }
+            if matches!(config.spans, JsonSpans::List | JsonSpans::All) && !spans.is_empty() {
+                entries.insert("spans", spans);
+            }
+        }
+
+        entries.write(&mut writer, config.max_depth)?;
+        writeln!(writer)
+    }
+}
+
+/// Replace objects and arrays more than `max_depth` levels inside the line with their JSON string.
+fn limit_depth(value: Value, depth: usize, max_depth: usize) -> Value {
+    match value {
+        Value::Object(_) | Value::Array(_) if depth > max_depth => Value::String(value.to_string()),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, limit_depth(v, depth + 1, max_depth)))
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|v| limit_depth(v, depth + 1, max_depth))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::TestWriter;
+    use super::*;
+    use tracing_subscriber::{fmt::format::JsonFields, layer::SubscriberExt};
+
+    struct FixedTime;
+
+    impl FormatTime for FixedTime {
+        fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+            w.write_str("2024-01-01T00:00:00Z")
+        }
+    }
+
+    fn capture(config: LoggerJson) -> String {
+        capture_with_redactor(config, None)
+    }
+
+    fn capture_with_redactor(config: LoggerJson, redactor: Option<Arc<Redactor>>) -> String {
+        let writer = TestWriter::default();
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(writer.clone())
+            .fmt_fields(JsonFields::new())
+            .event_format(
+                JsonLayout::new(config, "my-service")
+                    .with_redactor(redactor)
+                    .with_timer(FixedTime),
+            );
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = tracing::info_span!("request", request_id = "abc");
+            let _outer = outer.enter();
+            let inner = tracing::info_span!("db", table = "users");
+            let _inner = inner.enter();
+            tracing::info!(target: "app", user_id = 7, "user logged in");
+        });
+        writer.output()
+    }
+
+    #[test]
+    fn test_default_layout() {
+        assert_eq!(
+            capture(LoggerJson::default()),
+            concat!(
+                r#"{"timestamp":"2024-01-01T00:00:00Z","level":"INFO","message":"user logged in","user_id":7,"target":"app","#,
+                r#""span":{"name":"db","table":"users"},"#,
+                r#""spans":[{"name":"request","request_id":"abc"},{"name":"db","table":"users"}]}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_custom_layout() {
+        let config = LoggerJson::default()
+            .with_keys("@t", "severity", "msg")
+            .with_service_name_key("service")
+            .with_static_field("environment", "production")
+            .with_spans(JsonSpans::Current);
+        assert_eq!(
+            capture(config),
+            concat!(
+                r#"{"@t":"2024-01-01T00:00:00Z","severity":"INFO","service":"my-service","environment":"production","#,
+                r#""msg":"user logged in","user_id":7,"target":"app","span":{"name":"db","table":"users"}}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let config = LoggerJson::default()
+            .with_spans(JsonSpans::List)
+            .with_max_depth(1);
+        let doc: Value = serde_json::from_str(&capture(config)).unwrap();
+        assert!(doc.get("span").is_none());
+        assert_eq!(doc["spans"][0], r#"{"name":"request","request_id":"abc"}"#);
+        assert_eq!(doc["spans"][1], r#"{"name":"db","table":"users"}"#);
+    }
+
+    #[test]
+    fn test_redacted_fields() {
+        let redaction = crate::logger::LoggerRedaction::enabled()
+            .with_key("request_id")
+            .with_pattern("logged");
+        let redactor = Redactor::from_config(&redaction).unwrap();
+        let doc: Value =
+            serde_json::from_str(&capture_with_redactor(LoggerJson::default(), redactor)).unwrap();
+        assert_eq!(doc["message"], "user [REDACTED] in");
+        assert_eq!(doc["user_id"], 7);
+        assert_eq!(doc["spans"][0]["request_id"], "[REDACTED]");
+        assert_eq!(doc["span"]["table"], "users");
+    }
+}
@@ -0,0 +1,310 @@
+//! [logfmt](https://brandur.org/logfmt) event formatter.
+//!
+//! Each event is written as a single line of `key=value` pairs:
+//!
+//! ```text
+//! ts=2024-01-01T00:00:00.000000Z level=info target=my_app msg="user logged in" span=request request_id=42 user_id=7
+//! ```
+//!
+//! The fields of every enclosing span are flattened into the line, from the
+//! root span to the current one, followed by the event's own fields.
+
+use super::RedactVisitor;
+use crate::logger::Redactor;
+use std::{fmt, sync::Arc};
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+};
+use tracing_subscriber::{
+    field::{MakeVisitor, VisitFmt, VisitOutput},
+    fmt::{
+        FmtContext, FormatEvent, FormatFields, FormattedFields,
+        format::Writer,
+        time::{FormatTime, SystemTime},
+    },
+    registry::LookupSpan,
+};
+
+/// Formats events as logfmt lines.
+#[derive(Debug, Clone, Default)]
+pub struct Logfmt<T = SystemTime> {
+    timer: T,
+    redactor: Option<Arc<Redactor>>,
+}
+
+impl Logfmt {
+    /// Create a logfmt formatter using the default RFC 3339 UTC timer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T> Logfmt<T> {
+    /// Use the given timer for the `ts` field.
+    pub fn with_timer<T2>(self, timer: T2) -> Logfmt<T2> {
+        Logfmt {
+            timer,
+            redactor: self.redactor,
+        }
+    }
+
+    /// Redact event fields with `redactor` when it is set.
+    ///
+    /// Span fields are redacted by wrapping [`LogfmtFields`] in a `RedactFields`.
+    pub fn with_redactor(mut self, redactor: Option<Arc<Redactor>>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+}
+
+/// Formats span fields as logfmt `key=value` pairs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogfmtFields;
+
+impl<'writer> MakeVisitor<Writer<'writer>> for LogfmtFields {
+    type Visitor = LogfmtVisitor<'writer>;
+
+    fn make_visitor(&self, target: Writer<'writer>) -> Self::Visitor {
+        LogfmtVisitor::new(target, false)
+    }
+}
+
+impl<S, N, T> FormatEvent<S, N> for Logfmt<T>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    T: FormatTime,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut timestamp = String::new();
+        if self
+            .timer
+            .format_time(&mut Writer::new(&mut timestamp))
+            .is_ok()
+            && !timestamp.is_empty()
+        {
+            writer.write_str("ts=")?;
+            write_value(&mut writer, &timestamp)?;
+            writer.write_char(' ')?;
+        }
+
+        let metadata = event.metadata();
+        write!(
+            writer,
+            "level={}",
+            metadata.level().as_str().to_ascii_lowercase()
+        )?;
+        writer.write_str(" target=")?;
+        write_value(&mut writer, metadata.target())?;
+
+        // The message goes first so it stays readable next to the level
+        let mut message = RedactVisitor::new(MessageVisitor(None), self.redactor.clone());
+        event.record(&mut message);
+        let MessageVisitor(message) = message.into_inner();
+        if let Some(message) = message {
+            writer.write_str(" msg=")?;
+            write_value(&mut writer, &message)?;
+        }
+
+        if let Some(scope) = ctx.event_scope() {
+            let mut spans = scope.from_root().peekable();
+            while let Some(span) = spans.next() {
+                if spans.peek().is_none() {
+                    writer.write_str(" span=")?;
+                    write_value(&mut writer, span.name())?;
+                }
+                let extensions = span.extensions();
+                if let Some(fields) = extensions.get::<FormattedFields<N>>()
+                    && !fields.is_empty()
+                {
+                    write!(writer, " {}", fields.fields)?;
+                }
+            }
+        }
+
+        let mut visitor = RedactVisitor::new(
+            LogfmtVisitor::new(writer.by_ref(), true),
+            self.redactor.clone(),
+        );
+        event.record(&mut visitor);
+        visitor.finish()?;
+
+        writeln!(writer)
+    }
+}
+
+/// Extracts the `message` field of an event.
+struct MessageVisitor(Option<String>);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = Some(format!("{value:?}"));
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0 = Some(value.to_string());
+        }
+    }
+}
+
+/// Writes fields as ` key=value` pairs, skipping `message` for events.
+pub struct LogfmtVisitor<'writer> {
+    writer: Writer<'writer>,
+    skip_message: bool,
+    first: bool,
+    result: fmt::Result,
+}
+
+impl<'writer> LogfmtVisitor<'writer> {
+    fn new(writer: Writer<'writer>, is_event: bool) -> Self {
+        Self {
+            writer,
+            skip_message: is_event,
+            // Events are appended to an existing line, so always lead with a space
+            first: !is_event,
+            result: Ok(()),
+        }
+    }
+
+    fn record_value(&mut self, field: &Field, value: &str) {
+        if self.result.is_err() || (self.skip_message && field.name() == "message") {
+            return;
+        }
+        let name = match field.name() {
+            "message" => "msg",
+            name => name.strip_prefix("r#").unwrap_or(name),
+        };
+        self.result = (|| {
+            if !self.first {
+                self.writer.write_char(' ')?;
+            }
+            write_key(&mut self.writer, name)?;
+            self.writer.write_char('=')?;
+            write_value(&mut self.writer, value)
+        })();
+        self.first = false;
+    }
+}
+
+impl Visit for LogfmtVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_value(field, value);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record_value(field, &format!("{value:?}"));
+    }
+}
+
+impl VisitOutput<fmt::Result> for LogfmtVisitor<'_> {
+    fn finish(self) -> fmt::Result {
+        self.result
+    }
+}
+
+impl VisitFmt for LogfmtVisitor<'_> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        &mut self.writer
+    }
+}
+
+/// Write a key, replacing characters that would break the pair.
+fn write_key(writer: &mut impl fmt::Write, key: &str) -> fmt::Result {
+    for c in key.chars() {
+        match c {
+            ' ' | '=' | '"' => writer.write_char('_')?,
+            c if c.is_control() => writer.write_char('_')?,
+            c => writer.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Write a value, quoting and escaping it when needed.
+fn write_value(writer: &mut impl fmt::Write, value: &str) -> fmt::Result {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c == '\\' || c.is_control());
+    if !needs_quotes {
+        return writer.write_str(value);
+    }
+
+    writer.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => writer.write_str("\\\"")?,
+            '\\' => writer.write_str("\\\\")?,
+            '\n' => writer.write_str("\\n")?,
+            '\r' => writer.write_str("\\r")?,
+            '\t' => writer.write_str("\\t")?,
+            c if c.is_control() => write!(writer, "\\u{{{:04x}}}", c as u32)?,
+            c => writer.write_char(c)?,
+        }
+    }
+    writer.write_char('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::TestWriter;
+    use super::*;
+    use tracing_subscriber::fmt::time::FormatTime;
+
+    struct FixedTime;
+
+    impl FormatTime for FixedTime {
+        fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+            w.write_str("2024-01-01T00:00:00Z")
+        }
+    }
+
+    fn quoted(value: &str) -> String {
+        let mut out = String::new();
+        write_value(&mut out, value).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_write_value_quoting() {
+        assert_eq!(quoted("plain"), "plain");
+        assert_eq!(quoted(""), r#""""#);
+        assert_eq!(quoted("two words"), r#""two words""#);
+        assert_eq!(quoted("a=b"), r#""a=b""#);
+        assert_eq!(quoted("say \"hi\"\n"), r#""say \"hi\"\n""#);
+        assert_eq!(quoted("C:\\path"), r#""C:\\path""#);
+    }
+
+    #[test]
+    fn test_logfmt_event_with_span_fields() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let writer = TestWriter::default();
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(writer.clone())
+            .event_format(Logfmt::new().with_timer(FixedTime))
+            .fmt_fields(LogfmtFields);
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", request_id = "abc", path = "/a b");
+            let _entered = span.enter();
+            tracing::info!(target: "app", user_id = 7, "user logged in");
+        });
+
+        let output = writer.output();
+        assert_eq!(
+            output,
+            "ts=2024-01-01T00:00:00Z level=info target=app msg=\"user logged in\" span=request request_id=abc path=\"/a b\" user_id=7\n"
+        );
+    }
+}
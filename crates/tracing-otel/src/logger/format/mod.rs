@@ -0,0 +1,269 @@
+//! Custom event formatters for the fmt layers.
+
+mod ecs;
+mod gelf;
+mod json;
+mod logfmt;
+mod redact;
+mod timestamp;
+mod trace_ids;
+
+pub use ecs::Ecs;
+pub use gelf::Gelf;
+pub use json::JsonLayout;
+pub use logfmt::{Logfmt, LogfmtFields};
+pub use redact::{RedactFields, RedactVisitor};
+pub use timestamp::Timestamp;
+pub use trace_ids::{TraceIdsStyle, WithTraceIds};
+
+use crate::logger::{LoggerJson, LoggerTraceIds, Redactor};
+use crate::otel::{
+    opentelemetry::{
+        Context, KeyValue,
+        trace::{SpanId, TraceContextExt as _, TraceId},
+    },
+    tracing_opentelemetry::OpenTelemetrySpanExt as _,
+};
+use serde_json::{Map, Value};
+use std::{fmt, sync::Arc};
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+};
+use tracing_subscriber::{
+    fmt::{FmtContext, FormatFields, FormattedFields},
+    registry::LookupSpan,
+};
+
+/// Settings shared by the formatters of every output.
+#[derive(Debug, Clone, Default)]
+pub struct FormatOptions {
+    /// The name of the service being logged, embedded by ECS and GELF.
+    pub service_name: String,
+    /// Resource attributes of the service, e.g. `service.version`, embedded by ECS and GELF.
+    pub attributes: Vec<KeyValue>,
+    /// How the trace context is added to Compact, Pretty, Json and logfmt lines.
+    pub trace_ids: LoggerTraceIds,
+    /// Layout of the JSON format.
+    pub json: LoggerJson,
+    /// Timer of every format except ECS and GELF, which define their own.
+    pub timestamp: Timestamp,
+    /// Redactor applied to event and span fields of every format.
+    pub redactor: Option<Arc<Redactor>>,
+}
+
+impl FormatOptions {
+    /// Create options for the given service name and resource attributes.
+    pub fn new(service_name: impl Into<String>, attributes: Vec<KeyValue>) -> Self {
+        Self {
+            service_name: service_name.into(),
+            attributes,
+            trace_ids: LoggerTraceIds::default(),
+            json: LoggerJson::default(),
+            timestamp: Timestamp::default(),
+            redactor: None,
+        }
+    }
+
+    /// Set the redactor applied to event and span fields.
+    pub fn with_redactor(mut self, redactor: Option<Arc<Redactor>>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Set the timer.
+    pub fn with_timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Set the layout of the JSON format.
+    pub fn with_json(mut self, json: LoggerJson) -> Self {
+        self.json = json;
+        self
+    }
+
+    /// Set how the trace context is added to log lines.
+    pub fn with_trace_ids(mut self, trace_ids: LoggerTraceIds) -> Self {
+        self.trace_ids = trace_ids;
+        self
+    }
+
+    /// Get a resource attribute as a string.
+    fn attribute(&self, key: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| kv.value.to_string())
+    }
+}
+
+/// The OpenTelemetry trace id, span id and sampled flag of the current span.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceIds {
+    /// The current trace id.
+    pub trace_id: TraceId,
+    /// The current span id.
+    pub span_id: SpanId,
+    /// Whether the trace is sampled.
+    pub sampled: bool,
+}
+
+/// Returns the trace ids of the current span, if it has a valid OpenTelemetry context.
+///
+/// While an event is being dispatched `tracing::Span::current()` is not
+/// available, so the OpenTelemetry context attached when the span was entered
+/// is read first. This must not be called while holding a lock on the current
+/// span's extensions.
+pub fn current_trace_ids() -> Option<TraceIds> {
+    Context::map_current(trace_ids_of).or_else(|| trace_ids_of(&tracing::Span::current().context()))
+}
+
+fn trace_ids_of(context: &Context) -> Option<TraceIds> {
+    let span = context.span();
+    let span_context = span.span_context();
+    span_context.is_valid().then(|| TraceIds {
+        trace_id: span_context.trace_id(),
+        span_id: span_context.span_id(),
+        sampled: span_context.is_sampled(),
+    })
+}
+
+/// Collects the fields of every span in the event's scope, from the root span
+/// to the current one, when the spans were formatted with `JsonFields`.
+fn span_fields<S, N>(ctx: &FmtContext<'_, S, N>, redactor: Option<&Redactor>) -> Map<String, Value>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    let mut fields = Map::new();
+    for (_, span) in scope_spans(ctx, redactor) {
+        fields.extend(span);
+    }
+    fields
+}
+
+/// Returns the name and fields of every span in the event's scope, from the
+/// root span to the current one, when the spans were formatted with `JsonFields`.
+///
+/// Span fields are stored unredacted, so `redactor` is applied here.
+fn scope_spans<S, N>(
+    ctx: &FmtContext<'_, S, N>,
+    redactor: Option<&Redactor>,
+) -> Vec<(&'static str, Map<String, Value>)>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    let Some(scope) = ctx.event_scope() else {
+        return Vec::new();
+    };
+    scope
+        .from_root()
+        .map(|span| {
+            let extensions = span.extensions();
+            let mut fields = match extensions
+                .get::<FormattedFields<N>>()
+                .map(|formatted| serde_json::from_str::<Value>(&formatted.fields))
+            {
+                Some(Ok(Value::Object(map))) => map,
+                _ => Map::new(),
+            };
+            if let Some(redactor) = redactor {
+                for (key, value) in fields.iter_mut() {
+                    redactor.redact_json(key, value);
+                }
+            }
+            (span.name(), fields)
+        })
+        .collect()
+}
+
+/// Collects the message and the other fields of an event as JSON values.
+#[derive(Default)]
+struct JsonVisitor {
+    message: Option<String>,
+    fields: Map<String, Value>,
+}
+
+impl JsonVisitor {
+    fn from_event(event: &Event<'_>, redactor: Option<&Arc<Redactor>>) -> Self {
+        let mut visitor = RedactVisitor::new(Self::default(), redactor.cloned());
+        event.record(&mut visitor);
+        visitor.into_inner()
+    }
+
+    fn insert(&mut self, field: &Field, value: Value) {
+        if field.name() == "message" {
+            self.message = Some(match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            });
+        } else {
+            let name = field.name();
+            let name = name.strip_prefix("r#").unwrap_or(name);
+            self.fields.insert(name.to_string(), value);
+        }
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, Value::from(format!("{value:?}")));
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::MakeWriter;
+
+    /// An in-memory writer for formatter tests.
+    #[derive(Clone, Default)]
+    pub struct TestWriter(pub Arc<Mutex<Vec<u8>>>);
+
+    impl TestWriter {
+        pub fn output(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl std::io::Write for TestWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for TestWriter {
+        type Writer = TestWriter;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+}
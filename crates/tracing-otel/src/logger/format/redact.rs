@@ -0,0 +1,185 @@
+//! Redaction of event and span fields before they are formatted.
+
+use crate::logger::Redactor;
+use std::{fmt, sync::Arc};
+use tracing::field::{Field, Visit};
+use tracing_subscriber::field::{MakeVisitor, VisitFmt, VisitOutput};
+
+/// Wraps a field formatter such as `DefaultFields` or `PrettyFields`,
+/// redacting values before the inner formatter sees them.
+///
+/// Without a redactor fields are passed through unchanged.
+#[derive(Debug, Clone)]
+pub struct RedactFields<M> {
+    inner: M,
+    redactor: Option<Arc<Redactor>>,
+}
+
+impl<M> RedactFields<M> {
+    /// Wrap `inner`, redacting fields with `redactor` when it is set.
+    pub fn new(inner: M, redactor: Option<Arc<Redactor>>) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+impl<T, M: MakeVisitor<T>> MakeVisitor<T> for RedactFields<M> {
+    type Visitor = RedactVisitor<M::Visitor>;
+
+    fn make_visitor(&self, target: T) -> Self::Visitor {
+        RedactVisitor::new(self.inner.make_visitor(target), self.redactor.clone())
+    }
+}
+
+/// A visitor that redacts values before forwarding them to `inner`.
+///
+/// Redacted values are forwarded as strings.
+pub struct RedactVisitor<V> {
+    inner: V,
+    redactor: Option<Arc<Redactor>>,
+}
+
+impl<V> RedactVisitor<V> {
+    /// Wrap `inner`, redacting values with `redactor` when it is set.
+    pub fn new(inner: V, redactor: Option<Arc<Redactor>>) -> Self {
+        Self { inner, redactor }
+    }
+
+    /// Returns the wrapped visitor.
+    pub fn into_inner(self) -> V {
+        self.inner
+    }
+
+    fn redact(&self, field: &Field, value: impl FnOnce() -> String) -> Option<String> {
+        let redactor = self.redactor.as_deref()?;
+        redactor.redact_str(field.name(), &value())
+    }
+}
+
+impl<V: Visit> Visit for RedactVisitor<V> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        match self.redact(field, || value.to_string()) {
+            Some(redacted) => self.inner.record_str(field, &redacted),
+            None => self.inner.record_f64(field, value),
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        match self.redact(field, || value.to_string()) {
+            Some(redacted) => self.inner.record_str(field, &redacted),
+            None => self.inner.record_i64(field, value),
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match self.redact(field, || value.to_string()) {
+            Some(redacted) => self.inner.record_str(field, &redacted),
+            None => self.inner.record_u64(field, value),
+        }
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        match self.redact(field, || value.to_string()) {
+            Some(redacted) => self.inner.record_str(field, &redacted),
+            None => self.inner.record_i128(field, value),
+        }
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        match self.redact(field, || value.to_string()) {
+            Some(redacted) => self.inner.record_str(field, &redacted),
+            None => self.inner.record_u128(field, value),
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        match self.redact(field, || value.to_string()) {
+            Some(redacted) => self.inner.record_str(field, &redacted),
+            None => self.inner.record_bool(field, value),
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match self.redact(field, || value.to_string()) {
+            Some(redacted) => self.inner.record_str(field, &redacted),
+            None => self.inner.record_str(field, value),
+        }
+    }
+
+    fn record_bytes(&mut self, field: &Field, value: &[u8]) {
+        match self.redact(field, || format!("{value:?}")) {
+            Some(redacted) => self.inner.record_str(field, &redacted),
+            None => self.inner.record_bytes(field, value),
+        }
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        match self.redact(field, || value.to_string()) {
+            Some(redacted) => self.inner.record_str(field, &redacted),
+            None => self.inner.record_error(field, value),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        // The message is kept as a debug value so formatters still print it unquoted
+        match self.redact(field, || format!("{value:?}")) {
+            Some(redacted) if field.name() == "message" => {
+                self.inner.record_debug(field, &format_args!("{redacted}"))
+            }
+            Some(redacted) => self.inner.record_str(field, &redacted),
+            None => self.inner.record_debug(field, value),
+        }
+    }
+}
+
+impl<V: VisitOutput<O>, O> VisitOutput<O> for RedactVisitor<V> {
+    fn finish(self) -> O {
+        self.inner.finish()
+    }
+}
+
+impl<V: VisitFmt> VisitFmt for RedactVisitor<V> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        self.inner.writer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::TestWriter;
+    use super::*;
+    use crate::logger::LoggerRedaction;
+    use tracing_subscriber::{fmt::format::DefaultFields, layer::SubscriberExt};
+
+    #[test]
+    fn test_redacts_compact_fields() {
+        let redactor = Redactor::from_config(
+            &LoggerRedaction::enabled().with_pattern(r"\b(?:\d[ -]?){12,18}\d\b"),
+        )
+        .unwrap();
+        let writer = TestWriter::default();
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(writer.clone())
+            .with_ansi(false)
+            .without_time()
+            .compact()
+            .fmt_fields(RedactFields::new(DefaultFields::new(), redactor));
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("login", token = "abc123");
+            let _entered = span.enter();
+            tracing::info!(
+                password = "hunter2",
+                user = "alice",
+                "paid with 4111 1111 1111 1111"
+            );
+        });
+
+        let output = writer.output();
+        assert!(output.contains("paid with [REDACTED]"), "{output}");
+        assert!(output.contains(r#"password="[REDACTED]""#), "{output}");
+        assert!(output.contains(r#"token="[REDACTED]""#), "{output}");
+        assert!(output.contains(r#"user="alice""#), "{output}");
+        assert!(!output.contains("hunter2") && !output.contains("abc123"));
+    }
+}
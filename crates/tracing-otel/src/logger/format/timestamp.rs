@@ -0,0 +1,179 @@
+//! Configurable timer for the fmt layers.
+
+use crate::logger::{LoggerTimestamp, TimestampFormat, TimestampPrecision};
+use anyhow::{Result, anyhow};
+use std::fmt;
+use time::{OffsetDateTime, UtcOffset};
+use tracing_subscriber::fmt::{format::Writer, time::FormatTime};
+
+/// Writes event timestamps in the format and timezone of a [`LoggerTimestamp`].
+///
+/// With the default configuration the output matches `tracing_subscriber`'s
+/// default timer, e.g. `2024-01-01T12:00:00.000000Z`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timestamp {
+    format: TimestampFormat,
+    offset: UtcOffset,
+    precision: TimestampPrecision,
+}
+
+impl Default for Timestamp {
+    fn default() -> Self {
+        Self {
+            format: TimestampFormat::default(),
+            offset: UtcOffset::UTC,
+            precision: TimestampPrecision::default(),
+        }
+    }
+}
+
+impl Timestamp {
+    /// Resolve the timezone of `config` into a timer.
+    ///
+    /// The local offset is read once, when the timer is created. If it cannot
+    /// be determined, e.g. because other threads are already running, UTC is
+    /// used instead.
+    pub fn from_config(config: &LoggerTimestamp) -> Result<Self> {
+        let timezone = config.timezone.as_deref().map(str::trim).unwrap_or("utc");
+        let offset = match timezone.to_lowercase().as_str() {
+            "" | "utc" | "z" => UtcOffset::UTC,
+            "local" => UtcOffset::current_local_offset().unwrap_or_else(|_| {
+                eprintln!("Failed to determine the local UTC offset, using UTC for log timestamps");
+                UtcOffset::UTC
+            }),
+            _ => parse_offset(timezone)?,
+        };
+        Ok(Self {
+            format: config.format,
+            offset,
+            precision: config.precision,
+        })
+    }
+
+    /// Whether timestamps are turned off.
+    pub fn is_disabled(&self) -> bool {
+        self.format == TimestampFormat::None
+    }
+
+    fn write(&self, w: &mut impl fmt::Write, now: OffsetDateTime) -> fmt::Result {
+        match self.format {
+            TimestampFormat::None => Ok(()),
+            TimestampFormat::UnixMillis => write!(w, "{}", now.unix_timestamp_nanos() / 1_000_000),
+            TimestampFormat::UnixNanos => write!(w, "{}", now.unix_timestamp_nanos()),
+            TimestampFormat::Rfc3339 => {
+                let now = now.to_offset(self.offset);
+                write!(
+                    w,
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    now.year(),
+                    u8::from(now.month()),
+                    now.day(),
+                    now.hour(),
+                    now.minute(),
+                    now.second()
+                )?;
+                let nanos = now.nanosecond();
+                match self.precision {
+                    TimestampPrecision::Seconds => {}
+                    TimestampPrecision::Millis => write!(w, ".{:03}", nanos / 1_000_000)?,
+                    TimestampPrecision::Micros => write!(w, ".{:06}", nanos / 1_000)?,
+                    TimestampPrecision::Nanos => write!(w, ".{nanos:09}")?,
+                }
+                if self.offset.is_utc() {
+                    w.write_char('Z')
+                } else {
+                    let (hours, minutes, _) = self.offset.as_hms();
+                    let sign = if self.offset.is_negative() { '-' } else { '+' };
+                    write!(w, "{sign}{:02}:{:02}", hours.abs(), minutes.abs())
+                }
+            }
+        }
+    }
+}
+
+impl FormatTime for Timestamp {
+    fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+        self.write(w, OffsetDateTime::now_utc())
+    }
+}
+
+/// Parse a fixed offset such as `+02:00`, `-0530` or `+1`.
+fn parse_offset(s: &str) -> Result<UtcOffset> {
+    let invalid =
+        || anyhow!("Invalid timezone '{s}', expected utc, local or an offset like +02:00");
+    let (sign, rest) = match s.as_bytes().first() {
+        Some(b'+') => (1, &s[1..]),
+        Some(b'-') => (-1, &s[1..]),
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let hours: i8 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i8 = minutes.parse().map_err(|_| invalid())?;
+    UtcOffset::from_hms(sign * hours, sign * minutes, 0).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn render(config: LoggerTimestamp) -> String {
+        let mut out = String::new();
+        Timestamp::from_config(&config)
+            .unwrap()
+            .write(&mut out, datetime!(2024-01-01 12:00:00.123456789 UTC))
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_rfc3339_precision_and_offset() {
+        assert_eq!(
+            render(LoggerTimestamp::default()),
+            "2024-01-01T12:00:00.123456Z"
+        );
+        assert_eq!(
+            render(LoggerTimestamp::default().with_precision(TimestampPrecision::Seconds)),
+            "2024-01-01T12:00:00Z"
+        );
+        assert_eq!(
+            render(
+                LoggerTimestamp::default()
+                    .with_precision(TimestampPrecision::Millis)
+                    .with_timezone("+02:00")
+            ),
+            "2024-01-01T14:00:00.123+02:00"
+        );
+        assert_eq!(
+            render(
+                LoggerTimestamp::default()
+                    .with_precision(TimestampPrecision::Nanos)
+                    .with_timezone("-0530")
+            ),
+            "2024-01-01T06:30:00.123456789-05:30"
+        );
+    }
+
+    #[test]
+    fn test_unix_and_disabled_formats() {
+        let format = |format| render(LoggerTimestamp::default().with_format(format));
+        assert_eq!(format(TimestampFormat::UnixMillis), "1704110400123");
+        assert_eq!(format(TimestampFormat::UnixNanos), "1704110400123456789");
+        assert_eq!(format(TimestampFormat::None), "");
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(
+            parse_offset("+1").unwrap(),
+            UtcOffset::from_hms(1, 0, 0).unwrap()
+        );
+        assert!(parse_offset("02:00").is_err());
+        assert!(parse_offset("+26:00").is_err());
+        assert!(Timestamp::from_config(&LoggerTimestamp::default().with_timezone("mars")).is_err());
+    }
+}
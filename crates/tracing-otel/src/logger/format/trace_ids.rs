@@ -0,0 +1,198 @@
+//! Adds the current OpenTelemetry trace context to formatted events.
+//!
+//! [`WithTraceIds`] wraps another event formatter and appends the trace id,
+//! span id and sampled flag of the current span to its output:
+//!
+//! ```text
+//! 2024-01-01T00:00:00.000000Z  INFO app: user logged in user_id=7 trace_id=4bf92f3577b34da6a3ce929d0e0e4736 span_id=00f067aa0ba902b7 trace_sampled=true
+//! ```
+//!
+//! JSON output gets the same values as top-level keys.
+
+use super::current_trace_ids;
+use crate::logger::LoggerTraceIds;
+use std::fmt;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::{
+    fmt::{FmtContext, FormatEvent, FormatFields, format::Writer},
+    registry::LookupSpan,
+};
+
+/// How the trace context is added to the wrapped formatter's output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceIdsStyle {
+    /// Append ` key=value` pairs to the end of the line.
+    KeyValue,
+    /// Add keys to the top-level JSON object.
+    Json,
+}
+
+/// An event formatter that adds the current trace id, span id and sampled
+/// flag to the output of another formatter.
+///
+/// The inner formatter writes into a buffer that has ANSI escapes disabled, so
+/// colored output must be enabled on it directly, e.g. with `Format::with_ansi`.
+#[derive(Debug, Clone)]
+pub struct WithTraceIds<F> {
+    inner: F,
+    style: TraceIdsStyle,
+    config: LoggerTraceIds,
+}
+
+impl<F> WithTraceIds<F> {
+    /// Wrap `inner`, adding the trace context in the given style.
+    pub fn new(inner: F, style: TraceIdsStyle, config: LoggerTraceIds) -> Self {
+        Self {
+            inner,
+            style,
+            config,
+        }
+    }
+}
+
+impl<S, N, F> FormatEvent<S, N> for WithTraceIds<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let ids = self.config.enable.then(current_trace_ids).flatten();
+        let Some(ids) = ids else {
+            return self.inner.format_event(ctx, writer, event);
+        };
+
+        // Format into a buffer so the ids can be placed before the line ending
+        let mut buf = String::new();
+        self.inner.format_event(ctx, Writer::new(&mut buf), event)?;
+        let body = buf.trim_end_matches(['\n', '\r']);
+        let line_ending = &buf[body.len()..];
+
+        let (trace_id, span_id) = (ids.trace_id.to_string(), ids.span_id.to_string());
+        match self.style {
+            TraceIdsStyle::KeyValue => {
+                writer.write_str(body)?;
+                write!(
+                    writer,
+                    " {}={trace_id} {}={span_id} {}={}",
+                    self.config.trace_id_key,
+                    self.config.span_id_key,
+                    self.config.sampled_key,
+                    ids.sampled
+                )?;
+            }
+            TraceIdsStyle::Json => {
+                let Some(object) = body.strip_suffix('}') else {
+                    return writer.write_str(&buf);
+                };
+                writer.write_str(object)?;
+                if !object.trim_end().ends_with('{') {
+                    writer.write_char(',')?;
+                }
+                write!(
+                    writer,
+                    "{}:{},{}:{},{}:{}}}",
+                    json_string(&self.config.trace_id_key),
+                    json_string(&trace_id),
+                    json_string(&self.config.span_id_key),
+                    json_string(&span_id),
+                    json_string(&self.config.sampled_key),
+                    ids.sampled
+                )?;
+            }
+        }
+        writer.write_str(line_ending)
+    }
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{value}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::TestWriter;
+    use super::*;
+    use crate::otel::opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use serde_json::Value;
+    use tracing_subscriber::{
+        Registry,
+        fmt::{self as tfmt, format::JsonFields},
+        layer::SubscriberExt,
+    };
+
+    /// Log one event outside and one inside an OpenTelemetry span.
+    fn capture<F>(event_format: F) -> Vec<String>
+    where
+        F: FormatEvent<Registry, JsonFields> + Send + Sync + 'static,
+    {
+        let writer = TestWriter::default();
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(
+                tfmt::layer()
+                    .with_writer(writer.clone())
+                    .fmt_fields(JsonFields::new())
+                    .event_format(event_format),
+            )
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("outside");
+            let span = tracing::info_span!("request");
+            let _entered = span.enter();
+            tracing::info!(user_id = 7, "inside");
+        });
+        writer.output().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_key_value_trace_ids() {
+        let format = tfmt::format().compact().with_ansi(false);
+        let lines = capture(WithTraceIds::new(
+            format,
+            TraceIdsStyle::KeyValue,
+            LoggerTraceIds::default(),
+        ));
+
+        assert!(!lines[0].contains("trace_id="));
+        let inside: Vec<_> = lines[1].split(' ').collect();
+        let trace_id = inside[inside.len() - 3].strip_prefix("trace_id=").unwrap();
+        let span_id = inside[inside.len() - 2].strip_prefix("span_id=").unwrap();
+        assert_eq!(trace_id.len(), 32);
+        assert_eq!(span_id.len(), 16);
+        assert_eq!(inside[inside.len() - 1], "trace_sampled=true");
+    }
+
+    #[test]
+    fn test_json_trace_ids_with_custom_keys() {
+        let format = tfmt::format().json().flatten_event(true);
+        let config = LoggerTraceIds::default().with_keys("traceId", "spanId", "sampled");
+        let lines = capture(WithTraceIds::new(format, TraceIdsStyle::Json, config));
+
+        let outside: Value = serde_json::from_str(&lines[0]).unwrap();
+        assert!(outside.get("traceId").is_none());
+        let inside: Value = serde_json::from_str(&lines[1]).unwrap();
+        assert_eq!(inside["message"], "inside");
+        assert_eq!(inside["user_id"], 7);
+        assert_eq!(inside["traceId"].as_str().unwrap().len(), 32);
+        assert_eq!(inside["spanId"].as_str().unwrap().len(), 16);
+        assert_eq!(inside["sampled"], true);
+    }
+
+    #[test]
+    fn test_disabled_trace_ids() {
+        let format = tfmt::format().compact().with_ansi(false);
+        let lines = capture(WithTraceIds::new(
+            format,
+            TraceIdsStyle::KeyValue,
+            LoggerTraceIds::disabled(),
+        ));
+        assert!(!lines[1].contains("trace_id="));
+    }
+}
@@ -4,21 +4,36 @@ use crate::otel::OtelGuard;
 use anyhow::{Context, Result};
 
 use super::config::Logger;
-use super::subscriber::{create_output_layers, setup_tracing};
+use super::rate_limit::RateLimitLayer;
+use super::redact::Redactor;
+use super::sampled::SampledOnlyFilter;
+use super::subscriber::{
+    SetupOptions, create_output_layers, init_env_filter_from_logger, init_sink_filter,
+    setup_tracing_with_options,
+};
 
 /// Initialize tracing from a Logger configuration
 pub fn init_tracing_from_logger(logger: Logger) -> Result<OtelGuard> {
     let layers = create_output_layers(&logger)?;
-    let guard = setup_tracing(
-        &logger.service_name,
-        &logger.attributes,
-        logger.sample_ratio,
-        logger.metrics_interval_secs,
-        logger.level,
-        layers,
-        logger.otel_logs_enabled,
-    )
-    .context("Failed to initialize tracing")?;
+    let env_filter = init_env_filter_from_logger(&logger)?;
+    let otel_logs_filter =
+        init_sink_filter(&logger, logger.otel_logs_filter_directives().as_deref())?;
+    let redactor =
+        Redactor::from_config(&logger.redaction).context("Invalid log redaction settings")?;
+    let options = SetupOptions::new(logger.service_name.clone())
+        .with_attributes(logger.attributes.clone())
+        .with_sample_ratio(logger.sample_ratio)
+        .with_metrics_interval_secs(logger.metrics_interval_secs)
+        .with_env_filter(env_filter)
+        .with_layers(layers)
+        .with_otel_logs_enabled(logger.otel_logs_enabled)
+        .with_otel_logs_filter(otel_logs_filter)
+        .with_redactor(redactor)
+        .with_rate_limit(RateLimitLayer::from_config(&logger.rate_limit))
+        .with_otel_logs_sampled_only(SampledOnlyFilter::from_config(
+            &logger.otel_logs_sampled_only_or_default(),
+        ));
+    let guard = setup_tracing_with_options(options).context("Failed to initialize tracing")?;
     Ok(guard)
 }
 
@@ -8,11 +8,17 @@
 //!
 //! - Builder-style configuration API
 //! - Environment variable support (with "env" feature)
-//! - Multiple log formats (compact, pretty, json)
+//! - Multiple log formats (compact, pretty, json, logfmt, ecs, gelf)
+//! - Trace id, span id and sampled flag on every formatted log line
+//! - Redaction of sensitive fields by key or value pattern
+//! - Rate limiting of repeated events with suppressed-count summaries
+//! - Dropping of events belonging to unsampled traces, per output
+//! - Per-target filter directives (`EnvFilter` syntax)
 //! - Configurable sampling and metrics collection
 //! - Custom resource attributes
 //! - Optional console output
-//! - Optional file output
+//! - Optional file output with time/size rotation, compression and retention
+//! - Optional syslog (RFC 5424) and journald output
 //!
 //! # Quick Start
 //!
@@ -62,16 +68,31 @@ mod config;
 mod deserialize;
 #[cfg(feature = "env")]
 mod env;
+pub mod format;
 mod init;
+mod rate_limit;
+mod redact;
+mod rolling;
+mod sampled;
 mod subscriber;
+mod syslog;
 
 // Re-exports
-pub use config::{LogFormat, LogRollingRotation, Logger, LoggerFileAppender};
+pub use config::{
+    FilterPrecedence, JsonSpans, LogFormat, LogRollingRotation, Logger, LoggerFileAppender,
+    LoggerJournald, LoggerJson, LoggerRateLimit, LoggerRedaction, LoggerSampledOnly, LoggerSyslog,
+    LoggerTimestamp, LoggerTraceIds, RateLimitKey, RedactMode, TimestampFormat, TimestampPrecision,
+};
 pub use deserialize::default;
 #[cfg(feature = "env")]
 pub use env::{init_logger_from_env, init_logging_from_env};
 pub use init::{init_logging, init_tracing_from_logger};
+pub use rate_limit::{RATE_LIMIT_TARGET, RateLimitLayer};
+pub use redact::Redactor;
+pub use rolling::{RollingFileWriter, RollingPolicy};
+pub use sampled::SampledOnlyFilter;
 pub use subscriber::*;
+pub use syslog::{SyslogFacility, SyslogLayer, SyslogTransport};
 
 // Re-export FmtSpan
 pub use tracing_subscriber::fmt::format::FmtSpan;
@@ -0,0 +1,397 @@
+//! Rate limiting and duplicate suppression of events.
+
+use crate::logger::{LoggerRateLimit, RateLimitKey};
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    mem,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+use tracing::{
+    Dispatch, Event, Level, Subscriber,
+    callsite::Callsite as _,
+    dispatcher::WeakDispatch,
+    field::{Field, Value, Visit},
+    metadata::Kind,
+};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Target of the summary events, which are never rate limited themselves.
+///
+/// Summaries are filtered by this target rather than by the target of the
+/// suppressed events, so output filters must allow it for them to show.
+pub const RATE_LIMIT_TARGET: &str = module_path!();
+
+/// A layer that drops events once a key exceeds its budget for the current
+/// window, following a [`LoggerRateLimit`] configuration.
+///
+/// Suppressed events are dropped from every layer of the subscriber, so this
+/// layer must wrap the outputs rather than sit next to them:
+///
+/// ```rust
+/// use tracing_otel_extra::{LoggerRateLimit, logger::RateLimitLayer};
+/// use tracing_subscriber::{Layer as _, layer::SubscriberExt as _};
+///
+/// let outputs = vec![tracing_subscriber::fmt::layer().boxed()];
+/// let subscriber = tracing_subscriber::registry()
+///     .with(outputs.and_then(RateLimitLayer::new(&LoggerRateLimit::enabled())));
+/// ```
+///
+/// When a key had suppressed events, a summary event with their number is
+/// emitted at their level through the whole subscriber, after the next event
+/// that is recorded once the window is over. Without a later event, no
+/// summary is sent. Summaries have the [`RATE_LIMIT_TARGET`] target, so
+/// filters that only allow the targets of the suppressed events drop them.
+#[derive(Debug)]
+pub struct RateLimitLayer {
+    key: RateLimitKey,
+    max_events: u32,
+    window: Duration,
+    state: Mutex<State>,
+    dispatch: OnceLock<WeakDispatch>,
+}
+
+#[derive(Debug)]
+struct State {
+    windows: HashMap<u64, Window>,
+    pending: Vec<Summary>,
+    last_sweep: Instant,
+}
+
+#[derive(Debug)]
+struct Window {
+    started: Instant,
+    count: u32,
+    suppressed: u64,
+    level: Level,
+    target: &'static str,
+    callsite: &'static str,
+    message: Option<String>,
+}
+
+impl Window {
+    fn summary(&self) -> Option<Summary> {
+        (self.suppressed > 0).then(|| Summary {
+            level: self.level,
+            target: self.target,
+            callsite: self.callsite,
+            message: self.message.clone(),
+            suppressed: self.suppressed,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Summary {
+    level: Level,
+    target: &'static str,
+    callsite: &'static str,
+    message: Option<String>,
+    suppressed: u64,
+}
+
+impl RateLimitLayer {
+    /// Create a layer with the budget of `config`.
+    ///
+    /// The layer limits events even if `config.enable` is false.
+    pub fn new(config: &LoggerRateLimit) -> Self {
+        Self {
+            key: config.key,
+            max_events: config.max_events,
+            window: Duration::from_secs(config.window_secs.max(1)),
+            state: Mutex::new(State {
+                windows: HashMap::new(),
+                pending: Vec::new(),
+                last_sweep: Instant::now(),
+            }),
+            dispatch: OnceLock::new(),
+        }
+    }
+
+    /// Create a layer if rate limiting is enabled in `config`.
+    pub fn from_config(config: &LoggerRateLimit) -> Option<Self> {
+        config.enable.then(|| Self::new(config))
+    }
+
+    /// Returns whether `event` fits in its budget, counting it either way.
+    fn admit(&self, event: &Event<'_>, now: Instant) -> bool {
+        let metadata = event.metadata();
+        let message = match self.key {
+            RateLimitKey::Callsite => None,
+            RateLimitKey::Message => MessageVisitor::message(event),
+        };
+        let mut hasher = DefaultHasher::new();
+        match &message {
+            Some(message) => (metadata.level(), metadata.target(), message).hash(&mut hasher),
+            None => metadata.callsite().hash(&mut hasher),
+        }
+        let key = hasher.finish();
+
+        let Ok(mut state) = self.state.lock() else {
+            return true;
+        };
+        let state = &mut *state;
+        if now.duration_since(state.last_sweep) >= self.window {
+            // Report and forget the windows of keys that went quiet
+            state.windows.retain(|_, window| {
+                let expired = now.duration_since(window.started) >= self.window;
+                if expired {
+                    state.pending.extend(window.summary());
+                }
+                !expired
+            });
+            state.last_sweep = now;
+        }
+
+        let window = state.windows.entry(key).or_insert_with(|| Window {
+            started: now,
+            count: 0,
+            suppressed: 0,
+            level: *metadata.level(),
+            target: metadata.target(),
+            callsite: metadata.name(),
+            message,
+        });
+        if now.duration_since(window.started) >= self.window {
+            state.pending.extend(window.summary());
+            window.started = now;
+            window.count = 0;
+            window.suppressed = 0;
+        }
+        if window.count < self.max_events {
+            window.count += 1;
+            true
+        } else {
+            window.suppressed += 1;
+            false
+        }
+    }
+
+    fn take_pending(&self) -> Vec<Summary> {
+        self.state
+            .lock()
+            .map(|mut state| mem::take(&mut state.pending))
+            .unwrap_or_default()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RateLimitLayer {
+    fn on_register_dispatch(&self, subscriber: &Dispatch) {
+        let _ = self.dispatch.set(subscriber.downgrade());
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, _ctx: Context<'_, S>) -> bool {
+        event.metadata().target() == RATE_LIMIT_TARGET || self.admit(event, Instant::now())
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if event.metadata().target() == RATE_LIMIT_TARGET {
+            return;
+        }
+        let pending = self.take_pending();
+        if pending.is_empty() {
+            return;
+        }
+        let Some(dispatch) = self.dispatch.get().and_then(WeakDispatch::upgrade) else {
+            return;
+        };
+        for summary in pending {
+            emit_summary(&dispatch, summary);
+        }
+    }
+}
+
+/// Dispatch a summary event.
+///
+/// Macros emitting events while another event is being dispatched are
+/// discarded, so the event is built from its callsite and handed to
+/// `dispatch` directly. It is only sent if the filters of `dispatch` enable
+/// [`RATE_LIMIT_TARGET`] at the level of the suppressed events.
+fn emit_summary(dispatch: &Dispatch, summary: Summary) {
+    macro_rules! summary_callsite {
+        ($lvl:expr) => {
+            tracing::callsite! {
+                name: "rate limit summary",
+                kind: Kind::EVENT,
+                target: RATE_LIMIT_TARGET,
+                level: $lvl,
+                fields: message, suppressed, suppressed.target, suppressed.callsite, suppressed.message
+            }
+        };
+    }
+    let callsite = match summary.level {
+        Level::ERROR => summary_callsite!(Level::ERROR),
+        Level::WARN => summary_callsite!(Level::WARN),
+        Level::INFO => summary_callsite!(Level::INFO),
+        Level::DEBUG => summary_callsite!(Level::DEBUG),
+        Level::TRACE => summary_callsite!(Level::TRACE),
+    };
+    let metadata = callsite.metadata();
+    // The cached interest is not consulted: a callsite registered during a
+    // dispatch may have been registered with no subscriber at all.
+    if !dispatch.enabled(metadata) {
+        return;
+    }
+
+    let fields = metadata.fields();
+    let field = |name| fields.field(name).expect("summary field is declared");
+    let (message, suppressed, target, site, original) = (
+        field("message"),
+        field("suppressed"),
+        field("suppressed.target"),
+        field("suppressed.callsite"),
+        field("suppressed.message"),
+    );
+    let text = format_args!("Suppressed {} events by rate limit", summary.suppressed);
+    let values: [(&Field, Option<&dyn Value>); 5] = [
+        (&message, Some(&text)),
+        (&suppressed, Some(&summary.suppressed)),
+        (&target, Some(&summary.target)),
+        (&site, Some(&summary.callsite)),
+        (&original, summary.message.as_ref().map(|m| m as &dyn Value)),
+    ];
+    dispatch.event(&Event::new(metadata, &fields.value_set(&values)));
+}
+
+/// Reads the message of an event.
+#[derive(Default)]
+struct MessageVisitor(Option<String>);
+
+impl MessageVisitor {
+    fn message(event: &Event<'_>) -> Option<String> {
+        let mut visitor = Self::default();
+        event.record(&mut visitor);
+        visitor.0
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::format::test_support::TestWriter;
+    use super::*;
+    use std::sync::Arc;
+    use tracing_subscriber::{Registry, filter::LevelFilter, layer::SubscriberExt};
+
+    /// Runs a [`RateLimitLayer`] on a clock that the test advances.
+    struct FakeClock {
+        layer: RateLimitLayer,
+        now: Arc<Mutex<Instant>>,
+    }
+
+    impl<S: Subscriber> Layer<S> for FakeClock {
+        fn on_register_dispatch(&self, subscriber: &Dispatch) {
+            Layer::<S>::on_register_dispatch(&self.layer, subscriber);
+        }
+
+        fn event_enabled(&self, event: &Event<'_>, _ctx: Context<'_, S>) -> bool {
+            let now = *self.now.lock().unwrap();
+            event.metadata().target() == RATE_LIMIT_TARGET || self.layer.admit(event, now)
+        }
+
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            self.layer.on_event(event, ctx);
+        }
+    }
+
+    fn capture(config: LoggerRateLimit, f: impl FnOnce()) -> Vec<String> {
+        capture_with(RateLimitLayer::new(&config), f)
+    }
+
+    fn capture_with<L>(layer: L, f: impl FnOnce()) -> Vec<String>
+    where
+        L: Layer<Registry> + Send + Sync + 'static,
+    {
+        let writer = TestWriter::default();
+        let other = TestWriter::default();
+        let layers = vec![
+            tracing_subscriber::fmt::layer()
+                .with_writer(writer.clone())
+                .with_ansi(false)
+                .without_time()
+                .with_filter(LevelFilter::INFO)
+                .boxed(),
+            tracing_subscriber::fmt::layer()
+                .with_writer(other.clone())
+                .with_filter(LevelFilter::WARN)
+                .boxed(),
+        ];
+        let subscriber = tracing_subscriber::registry().with(layers.and_then(layer));
+        tracing::subscriber::with_default(subscriber, f);
+        writer.output().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_limits_per_callsite() {
+        let lines = capture(LoggerRateLimit::enabled().with_max_events(3), || {
+            for attempt in 0..100 {
+                tracing::warn!(attempt, "retrying");
+            }
+            tracing::info!("done");
+        });
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line.contains("retrying"))
+                .count(),
+            3
+        );
+        assert!(lines.iter().any(|line| line.contains("done")), "{lines:?}");
+    }
+
+    #[test]
+    fn test_summary_after_window() {
+        let config = LoggerRateLimit::enabled()
+            .with_key(RateLimitKey::Message)
+            .with_max_events(2);
+        let layer = RateLimitLayer::new(&config);
+        let now = Arc::new(Mutex::new(Instant::now()));
+        let layer = FakeClock {
+            layer,
+            now: now.clone(),
+        };
+        let lines = capture_with(layer, || {
+            for attempt in 0..10 {
+                tracing::warn!(attempt, "connection refused");
+                tracing::warn!("unrelated");
+            }
+            tracing::info!("same window");
+            *now.lock().unwrap() += Duration::from_secs(1);
+            tracing::info!("next window");
+        });
+        let position = |text: &str| lines.iter().position(|line| line.contains(text));
+        let summary_at = position(r#"suppressed.message="connection refused""#)
+            .unwrap_or_else(|| panic!("{lines:?}"));
+        // Nothing is summarized until an event arrives after the window
+        assert!(position("same window") < Some(summary_at), "{lines:?}");
+        let summary = &lines[summary_at];
+        assert!(summary.contains(" WARN "), "{summary}");
+        assert!(
+            summary.contains("Suppressed 8 events by rate limit"),
+            "{summary}"
+        );
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line.contains("Suppressed"))
+                .count(),
+            2,
+            "{lines:?}"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Redaction of sensitive field values.
+
+use crate::logger::{LoggerRedaction, RedactMode};
+use crate::otel::Redact;
+use anyhow::{Context as _, Result, bail};
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use serde_json::Value;
+use sha2::Sha256;
+use std::{borrow::Cow, fmt::Write as _, sync::Arc};
+
+/// Replaces sensitive values following a [`LoggerRedaction`] configuration.
+///
+/// The same redactor is applied to the fmt outputs, syslog, span attributes
+/// and OpenTelemetry log records, so a value is hidden the same way everywhere.
+#[derive(Debug)]
+pub struct Redactor {
+    keys: Vec<String>,
+    patterns: Vec<Regex>,
+    replacement: Replacement,
+}
+
+/// What a redacted value is replaced with.
+enum Replacement {
+    Mask(String),
+    // Keyed so that a digest cannot be reversed by hashing guesses of
+    // low-entropy values such as card numbers or phone numbers.
+    Hash(Hmac<Sha256>),
+}
+
+impl std::fmt::Debug for Replacement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mask(mask) => f.debug_tuple("Mask").field(mask).finish(),
+            Self::Hash(_) => f.write_str("Hash"),
+        }
+    }
+}
+
+impl Redactor {
+    /// Compile the patterns of `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern is not a valid regular expression, or if
+    /// the mode is [`RedactMode::Hash`] without a `hash_secret`.
+    pub fn new(config: &LoggerRedaction) -> Result<Self> {
+        let patterns = config
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .with_context(|| format!("Invalid redaction pattern '{pattern}'"))
+            })
+            .collect::<Result<_>>()?;
+        let replacement = match config.mode {
+            RedactMode::Mask => Replacement::Mask(config.mask.clone()),
+            RedactMode::Hash => {
+                let secret = match config.hash_secret.as_deref() {
+                    Some(secret) if !secret.is_empty() => secret,
+                    _ => bail!("The hash redaction mode requires a hash_secret"),
+                };
+                let mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+                    .context("Invalid redaction hash_secret")?;
+                Replacement::Hash(mac)
+            }
+        };
+        Ok(Self {
+            keys: config.keys.iter().map(|key| normalize_key(key)).collect(),
+            patterns,
+            replacement,
+        })
+    }
+
+    /// Build a shared redactor if redaction is enabled in `config`.
+    pub fn from_config(config: &LoggerRedaction) -> Result<Option<Arc<Self>>> {
+        if !config.enable {
+            return Ok(None);
+        }
+        Self::new(config).map(|redactor| Some(Arc::new(redactor)))
+    }
+
+    /// Whether values of `key` are always redacted.
+    pub fn is_sensitive_key(&self, key: &str) -> bool {
+        let key = normalize_key(key.strip_prefix("r#").unwrap_or(key));
+        let last = key.rsplit('.').next().unwrap_or(&key);
+        self.keys
+            .iter()
+            .any(|denied| *denied == key || denied == last)
+    }
+
+    /// Returns the redacted form of `value`, or `None` if nothing is sensitive.
+    pub fn redact_str(&self, key: &str, value: &str) -> Option<String> {
+        if self.is_sensitive_key(key) {
+            return Some(self.replacement(value).into_owned());
+        }
+        let mut redacted = Cow::Borrowed(value);
+        for pattern in &self.patterns {
+            if pattern.is_match(&redacted) {
+                let replaced = pattern
+                    .replace_all(&redacted, |caps: &regex::Captures<'_>| {
+                        self.replacement(&caps[0]).into_owned()
+                    })
+                    .into_owned();
+                redacted = Cow::Owned(replaced);
+            }
+        }
+        match redacted {
+            Cow::Owned(redacted) => Some(redacted),
+            Cow::Borrowed(_) => None,
+        }
+    }
+
+    /// Redact a JSON value of the field `key` in place, descending into
+    /// objects and arrays.
+    pub fn redact_json(&self, key: &str, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                if self.is_sensitive_key(key) {
+                    *value = Value::String(self.replacement(&value.to_string()).into_owned());
+                } else {
+                    for (key, value) in map.iter_mut() {
+                        self.redact_json(key, value);
+                    }
+                }
+            }
+            Value::Array(values) => {
+                if self.is_sensitive_key(key) {
+                    *value = Value::String(self.replacement(&value.to_string()).into_owned());
+                } else {
+                    for value in values {
+                        self.redact_json(key, value);
+                    }
+                }
+            }
+            Value::String(s) => {
+                if let Some(redacted) = self.redact_str(key, s) {
+                    *s = redacted;
+                }
+            }
+            Value::Null => {}
+            other => {
+                if let Some(redacted) = self.redact_str(key, &other.to_string()) {
+                    *other = Value::String(redacted);
+                }
+            }
+        }
+    }
+
+    fn replacement<'a>(&'a self, value: &str) -> Cow<'a, str> {
+        match &self.replacement {
+            Replacement::Mask(mask) => Cow::Borrowed(mask),
+            Replacement::Hash(mac) => {
+                let mut mac = mac.clone();
+                mac.update(value.as_bytes());
+                let digest = mac.finalize().into_bytes();
+                let mut hashed = String::from("hmac-sha256:");
+                for byte in &digest[..8] {
+                    let _ = write!(hashed, "{byte:02x}");
+                }
+                Cow::Owned(hashed)
+            }
+        }
+    }
+}
+
+impl Redact for Redactor {
+    fn redact(&self, key: &str, value: &str) -> Option<String> {
+        self.redact_str(key, value)
+    }
+}
+
+/// Lowercase a key and treat `-` like `_`.
+fn normalize_key(key: &str) -> String {
+    key.trim().to_lowercase().replace('-', "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CARD: &str = r"\b(?:\d[ -]?){12,18}\d\b";
+
+    #[test]
+    fn test_key_deny_list() {
+        let redactor = Redactor::new(&LoggerRedaction::enabled().with_key("X-Session")).unwrap();
+        assert_eq!(
+            redactor.redact_str("password", "hunter2").as_deref(),
+            Some("[REDACTED]")
+        );
+        assert!(redactor.is_sensitive_key("Authorization"));
+        assert!(redactor.is_sensitive_key("http.request.header.authorization"));
+        assert!(redactor.is_sensitive_key("x_session"));
+        assert!(redactor.is_sensitive_key("r#token"));
+        assert_eq!(redactor.redact_str("user", "alice"), None);
+    }
+
+    #[test]
+    fn test_patterns_replace_matches_only() {
+        let redactor = Redactor::new(&LoggerRedaction::enabled().with_pattern(CARD)).unwrap();
+        assert_eq!(
+            redactor
+                .redact_str("message", "paid with 4111 1111 1111 1111 today")
+                .as_deref(),
+            Some("paid with [REDACTED] today")
+        );
+        assert_eq!(redactor.redact_str("message", "order 42"), None);
+        assert!(Redactor::new(&LoggerRedaction::enabled().with_pattern("(")).is_err());
+    }
+
+    #[test]
+    fn test_hash_mode() {
+        let config = LoggerRedaction::enabled().with_mode(RedactMode::Hash);
+        assert!(Redactor::new(&config).is_err());
+        assert!(Redactor::new(&config.clone().with_hash_secret("")).is_err());
+
+        let redactor = Redactor::new(&config.clone().with_hash_secret("key")).unwrap();
+        let hashed = redactor.redact_str("token", "abc").unwrap();
+        assert_eq!(hashed, "hmac-sha256:9c196e32dc0175f8");
+        assert_eq!(redactor.redact_str("token", "abc").unwrap(), hashed);
+
+        let other = Redactor::new(&config.with_hash_secret("other")).unwrap();
+        assert_ne!(other.redact_str("token", "abc").unwrap(), hashed);
+    }
+
+    #[test]
+    fn test_redact_json() {
+        let redactor = Redactor::new(&LoggerRedaction::enabled().with_pattern(CARD)).unwrap();
+        let mut value = json!({
+            "user": {"name": "alice", "password": "hunter2"},
+            "cookie": ["a", "b"],
+            "card": 4111111111111111u64,
+            "count": 3,
+        });
+        redactor.redact_json("fields", &mut value);
+        assert_eq!(
+            value,
+            json!({
+                "user": {"name": "alice", "password": "[REDACTED]"},
+                "cookie": "[REDACTED]",
+                "card": "[REDACTED]",
+                "count": 3,
+            })
+        );
+    }
+}
@@ -0,0 +1,549 @@
+//! Size- and time-based rolling file writer with compression and retention.
+//!
+//! [`RollingFileWriter`] is used instead of `tracing_appender::rolling` when a
+//! [`LoggerFileAppender`](super::LoggerFileAppender) sets a maximum file size,
+//! compression, or a size/age retention limit.
+//!
+//! The active file is always `{prefix}.{suffix}`. On rotation it is renamed to
+//! `{prefix}.{timestamp}.{suffix}` (with a counter before the suffix if that
+//! name is taken), then a background thread per writer optionally gzips it and
+//! prunes old files by count, total size and age. Failures of that thread are
+//! printed to stderr, as the writer may itself be the sink for log events.
+
+use anyhow::{Context, Result};
+use flate2::{Compression, write::GzEncoder};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, mpsc},
+    thread::JoinHandle,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use time::{OffsetDateTime, macros::format_description};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Rotation and retention settings for a [`RollingFileWriter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RollingPolicy {
+    /// Rotate when the wall clock enters a new period of this many seconds.
+    pub period_secs: Option<u64>,
+    /// Rotate when the active file would grow beyond this many bytes.
+    pub max_file_size: Option<u64>,
+    /// Keep at most this many files, including the active one.
+    pub max_files: usize,
+    /// Delete the oldest rotated files while all files exceed this many bytes.
+    ///
+    /// Checked on rotation, so the active file may add up to `max_file_size`.
+    pub max_total_size: Option<u64>,
+    /// Delete rotated files older than this.
+    pub max_age: Option<Duration>,
+    /// Gzip rotated files in the background.
+    pub compress: bool,
+}
+
+/// A file writer that rotates on size and/or time and prunes old files.
+#[derive(Debug, Clone)]
+pub struct RollingFileWriter {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    files: LogFiles,
+    policy: RollingPolicy,
+    file: File,
+    size: u64,
+    period: Option<u64>,
+    worker: Worker,
+}
+
+/// The thread compressing and pruning rotated files, joined on drop so
+/// pending work finishes before the writer goes away.
+#[derive(Debug)]
+struct Worker {
+    sender: Option<mpsc::Sender<PathBuf>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Naming of the active and rotated files in the log directory.
+#[derive(Debug, Clone)]
+struct LogFiles {
+    dir: PathBuf,
+    prefix: String,
+    suffix: String,
+}
+
+impl RollingFileWriter {
+    /// Create a writer appending to `{dir}/{prefix}.{suffix}`.
+    pub fn new(
+        dir: impl AsRef<Path>,
+        prefix: impl Into<String>,
+        suffix: impl Into<String>,
+        policy: RollingPolicy,
+    ) -> Result<Self> {
+        let files = LogFiles {
+            dir: dir.as_ref().to_path_buf(),
+            prefix: prefix.into(),
+            suffix: suffix.into(),
+        };
+        fs::create_dir_all(&files.dir)
+            .with_context(|| format!("Failed to create log directory {:?}", files.dir))?;
+
+        let file = files.open_active()?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        files.prune(&policy, size);
+        let worker = Worker::spawn(files.clone(), policy.clone())
+            .context("Failed to start log rotation thread")?;
+
+        Ok(Self {
+            state: Arc::new(Mutex::new(State {
+                period: current_period(&policy),
+                files,
+                policy,
+                file,
+                size,
+                worker,
+            })),
+        })
+    }
+
+    /// Path of the file currently being written.
+    pub fn active_path(&self) -> PathBuf {
+        self.lock().files.active_path()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl State {
+    fn should_rotate(&self, incoming: usize) -> bool {
+        let size_exceeded = self
+            .policy
+            .max_file_size
+            .is_some_and(|max| self.size > 0 && self.size + incoming as u64 > max);
+        size_exceeded || current_period(&self.policy) != self.period
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let active = self.files.active_path();
+        let rotated = self.files.next_rotated_path();
+        let renamed = fs::rename(&active, &rotated);
+        // Reopen the active file even if the rename failed (e.g. the file was
+        // removed), so writes go on; rotation is retried at the next limit
+        self.file = self.files.open_active().map_err(io::Error::other)?;
+        self.size = 0;
+        self.period = current_period(&self.policy);
+        match renamed {
+            Ok(()) => self.worker.send(rotated),
+            Err(error) => eprintln!("Failed to rotate log file {active:?}: {error}"),
+        }
+        Ok(())
+    }
+}
+
+impl Worker {
+    fn spawn(files: LogFiles, policy: RollingPolicy) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::channel::<PathBuf>();
+        let handle = std::thread::Builder::new()
+            .name("log-rotation".to_string())
+            .spawn(move || {
+                for rotated in receiver {
+                    if policy.compress
+                        && let Err(error) = compress(&rotated)
+                    {
+                        eprintln!("Failed to compress rotated log file {rotated:?}: {error}");
+                    }
+                    // The active file was empty when `rotated` was rotated out
+                    files.prune(&policy, 0);
+                }
+            })?;
+        Ok(Self {
+            sender: Some(sender),
+            handle: Some(handle),
+        })
+    }
+
+    fn send(&self, rotated: PathBuf) {
+        if let Some(sender) = &self.sender {
+            // The thread only stops once the sender is dropped
+            let _ = sender.send(rotated);
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(handle) = self.handle.take()
+            && handle.thread().id() != std::thread::current().id()
+        {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Write for RollingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.lock();
+        if state.should_rotate(buf.len()) {
+            state.rotate()?;
+        }
+        let written = state.file.write(buf)?;
+        state.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().file.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RollingFileWriter {
+    type Writer = RollingFileWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+impl LogFiles {
+    fn active_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.{}", self.prefix, self.suffix))
+    }
+
+    fn open_active(&self) -> Result<File> {
+        let path = self.active_path();
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open log file {path:?}"))
+    }
+
+    /// A unique rotated file name stamped with the current UTC time.
+    fn next_rotated_path(&self) -> PathBuf {
+        let stamp = OffsetDateTime::now_utc()
+            .format(format_description!(
+                "[year]-[month]-[day]-[hour][minute][second]"
+            ))
+            .unwrap_or_else(|_| unix_secs().to_string());
+        (0..)
+            .map(|n| match n {
+                0 => format!("{}.{stamp}.{}", self.prefix, self.suffix),
+                n => format!("{}.{stamp}.{n}.{}", self.prefix, self.suffix),
+            })
+            .map(|name| self.dir.join(name))
+            .find(|path| !path.exists() && !gz_path(path).exists())
+            .expect("unbounded iterator always yields a free name")
+    }
+
+    /// Rotated files (plain or gzipped) of this writer, oldest first.
+    fn rotated_files(&self) -> Vec<(PathBuf, fs::Metadata)> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let key = self.rotated_key(&entry.file_name().to_string_lossy())?;
+                let meta = entry.metadata().ok().filter(|meta| meta.is_file())?;
+                let modified = meta.modified().unwrap_or(UNIX_EPOCH);
+                Some(((modified, key), entry.path(), meta))
+            })
+            .collect();
+        files.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+        files
+            .into_iter()
+            .map(|(_, path, meta)| (path, meta))
+            .collect()
+    }
+
+    /// The stamp and counter of a file named `{prefix}.{stamp}[.{n}].{suffix}[.gz]`,
+    /// as written by [`Self::next_rotated_path`].
+    fn rotated_key(&self, name: &str) -> Option<(String, u64)> {
+        let name = name.strip_suffix(".gz").unwrap_or(name);
+        let rotated = name
+            .strip_prefix(self.prefix.as_str())?
+            .strip_prefix('.')?
+            .strip_suffix(self.suffix.as_str())?
+            .strip_suffix('.')?;
+        let (stamp, counter) = match rotated.split_once('.') {
+            Some((stamp, counter)) if counter.bytes().all(|b| b.is_ascii_digit()) => {
+                (stamp, counter.parse().ok()?)
+            }
+            Some(_) => return None,
+            None => (rotated, 0),
+        };
+        let is_stamp = !stamp.is_empty() && stamp.bytes().all(|b| b.is_ascii_digit() || b == b'-');
+        is_stamp.then(|| (stamp.to_string(), counter))
+    }
+
+    /// Delete rotated files beyond the count, age and total size limits,
+    /// counting `active_size` bytes for the active file.
+    fn prune(&self, policy: &RollingPolicy, active_size: u64) {
+        let mut files = self.rotated_files();
+        let now = SystemTime::now();
+        let remove = |path: &Path| {
+            if let Err(error) = fs::remove_file(path) {
+                eprintln!("Failed to remove old log file {path:?}: {error}");
+            }
+        };
+
+        let excess = files
+            .len()
+            .saturating_sub(policy.max_files.saturating_sub(1));
+        for (path, _) in files.drain(..excess) {
+            remove(&path);
+        }
+
+        if let Some(max_age) = policy.max_age {
+            files.retain(|(path, meta)| {
+                let age = meta
+                    .modified()
+                    .ok()
+                    .and_then(|modified| now.duration_since(modified).ok());
+                if age.is_some_and(|age| age > max_age) {
+                    remove(path);
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        if let Some(max_total_size) = policy.max_total_size {
+            let mut total: u64 = active_size + files.iter().map(|(_, m)| m.len()).sum::<u64>();
+            for (path, meta) in files {
+                if total <= max_total_size {
+                    break;
+                }
+                remove(&path);
+                total = total.saturating_sub(meta.len());
+            }
+        }
+    }
+}
+
+/// Gzip `path` into `path.gz` and remove the original.
+fn compress(path: &Path) -> io::Result<()> {
+    let target = gz_path(path);
+    let mut input = File::open(path)?;
+    let mut encoder = GzEncoder::new(File::create(&target)?, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::remove_file(path)
+}
+
+fn gz_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn current_period(policy: &RollingPolicy) -> Option<u64> {
+    policy
+        .period_secs
+        .filter(|secs| *secs > 0)
+        .map(|secs| unix_secs() / secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_policy() -> RollingPolicy {
+        RollingPolicy {
+            period_secs: None,
+            max_file_size: Some(64),
+            max_files: 10,
+            max_total_size: None,
+            max_age: None,
+            compress: false,
+        }
+    }
+
+    fn rotated_count(dir: &Path) -> usize {
+        fs::read_dir(dir).unwrap().count() - 1
+    }
+
+    #[test]
+    fn test_rotates_on_max_file_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RollingFileWriter::new(dir.path(), "app", "log", test_policy()).unwrap();
+
+        for _ in 0..4 {
+            writer.write_all(&[b'x'; 40]).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert_eq!(rotated_count(dir.path()), 3);
+        assert_eq!(fs::metadata(writer.active_path()).unwrap().len(), 40);
+    }
+
+    #[test]
+    fn test_prunes_by_count_and_total_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = RollingPolicy {
+            max_files: 3,
+            ..test_policy()
+        };
+        let mut writer = RollingFileWriter::new(dir.path(), "app", "log", policy).unwrap();
+        for _ in 0..6 {
+            writer.write_all(&[b'x'; 40]).unwrap();
+        }
+        // Dropping the writer waits for the rotation thread
+        drop(writer);
+        assert_eq!(rotated_count(dir.path()), 2);
+
+        let dir = tempfile::tempdir().unwrap();
+        let policy = RollingPolicy {
+            max_total_size: Some(100),
+            ..test_policy()
+        };
+        let mut writer = RollingFileWriter::new(dir.path(), "app", "log", policy).unwrap();
+        for _ in 0..6 {
+            writer.write_all(&[b'x'; 40]).unwrap();
+        }
+        drop(writer);
+        // Pruning runs on rotation, when the new active file is still empty.
+        assert_eq!(rotated_count(dir.path()), 2);
+    }
+
+    #[test]
+    fn test_rotates_on_new_period() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = RollingPolicy {
+            period_secs: Some(3600),
+            max_file_size: None,
+            ..test_policy()
+        };
+        let mut writer = RollingFileWriter::new(dir.path(), "app", "log", policy).unwrap();
+        writer.write_all(b"first\n").unwrap();
+        writer.write_all(b"second\n").unwrap();
+        assert_eq!(rotated_count(dir.path()), 0);
+
+        // Pretend the first period has passed
+        writer.lock().period = Some(0);
+        writer.write_all(b"third\n").unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(rotated_count(dir.path()), 1);
+        assert_eq!(fs::read_to_string(writer.active_path()).unwrap(), "third\n");
+    }
+
+    #[test]
+    fn test_reopens_active_file_when_rename_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RollingFileWriter::new(dir.path(), "app", "log", test_policy()).unwrap();
+        writer.write_all(&[b'x'; 40]).unwrap();
+        fs::remove_file(writer.active_path()).unwrap();
+
+        writer.write_all(&[b'y'; 40]).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(fs::read(writer.active_path()).unwrap(), [b'y'; 40]);
+    }
+
+    #[test]
+    fn test_prunes_by_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let touch = |name: &str, age: Duration| {
+            let file = File::create(dir.path().join(name)).unwrap();
+            file.set_modified(SystemTime::now() - age).unwrap();
+        };
+        touch("app.2020-01-01-000000.log.gz", Duration::from_secs(7200));
+        touch("app.2020-01-01-010000.log", Duration::from_secs(60));
+        touch("app.errors.log", Duration::from_secs(7200));
+
+        let policy = RollingPolicy {
+            max_age: Some(Duration::from_secs(3600)),
+            ..test_policy()
+        };
+        // Old files are pruned when the writer starts
+        let writer = RollingFileWriter::new(dir.path(), "app", "log", policy).unwrap();
+        drop(writer);
+
+        let mut names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["app.2020-01-01-010000.log", "app.errors.log", "app.log"]
+        );
+    }
+
+    #[test]
+    fn test_rotated_files_match_own_names_in_counter_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = LogFiles {
+            dir: dir.path().to_path_buf(),
+            prefix: "app".to_string(),
+            suffix: "log".to_string(),
+        };
+        let modified = SystemTime::now() - Duration::from_secs(60);
+        for name in [
+            "app.log",
+            "app.errors.log",
+            "app.errors.2020-01-01-000000.log",
+            "app.2020-01-01-000000.10.log",
+            "app.2020-01-01-000000.2.log.gz",
+            "app.2020-01-01-000000.log",
+            "other.2020-01-01-000000.log",
+        ] {
+            let file = File::create(dir.path().join(name)).unwrap();
+            file.set_modified(modified).unwrap();
+        }
+
+        let names: Vec<_> = files
+            .rotated_files()
+            .into_iter()
+            .map(|(path, _)| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "app.2020-01-01-000000.log",
+                "app.2020-01-01-000000.2.log.gz",
+                "app.2020-01-01-000000.10.log",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compresses_rotated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = RollingPolicy {
+            compress: true,
+            ..test_policy()
+        };
+        let mut writer = RollingFileWriter::new(dir.path(), "app", "log", policy).unwrap();
+        writer.write_all(&[b'x'; 40]).unwrap();
+        writer.write_all(&[b'x'; 40]).unwrap();
+        drop(writer);
+
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        assert!(
+            names.iter().any(|name| name.ends_with(".log.gz")),
+            "Expected rotated file to be gzipped: {names:?}"
+        );
+        assert_eq!(names.len(), 2, "{names:?}");
+    }
+}
@@ -0,0 +1,134 @@
+//! Dropping of events that belong to unsampled traces.
+
+use crate::logger::{LoggerSampledOnly, format::current_trace_ids};
+use tracing::{Event, Level, Metadata, Subscriber, subscriber::Interest};
+use tracing_subscriber::{
+    filter::LevelFilter,
+    layer::{Context, Filter},
+};
+
+/// A per-layer filter that drops events inside spans whose OpenTelemetry
+/// trace is not sampled, following a [`LoggerSampledOnly`] configuration.
+///
+/// Events outside any trace, and events at `always_level` or more severe,
+/// always pass. Combine it with a sink's own filter:
+///
+/// ```rust
+/// use tracing_otel_extra::{LoggerSampledOnly, logger::SampledOnlyFilter};
+/// use tracing_subscriber::{
+///     Layer as _,
+///     filter::{FilterExt as _, LevelFilter},
+///     layer::SubscriberExt as _,
+/// };
+///
+/// let sampled = SampledOnlyFilter::new(&LoggerSampledOnly::enabled());
+/// let subscriber = tracing_subscriber::registry()
+///     .with(tracing_subscriber::fmt::layer().with_filter(LevelFilter::INFO.and(sampled)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SampledOnlyFilter {
+    always_level: Level,
+}
+
+impl SampledOnlyFilter {
+    /// Create a filter keeping events at `config.always_level` or more severe.
+    ///
+    /// The filter drops events even if `config.enable` is false.
+    pub fn new(config: &LoggerSampledOnly) -> Self {
+        Self {
+            always_level: config.always_level,
+        }
+    }
+
+    /// Create a filter if dropping is enabled in `config`.
+    pub fn from_config(config: &LoggerSampledOnly) -> Option<Self> {
+        config.enable.then(|| Self::new(config))
+    }
+
+    /// Returns whether an event at `level` in the current span passes.
+    fn admits(&self, level: &Level) -> bool {
+        *level <= self.always_level || current_trace_ids().is_none_or(|ids| ids.sampled)
+    }
+}
+
+impl<S: Subscriber> Filter<S> for SampledOnlyFilter {
+    fn enabled(&self, _meta: &Metadata<'_>, _cx: &Context<'_, S>) -> bool {
+        true
+    }
+
+    fn callsite_enabled(&self, _meta: &'static Metadata<'static>) -> Interest {
+        // Whether a trace is sampled is only known per event
+        Interest::sometimes()
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, _cx: &Context<'_, S>) -> bool {
+        self.admits(event.metadata().level())
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::TRACE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::format::test_support::TestWriter;
+    use super::*;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+    use tracing_subscriber::{Layer as _, filter::FilterExt as _, layer::SubscriberExt as _};
+
+    fn capture(sampler: Sampler, f: impl FnOnce()) -> Vec<String> {
+        let writer = TestWriter::default();
+        let provider = SdkTracerProvider::builder().with_sampler(sampler).build();
+        let sampled = SampledOnlyFilter::new(&LoggerSampledOnly::enabled());
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")))
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(writer.clone())
+                    .with_ansi(false)
+                    .without_time()
+                    .with_filter(LevelFilter::INFO.and(sampled)),
+            );
+        tracing::subscriber::with_default(subscriber, f);
+        writer.output().lines().map(str::to_string).collect()
+    }
+
+    fn emit() {
+        tracing::info!("outside");
+        tracing::info_span!("request").in_scope(|| {
+            tracing::info!("inside");
+            tracing::warn!("slow");
+            tracing::debug!("verbose");
+        });
+    }
+
+    #[test]
+    fn test_drops_unsampled_below_always_level() {
+        let lines = capture(Sampler::AlwaysOff, emit);
+        assert!(
+            lines.iter().any(|line| line.contains("outside")),
+            "{lines:?}"
+        );
+        assert!(
+            !lines.iter().any(|line| line.contains("inside")),
+            "{lines:?}"
+        );
+        assert!(lines.iter().any(|line| line.contains("slow")), "{lines:?}");
+        assert!(
+            !lines.iter().any(|line| line.contains("verbose")),
+            "{lines:?}"
+        );
+    }
+
+    #[test]
+    fn test_keeps_sampled() {
+        let lines = capture(Sampler::AlwaysOn, emit);
+        assert!(
+            lines.iter().any(|line| line.contains("inside")),
+            "{lines:?}"
+        );
+        assert!(lines.iter().any(|line| line.contains("slow")), "{lines:?}");
+    }
+}
@@ -1,29 +1,39 @@
 //! Tracing subscriber setup and output layer management.
 
 use crate::{
-    logger::{LogFormat, Logger},
+    logger::{
+        FilterPrecedence, LogFormat, Logger, LoggerFileAppender, LoggerJournald, LoggerSampledOnly,
+        LoggerSyslog, RateLimitLayer, Redactor, RollingFileWriter, SampledOnlyFilter, SyslogLayer,
+        format::{
+            Ecs, FormatOptions, Gelf, JsonLayout, Logfmt, LogfmtFields, RedactFields, Timestamp,
+            TraceIdsStyle, WithTraceIds,
+        },
+    },
     otel::{
-        OtelGuard, get_resource, init_logger_provider, init_meter_provider, init_tracer_provider,
-        init_tracing_subscriber, opentelemetry::KeyValue,
+        OtelGuard, ProviderOptions, Redact, SubscriberOptions, get_resource, init_providers,
+        init_tracing_subscriber_with_options, opentelemetry::KeyValue,
     },
 };
-use anyhow::{Context, Result, anyhow};
-use std::sync::OnceLock;
+use anyhow::{Context, Result, anyhow, ensure};
+use std::sync::{Arc, Mutex};
 use tracing::Level;
 use tracing_appender::non_blocking::WorkerGuard;
-use tracing_opentelemetry_extra::BoxLayer;
+use tracing_opentelemetry_extra::{BoxFilter, BoxLayer};
 use tracing_subscriber::{
     EnvFilter, Layer, Registry,
+    filter::FilterExt,
     fmt::{self, MakeWriter, format::FmtSpan},
 };
 
-// Keep non-blocking appender worker guard to prevent log loss
-static NONBLOCKING_APPENDER_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+// Keep non-blocking appender worker guards to prevent log loss
+static NONBLOCKING_APPENDER_GUARDS: Mutex<Vec<WorkerGuard>> = Mutex::new(Vec::new());
 
 pub fn set_nonblocking_appender_guard(guard: WorkerGuard) -> Result<()> {
-    NONBLOCKING_APPENDER_GUARD
-        .set(guard)
-        .map_err(|_| anyhow!("cannot lock for appender"))
+    NONBLOCKING_APPENDER_GUARDS
+        .lock()
+        .map_err(|_| anyhow!("cannot lock for appender"))?
+        .push(guard);
+    Ok(())
 }
 
 /// Creates an environment filter for tracing based on the given level.
@@ -31,20 +41,157 @@ pub fn init_env_filter(level: &Level) -> EnvFilter {
     EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level.to_string()))
 }
 
+/// Creates an environment filter from the directives in `env_var` and the configured `directives`.
+///
+/// With [`FilterPrecedence::Env`] a non-empty, valid `env_var` wins; with
+/// [`FilterPrecedence::Config`] the configured directives win and `env_var` is ignored.
+pub fn build_env_filter(
+    env_var: &str,
+    directives: &str,
+    precedence: &FilterPrecedence,
+) -> Result<EnvFilter> {
+    if *precedence == FilterPrecedence::Env
+        && let Some(env_directives) = std::env::var(env_var)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+        && let Ok(filter) = EnvFilter::builder().parse(&env_directives)
+    {
+        return Ok(filter);
+    }
+
+    EnvFilter::builder()
+        .parse(directives)
+        .with_context(|| format!("Invalid filter directives: '{directives}'"))
+}
+
+/// Creates an environment filter from the filter settings of a [`Logger`].
+pub fn init_env_filter_from_logger(logger: &Logger) -> Result<EnvFilter> {
+    build_env_filter(
+        &logger.filter_env_var,
+        &logger.filter_or_level(),
+        &logger.filter_precedence,
+    )
+}
+
+/// Creates the filter of a single output sink.
+///
+/// Directives set for the sink itself are used as written; without them the
+/// sink shares the [`Logger`]'s base filter, which honours the filter
+/// environment variable and precedence.
+pub fn init_sink_filter(logger: &Logger, directives: Option<&str>) -> Result<EnvFilter> {
+    match directives {
+        Some(directives) => EnvFilter::builder()
+            .parse(directives)
+            .with_context(|| format!("Invalid filter directives: '{directives}'")),
+        None => init_env_filter_from_logger(logger),
+    }
+}
+
+/// Combines a sink filter with the dropping of events of unsampled traces, if enabled.
+fn with_sampled_only(filter: EnvFilter, sampled_only: Option<SampledOnlyFilter>) -> BoxFilter {
+    match sampled_only {
+        Some(sampled_only) => FilterExt::boxed(filter.and(sampled_only)),
+        None => FilterExt::boxed(filter),
+    }
+}
+
+/// Creates the filter of an output sink from its directives and sampled-only settings.
+fn sink_filter(
+    logger: &Logger,
+    directives: Option<String>,
+    sampled_only: &LoggerSampledOnly,
+) -> Result<BoxFilter> {
+    Ok(with_sampled_only(
+        init_sink_filter(logger, directives.as_deref())?,
+        SampledOnlyFilter::from_config(sampled_only),
+    ))
+}
+
 /// Apply the specified format to a tracing layer
 fn apply_layer_format<N, W>(
     layer: fmt::Layer<Registry, N, fmt::format::Format, W>,
     format: &LogFormat,
+    ansi: bool,
+    options: &FormatOptions,
 ) -> Box<dyn Layer<Registry> + Sync + Send>
 where
     N: for<'writer> fmt::format::FormatFields<'writer> + Sync + Send + 'static,
     W: for<'writer> MakeWriter<'writer> + Sync + Send + 'static,
 {
+    let trace_ids = options.trace_ids.clone();
+    let timer = options.timestamp;
+    let redactor = options.redactor.clone();
     match format {
-        LogFormat::Compact => layer.compact().boxed(),
-        LogFormat::Pretty => layer.pretty().boxed(),
+        LogFormat::Compact if timer.is_disabled() => layer
+            .event_format(WithTraceIds::new(
+                fmt::format().compact().with_ansi(ansi).without_time(),
+                TraceIdsStyle::KeyValue,
+                trace_ids,
+            ))
+            .fmt_fields(RedactFields::new(
+                fmt::format::DefaultFields::new(),
+                redactor,
+            ))
+            .boxed(),
+        LogFormat::Compact => layer
+            .event_format(WithTraceIds::new(
+                fmt::format().compact().with_ansi(ansi).with_timer(timer),
+                TraceIdsStyle::KeyValue,
+                trace_ids,
+            ))
+            .fmt_fields(RedactFields::new(
+                fmt::format::DefaultFields::new(),
+                redactor,
+            ))
+            .boxed(),
+        LogFormat::Pretty if timer.is_disabled() => layer
+            .event_format(WithTraceIds::new(
+                fmt::format().pretty().with_ansi(ansi).without_time(),
+                TraceIdsStyle::KeyValue,
+                trace_ids,
+            ))
+            .fmt_fields(RedactFields::new(
+                fmt::format::PrettyFields::new(),
+                redactor,
+            ))
+            .boxed(),
+        LogFormat::Pretty => layer
+            .event_format(WithTraceIds::new(
+                fmt::format().pretty().with_ansi(ansi).with_timer(timer),
+                TraceIdsStyle::KeyValue,
+                trace_ids,
+            ))
+            .fmt_fields(RedactFields::new(
+                fmt::format::PrettyFields::new(),
+                redactor,
+            ))
+            .boxed(),
         LogFormat::Json => layer
-            .event_format(fmt::format().json().flatten_event(true))
+            .event_format(WithTraceIds::new(
+                JsonLayout::new(options.json.clone(), options.service_name.clone())
+                    .with_redactor(redactor)
+                    .with_timer(timer),
+                TraceIdsStyle::Json,
+                trace_ids,
+            ))
+            .fmt_fields(fmt::format::JsonFields::new())
+            .boxed(),
+        LogFormat::Logfmt => layer
+            .event_format(WithTraceIds::new(
+                Logfmt::new()
+                    .with_redactor(redactor.clone())
+                    .with_timer(timer),
+                TraceIdsStyle::KeyValue,
+                trace_ids,
+            ))
+            .fmt_fields(RedactFields::new(LogfmtFields, redactor))
+            .boxed(),
+        LogFormat::Ecs => layer
+            .event_format(Ecs::new(options))
+            .fmt_fields(fmt::format::JsonFields::new())
+            .boxed(),
+        LogFormat::Gelf => layer
+            .event_format(Gelf::new(options))
             .fmt_fields(fmt::format::JsonFields::new())
             .boxed(),
     }
@@ -57,6 +204,20 @@ pub fn init_layer<W2>(
     span_events: FmtSpan,
     ansi: bool,
 ) -> Box<dyn Layer<Registry> + Sync + Send>
+where
+    W2: for<'writer> MakeWriter<'writer> + Sync + Send + 'static,
+{
+    init_layer_with_options(writer, format, span_events, ansi, &FormatOptions::default())
+}
+
+/// Initialize a format layer with the given writer, format and formatter settings
+pub fn init_layer_with_options<W2>(
+    writer: W2,
+    format: &LogFormat,
+    span_events: FmtSpan,
+    ansi: bool,
+    options: &FormatOptions,
+) -> Box<dyn Layer<Registry> + Sync + Send>
 where
     W2: for<'writer> MakeWriter<'writer> + Sync + Send + 'static,
 {
@@ -64,7 +225,20 @@ where
         .with_writer(writer)
         .with_ansi(ansi)
         .with_span_events(span_events);
-    apply_layer_format(layer, format)
+    apply_layer_format(layer, format, ansi, options)
+}
+
+/// Formatter settings taken from the logger configuration
+fn format_options(logger: &Logger) -> Result<FormatOptions> {
+    let timestamp =
+        Timestamp::from_config(&logger.timestamp).context("Invalid log timestamp settings")?;
+    Ok(
+        FormatOptions::new(logger.service_name.clone(), logger.attributes.clone())
+            .with_trace_ids(logger.trace_ids.clone())
+            .with_json(logger.json.clone())
+            .with_timestamp(timestamp)
+            .with_redactor(Redactor::from_config(&logger.redaction)?),
+    )
 }
 
 /// Create output layers based on configuration.
@@ -73,52 +247,179 @@ pub fn create_output_layers(logger: &Logger) -> Result<Vec<BoxLayer>> {
 
     // Add console layer if enabled
     if logger.console_enabled {
-        let stdout_layer = init_layer(
+        let stdout_layer = init_layer_with_options(
             std::io::stdout,
             &logger.format,
             logger.span_events.clone(),
             logger.ansi,
+            &format_options(logger)?,
         );
-        layers.push(stdout_layer);
+        let filter = sink_filter(
+            logger,
+            logger.console_filter_directives(),
+            &logger.console_sampled_only_or_default(),
+        )?;
+        layers.push(stdout_layer.with_filter(filter).boxed());
     }
     // Add file layer if configured and enabled
     if let Some(config) = &logger.file_appender
         && config.enable
     {
-        let rolling_builder = tracing_appender::rolling::Builder::new()
-            .max_log_files(config.max_log_files)
-            .rotation(config.get_rolling_rotation());
+        let layer = create_file_layer(logger, config, config.filename_prefix_or_default())?;
+        layers.push(layer);
+    }
+    // Add named file sinks, using the sink name as the default filename prefix
+    for (name, config) in logger.sinks.iter().filter(|(_, config)| config.enable) {
+        let filename_prefix = config
+            .filename_prefix
+            .clone()
+            .unwrap_or_else(|| name.clone());
+        let layer = create_file_layer(logger, config, filename_prefix)
+            .with_context(|| format!("Failed to build file sink '{name}'"))?;
+        layers.push(layer);
+    }
+    // Add syslog and journald layers if configured and enabled
+    if let Some(config) = &logger.syslog
+        && config.enable
+    {
+        layers.push(create_syslog_layer(logger, config)?);
+    }
+    if let Some(config) = &logger.journald
+        && config.enable
+    {
+        // tracing-journald formats fields itself, so they cannot be redacted
+        ensure!(
+            !logger.redaction.enable,
+            "journald output cannot be redacted; disable journald or redaction"
+        );
+        layers.push(create_journald_layer(logger, config)?);
+    }
+    Ok(layers)
+}
+
+/// Create a filtered syslog output layer.
+fn create_syslog_layer(logger: &Logger, config: &LoggerSyslog) -> Result<BoxLayer> {
+    let app_name = config.app_name.as_deref().unwrap_or(&logger.service_name);
+    let layer = SyslogLayer::new(
+        &config.transport,
+        &config.address_or_default(),
+        config.facility,
+        app_name,
+        config.hostname.as_deref(),
+    )
+    .context("Failed to build syslog layer")?
+    .with_redactor(Redactor::from_config(&logger.redaction)?);
+    let filter = sink_filter(
+        logger,
+        config.filter_directives(),
+        &config.sampled_only_or_default(logger),
+    )?;
+    Ok(layer.with_filter(filter).boxed())
+}
+
+/// Create a filtered journald output layer.
+#[cfg(feature = "journald")]
+fn create_journald_layer(logger: &Logger, config: &LoggerJournald) -> Result<BoxLayer> {
+    let identifier = config
+        .syslog_identifier
+        .clone()
+        .unwrap_or_else(|| logger.service_name.clone());
+    let mut layer = tracing_journald::layer()
+        .context("Failed to connect to journald")?
+        .with_syslog_identifier(identifier);
+    if let Some(prefix) = &config.field_prefix {
+        layer = layer.with_field_prefix(Some(prefix.clone()));
+    }
+    let filter = sink_filter(
+        logger,
+        config.filter_directives(),
+        &config.sampled_only_or_default(logger),
+    )?;
+    Ok(layer.with_filter(filter).boxed())
+}
+
+/// Journald output needs the `journald` feature.
+#[cfg(not(feature = "journald"))]
+fn create_journald_layer(_logger: &Logger, _config: &LoggerJournald) -> Result<BoxLayer> {
+    Err(anyhow!("journald output requires the `journald` feature"))
+}
 
-        let file_appender = rolling_builder
-            .filename_prefix(config.filename_prefix_or_default())
+/// Create a filtered file output layer from a file appender configuration.
+fn create_file_layer(
+    logger: &Logger,
+    config: &LoggerFileAppender,
+    filename_prefix: String,
+) -> Result<BoxLayer> {
+    let file_appender_layer = if config.uses_rolling_policy() {
+        let writer = RollingFileWriter::new(
+            config.dir_or_default(),
+            filename_prefix,
+            config.filename_suffix_or_default(),
+            config.rolling_policy(),
+        )
+        .context("Failed to build rolling file writer")?;
+        init_file_writer_layer(logger, config, writer)?
+    } else {
+        let file_appender = tracing_appender::rolling::Builder::new()
+            .max_log_files(config.max_log_files)
+            .rotation(config.get_rolling_rotation())
+            .filename_prefix(filename_prefix)
             .filename_suffix(config.filename_suffix_or_default())
             .build(config.dir_or_default())
             .context("Failed to build file appender")?;
+        init_file_writer_layer(logger, config, file_appender)?
+    };
+    let filter = sink_filter(
+        logger,
+        config.filter_directives(),
+        &config.sampled_only_or_default(logger),
+    )?;
+    Ok(file_appender_layer.with_filter(filter).boxed())
+}
 
-        let file_appender_layer = if config.non_blocking {
-            let (non_blocking_file_appender, work_guard) =
-                tracing_appender::non_blocking(file_appender);
-            set_nonblocking_appender_guard(work_guard)?;
-            init_layer(
-                non_blocking_file_appender,
-                &config.format_or_default(),
-                logger.span_events.clone(),
-                config.ansi,
-            )
-        } else {
-            init_layer(
-                file_appender,
-                &config.format_or_default(),
-                logger.span_events.clone(),
-                config.ansi,
-            )
-        };
-        layers.push(file_appender_layer);
+/// Initialize a format layer for a file writer, optionally making it non-blocking.
+fn init_file_writer_layer<W>(
+    logger: &Logger,
+    config: &LoggerFileAppender,
+    writer: W,
+) -> Result<BoxLayer>
+where
+    W: std::io::Write + for<'writer> MakeWriter<'writer> + Sync + Send + 'static,
+{
+    let mut options = format_options(logger)?
+        .with_json(config.json.clone().unwrap_or_else(|| logger.json.clone()));
+    if let Some(timestamp) = &config.timestamp {
+        options = options.with_timestamp(
+            Timestamp::from_config(timestamp).context("Invalid file log timestamp settings")?,
+        );
     }
-    Ok(layers)
+    let layer = if config.non_blocking {
+        let (non_blocking_writer, work_guard) = tracing_appender::non_blocking(writer);
+        set_nonblocking_appender_guard(work_guard)?;
+        init_layer_with_options(
+            non_blocking_writer,
+            &config.format_or_default(),
+            logger.span_events.clone(),
+            config.ansi,
+            &options,
+        )
+    } else {
+        init_layer_with_options(
+            writer,
+            &config.format_or_default(),
+            logger.span_events.clone(),
+            config.ansi,
+            &options,
+        )
+    };
+    Ok(layer)
 }
 
 /// Initializes the complete tracing stack with OpenTelemetry integration.
+///
+/// `level` is the default of a global filter which `RUST_LOG` overrides, and
+/// OpenTelemetry logs are exported when `otel_logs_enabled` is set. This is
+/// [`setup_tracing_with_options`] with only these settings.
 pub fn setup_tracing(
     service_name: &str,
     attributes: &[KeyValue],
@@ -128,24 +429,205 @@ pub fn setup_tracing(
     layers: Vec<BoxLayer>,
     otel_logs_enabled: bool,
 ) -> Result<OtelGuard> {
-    let env_filter = init_env_filter(&level);
-    let resource = get_resource(service_name, attributes);
-    let tracer_provider = init_tracer_provider(&resource, sample_ratio)?;
-    let meter_provider = init_meter_provider(&resource, metrics_interval_secs)?;
-    let logger_provider = if otel_logs_enabled {
-        Some(init_logger_provider(&resource)?)
-    } else {
-        None
-    };
+    setup_tracing_with_options(
+        SetupOptions::new(service_name)
+            .with_attributes(attributes.to_vec())
+            .with_sample_ratio(sample_ratio)
+            .with_metrics_interval_secs(metrics_interval_secs)
+            .with_filter(init_env_filter(&level))
+            .with_layers(layers)
+            .with_otel_logs_enabled(otel_logs_enabled),
+    )
+}
+
+/// Settings of [`setup_tracing_with_options`].
+///
+/// `filter` applies to every layer. On top of it, entries in `layers` may carry
+/// their own filter, the traces and metrics layers are filtered by
+/// `env_filter`, and OpenTelemetry logs, when enabled, by `otel_logs_filter`.
+pub struct SetupOptions {
+    /// Name of the service, recorded in the resource.
+    pub service_name: String,
+    /// Additional resource attributes.
+    pub attributes: Vec<KeyValue>,
+    /// The ratio of traces to sample (0.0 to 1.0).
+    pub sample_ratio: f64,
+    /// The interval in seconds between metric collections.
+    pub metrics_interval_secs: u64,
+    /// Filter applied to every layer.
+    pub filter: Option<EnvFilter>,
+    /// Filter of the traces and metrics layers.
+    pub env_filter: Option<EnvFilter>,
+    /// Output layers.
+    pub layers: Vec<BoxLayer>,
+    /// Whether OpenTelemetry logs are exported.
+    pub otel_logs_enabled: bool,
+    /// Filter of OpenTelemetry logs.
+    pub otel_logs_filter: Option<EnvFilter>,
+    /// Redactor applied to span attributes and OpenTelemetry log records.
+    pub redactor: Option<Arc<Redactor>>,
+    /// Rate limit applied to events before they reach any output.
+    pub rate_limit: Option<RateLimitLayer>,
+    /// Dropping of events of unsampled traces from OpenTelemetry logs.
+    pub otel_logs_sampled_only: Option<SampledOnlyFilter>,
+}
+
+impl SetupOptions {
+    /// Create the settings of `service_name`, sampling every trace and
+    /// collecting metrics every 30 seconds.
+    pub fn new(service_name: impl Into<String>) -> Self {
+        Self {
+            service_name: service_name.into(),
+            attributes: Vec::new(),
+            sample_ratio: 1.0,
+            metrics_interval_secs: 30,
+            filter: None,
+            env_filter: None,
+            layers: Vec::new(),
+            otel_logs_enabled: false,
+            otel_logs_filter: None,
+            redactor: None,
+            rate_limit: None,
+            otel_logs_sampled_only: None,
+        }
+    }
+
+    /// Set the additional resource attributes.
+    pub fn with_attributes(mut self, attributes: Vec<KeyValue>) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Set the ratio of traces to sample.
+    pub fn with_sample_ratio(mut self, sample_ratio: f64) -> Self {
+        self.sample_ratio = sample_ratio;
+        self
+    }
 
-    let guard = init_tracing_subscriber(
+    /// Set the interval in seconds between metric collections.
+    pub fn with_metrics_interval_secs(mut self, metrics_interval_secs: u64) -> Self {
+        self.metrics_interval_secs = metrics_interval_secs;
+        self
+    }
+
+    /// Set the filter applied to every layer.
+    pub fn with_filter(mut self, filter: EnvFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Set the filter of the traces and metrics layers.
+    pub fn with_env_filter(mut self, env_filter: EnvFilter) -> Self {
+        self.env_filter = Some(env_filter);
+        self
+    }
+
+    /// Set the output layers.
+    pub fn with_layers(mut self, layers: Vec<BoxLayer>) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Set whether OpenTelemetry logs are exported.
+    pub fn with_otel_logs_enabled(mut self, otel_logs_enabled: bool) -> Self {
+        self.otel_logs_enabled = otel_logs_enabled;
+        self
+    }
+
+    /// Set the filter of OpenTelemetry logs.
+    pub fn with_otel_logs_filter(mut self, otel_logs_filter: EnvFilter) -> Self {
+        self.otel_logs_filter = Some(otel_logs_filter);
+        self
+    }
+
+    /// Set the redactor applied to span attributes and OpenTelemetry log records.
+    pub fn with_redactor(mut self, redactor: Option<Arc<Redactor>>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Set the rate limit applied to events before they reach any output.
+    pub fn with_rate_limit(mut self, rate_limit: Option<RateLimitLayer>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Set the dropping of events of unsampled traces from OpenTelemetry logs.
+    pub fn with_otel_logs_sampled_only(mut self, sampled_only: Option<SampledOnlyFilter>) -> Self {
+        self.otel_logs_sampled_only = sampled_only;
+        self
+    }
+}
+
+impl std::fmt::Debug for SetupOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SetupOptions")
+            .field("service_name", &self.service_name)
+            .field("attributes", &self.attributes)
+            .field("sample_ratio", &self.sample_ratio)
+            .field("metrics_interval_secs", &self.metrics_interval_secs)
+            .field("filter", &self.filter)
+            .field("env_filter", &self.env_filter)
+            .field("layers", &self.layers.len())
+            .field("otel_logs_enabled", &self.otel_logs_enabled)
+            .field("otel_logs_filter", &self.otel_logs_filter)
+            .field("redactor", &self.redactor)
+            .field("rate_limit", &self.rate_limit)
+            .field("otel_logs_sampled_only", &self.otel_logs_sampled_only)
+            .finish()
+    }
+}
+
+/// Initializes the tracing stack described by `options`, with per-sink
+/// filters, redaction, rate limiting and dropping of unsampled OpenTelemetry
+/// logs.
+pub fn setup_tracing_with_options(options: SetupOptions) -> Result<OtelGuard> {
+    let SetupOptions {
         service_name,
+        attributes,
+        sample_ratio,
+        metrics_interval_secs,
+        filter,
         env_filter,
         layers,
-        tracer_provider,
-        meter_provider,
-        logger_provider,
+        otel_logs_enabled,
+        otel_logs_filter,
+        redactor,
+        rate_limit,
+        otel_logs_sampled_only,
+    } = options;
+    let resource = get_resource(&service_name, &attributes);
+    let providers = init_providers(
+        &resource,
+        &ProviderOptions::default()
+            .with_sample_ratio(sample_ratio)
+            .with_metrics_interval_secs(metrics_interval_secs)
+            .with_logs_enabled(otel_logs_enabled)
+            .with_redactor(redactor.map(|redactor| redactor as Arc<dyn Redact>)),
     )?;
 
+    // The rate limit wraps every output so suppressed events reach none of them
+    let mut subscriber_options =
+        SubscriberOptions::default().with_wrapper(|layers| match rate_limit {
+            Some(rate_limit) => layers.and_then(rate_limit).boxed(),
+            None => layers.boxed(),
+        });
+    if let Some(filter) = filter {
+        subscriber_options = subscriber_options.with_filter(filter);
+    }
+    if let Some(env_filter) = env_filter {
+        subscriber_options = subscriber_options.with_traces_filter(env_filter);
+    }
+    let logs_filter = match (otel_logs_filter, otel_logs_sampled_only) {
+        (Some(filter), sampled_only) => Some(with_sampled_only(filter, sampled_only)),
+        (None, Some(sampled_only)) => Some(FilterExt::boxed(sampled_only)),
+        (None, None) => None,
+    };
+    if let Some(logs_filter) = logs_filter {
+        subscriber_options = subscriber_options.with_logs_filter(logs_filter);
+    }
+    let guard =
+        init_tracing_subscriber_with_options(&service_name, layers, providers, subscriber_options)?;
+
     Ok(guard)
 }
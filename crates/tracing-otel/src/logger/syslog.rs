@@ -0,0 +1,628 @@
+//! RFC 5424 syslog output layer.
+//!
+//! [`SyslogLayer`] formats each event as an RFC 5424 message and sends it over
+//! a unix datagram socket (e.g. `/dev/log`), UDP, or TCP with octet-counting
+//! framing (RFC 6587). Event fields are sent as the `fields@32473` structured
+//! data element and the fields of each enclosing span as `span@32473`.
+//!
+//! TCP connects and writes time out after a second. While the server is
+//! unreachable, events are dropped and reconnects are backed off
+//! exponentially up to a minute, so logging threads are not stalled.
+//!
+//! Send failures are printed to stderr once per outage: after the first
+//! failure, further ones are not reported until a message is sent again.
+
+use super::{Redactor, format::RedactVisitor};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Write as _},
+    io::{self, Write as _},
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tracing::{
+    Event, Level, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+};
+use tracing_subscriber::{Layer, layer::Context as LayerContext, registry::LookupSpan};
+
+/// Private enterprise number reserved for documentation (RFC 5612), used for SD-IDs.
+const ENTERPRISE_ID: &str = "32473";
+/// Default unix socket path of the local syslog daemon.
+const DEFAULT_UNIX_PATH: &str = "/dev/log";
+/// Default network address of the syslog daemon.
+const DEFAULT_NETWORK_ADDRESS: &str = "127.0.0.1:514";
+/// Timeout of TCP connection attempts and writes, so a stalled server cannot
+/// block logging threads for long.
+const TCP_TIMEOUT: Duration = Duration::from_secs(1);
+/// Initial and maximum wait before reconnecting to an unreachable TCP server.
+const TCP_BACKOFF: (Duration, Duration) = (Duration::from_secs(1), Duration::from_secs(60));
+
+/// Transport used to reach the syslog daemon
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq)]
+pub enum SyslogTransport {
+    /// Unix datagram socket (default, `/dev/log`)
+    #[serde(rename = "unix")]
+    #[default]
+    Unix,
+    /// UDP datagrams (default `127.0.0.1:514`)
+    #[serde(rename = "udp")]
+    Udp,
+    /// TCP stream with octet-counting framing (default `127.0.0.1:514`)
+    #[serde(rename = "tcp")]
+    Tcp,
+}
+
+impl SyslogTransport {
+    /// Default address for this transport.
+    pub fn default_address(&self) -> &'static str {
+        match self {
+            SyslogTransport::Unix => DEFAULT_UNIX_PATH,
+            SyslogTransport::Udp | SyslogTransport::Tcp => DEFAULT_NETWORK_ADDRESS,
+        }
+    }
+}
+
+/// Syslog facility
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogFacility {
+    Kern,
+    #[default]
+    User,
+    Mail,
+    Daemon,
+    Auth,
+    Syslog,
+    Lpr,
+    News,
+    Uucp,
+    Cron,
+    Authpriv,
+    Ftp,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
+impl SyslogFacility {
+    /// Numeric facility code as defined by RFC 5424.
+    pub fn code(self) -> u8 {
+        match self {
+            SyslogFacility::Kern => 0,
+            SyslogFacility::User => 1,
+            SyslogFacility::Mail => 2,
+            SyslogFacility::Daemon => 3,
+            SyslogFacility::Auth => 4,
+            SyslogFacility::Syslog => 5,
+            SyslogFacility::Lpr => 6,
+            SyslogFacility::News => 7,
+            SyslogFacility::Uucp => 8,
+            SyslogFacility::Cron => 9,
+            SyslogFacility::Authpriv => 10,
+            SyslogFacility::Ftp => 11,
+            SyslogFacility::Local0 => 16,
+            SyslogFacility::Local1 => 17,
+            SyslogFacility::Local2 => 18,
+            SyslogFacility::Local3 => 19,
+            SyslogFacility::Local4 => 20,
+            SyslogFacility::Local5 => 21,
+            SyslogFacility::Local6 => 22,
+            SyslogFacility::Local7 => 23,
+        }
+    }
+}
+
+/// Map a tracing level to a syslog severity.
+pub fn level_to_severity(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        Level::DEBUG | Level::TRACE => 7,
+    }
+}
+
+/// Connected socket to the syslog daemon.
+#[derive(Debug)]
+enum Sender {
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixDatagram),
+    Udp(UdpSocket),
+    Tcp {
+        address: String,
+        connection: Mutex<TcpConnection>,
+    },
+}
+
+/// TCP connection state; events are dropped without blocking while
+/// reconnecting is backed off.
+#[derive(Debug)]
+struct TcpConnection {
+    stream: Option<TcpStream>,
+    retry_at: Option<Instant>,
+    backoff: Duration,
+}
+
+impl TcpConnection {
+    fn connected(stream: TcpStream) -> Self {
+        Self {
+            stream: Some(stream),
+            retry_at: None,
+            backoff: TCP_BACKOFF.0,
+        }
+    }
+
+    /// Write `frame`, reconnecting once unless a reconnect is backed off.
+    fn send(&mut self, address: &str, frame: &[u8]) -> io::Result<()> {
+        if let Some(stream) = self.stream.as_mut() {
+            if stream.write_all(frame).is_ok() {
+                return Ok(());
+            }
+            self.stream = None;
+        }
+        if self
+            .retry_at
+            .is_some_and(|retry_at| Instant::now() < retry_at)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "syslog server unreachable, waiting to reconnect",
+            ));
+        }
+        let result = connect_tcp(address).and_then(|mut stream| {
+            stream.write_all(frame)?;
+            Ok(stream)
+        });
+        match result {
+            Ok(stream) => {
+                *self = Self::connected(stream);
+                Ok(())
+            }
+            Err(e) => {
+                self.retry_at = Some(Instant::now() + self.backoff);
+                self.backoff = (self.backoff * 2).min(TCP_BACKOFF.1);
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Connect to `address` with [`TCP_TIMEOUT`] on the connection and its writes.
+fn connect_tcp(address: &str) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    for addr in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, TCP_TIMEOUT) {
+            Ok(stream) => {
+                stream.set_write_timeout(Some(TCP_TIMEOUT))?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "address resolved to nothing")
+    }))
+}
+
+impl Sender {
+    fn connect(transport: &SyslogTransport, address: &str) -> Result<Self> {
+        match transport {
+            #[cfg(unix)]
+            SyslogTransport::Unix => {
+                let socket = std::os::unix::net::UnixDatagram::unbound()
+                    .context("Failed to create syslog unix socket")?;
+                socket
+                    .connect(address)
+                    .with_context(|| format!("Failed to connect to syslog socket {address}"))?;
+                Ok(Sender::Unix(socket))
+            }
+            #[cfg(not(unix))]
+            SyslogTransport::Unix => {
+                anyhow::bail!("Unix syslog transport requires a unix platform")
+            }
+            SyslogTransport::Udp => {
+                let target = address
+                    .to_socket_addrs()
+                    .ok()
+                    .and_then(|mut addrs| addrs.next())
+                    .with_context(|| format!("Failed to resolve syslog address {address}"))?;
+                // Bind to the unspecified address of the target's family
+                let local = match target {
+                    SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+                    SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+                };
+                let socket = UdpSocket::bind(local).context("Failed to bind syslog UDP socket")?;
+                socket
+                    .connect(target)
+                    .with_context(|| format!("Failed to connect to syslog address {address}"))?;
+                Ok(Sender::Udp(socket))
+            }
+            SyslogTransport::Tcp => {
+                let stream = connect_tcp(address)
+                    .with_context(|| format!("Failed to connect to syslog server {address}"))?;
+                Ok(Sender::Tcp {
+                    address: address.to_string(),
+                    connection: Mutex::new(TcpConnection::connected(stream)),
+                })
+            }
+        }
+    }
+
+    fn send(&self, message: &str) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Sender::Unix(socket) => socket.send(message.as_bytes()).map(|_| ()),
+            Sender::Udp(socket) => socket.send(message.as_bytes()).map(|_| ()),
+            Sender::Tcp {
+                address,
+                connection,
+            } => {
+                let frame = format!("{} {message}", message.len());
+                connection
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .send(address, frame.as_bytes())
+            }
+        }
+    }
+}
+
+/// Tracks whether sending is failing, so each outage is reported once.
+#[derive(Debug, Default)]
+struct SendErrors {
+    failing: AtomicBool,
+}
+
+impl SendErrors {
+    /// The error to report for `result`, if it is the first failure of an outage.
+    fn report(&self, result: io::Result<()>) -> Option<io::Error> {
+        match result {
+            Ok(()) => {
+                self.failing.store(false, Ordering::Relaxed);
+                None
+            }
+            // Events are dropped silently while a TCP reconnect is backed off
+            Err(e) if e.kind() == io::ErrorKind::NotConnected => None,
+            Err(e) => (!self.failing.swap(true, Ordering::Relaxed)).then_some(e),
+        }
+    }
+}
+
+/// A [`Layer`] that sends events to syslog as RFC 5424 messages.
+#[derive(Debug)]
+pub struct SyslogLayer {
+    sender: Sender,
+    send_errors: SendErrors,
+    facility: SyslogFacility,
+    hostname: String,
+    app_name: String,
+    procid: String,
+    redactor: Option<Arc<Redactor>>,
+}
+
+impl SyslogLayer {
+    /// Connect to the syslog daemon at `address` over `transport`.
+    pub fn new(
+        transport: &SyslogTransport,
+        address: &str,
+        facility: SyslogFacility,
+        app_name: &str,
+        hostname: Option<&str>,
+    ) -> Result<Self> {
+        let hostname = hostname
+            .map(str::to_string)
+            .or_else(|| std::env::var("HOSTNAME").ok())
+            .filter(|host| !host.trim().is_empty())
+            .unwrap_or_else(|| "-".to_string());
+        Ok(Self {
+            sender: Sender::connect(transport, address)?,
+            send_errors: SendErrors::default(),
+            facility,
+            hostname: header_field(&hostname, 255),
+            app_name: header_field(app_name, 48),
+            procid: std::process::id().to_string(),
+            redactor: None,
+        })
+    }
+
+    /// Redact event and span fields with `redactor` when it is set.
+    pub fn with_redactor(mut self, redactor: Option<Arc<Redactor>>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    fn field_visitor(&self) -> RedactVisitor<FieldVisitor> {
+        RedactVisitor::new(FieldVisitor::default(), self.redactor.clone())
+    }
+
+    /// Format an RFC 5424 message.
+    fn format(
+        &self,
+        level: &Level,
+        event_fields: &[(String, String)],
+        span_fields: &[(String, String)],
+        message: &str,
+    ) -> String {
+        let priority = self.facility.code() * 8 + level_to_severity(level);
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_else(|_| "-".to_string());
+
+        let mut out = format!(
+            "<{priority}>1 {timestamp} {} {} {} - ",
+            self.hostname, self.app_name, self.procid
+        );
+        if event_fields.is_empty() && span_fields.is_empty() {
+            out.push('-');
+        } else {
+            write_sd_element(&mut out, "fields", event_fields);
+            write_sd_element(&mut out, "span", span_fields);
+        }
+        if !message.is_empty() {
+            out.push(' ');
+            out.push_str(message);
+        }
+        out
+    }
+}
+
+/// Formatted fields of a span, stored in the span's extensions.
+#[derive(Debug, Default)]
+struct SyslogSpanFields(Vec<(String, String)>);
+
+impl<S> Layer<S> for SyslogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut visitor = self.field_visitor();
+        attrs.record(&mut visitor);
+        span.extensions_mut()
+            .insert(SyslogSpanFields(visitor.into_inner().fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut visitor = self.field_visitor();
+        values.record(&mut visitor);
+        let mut extensions = span.extensions_mut();
+        if let Some(fields) = extensions.get_mut::<SyslogSpanFields>() {
+            fields.0.extend(visitor.into_inner().fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let mut visitor = self.field_visitor();
+        event.record(&mut visitor);
+        let visitor = visitor.into_inner();
+
+        let mut span_fields = Vec::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(fields) = span.extensions().get::<SyslogSpanFields>() {
+                    span_fields.extend(fields.0.iter().cloned());
+                }
+            }
+        }
+
+        let mut event_fields = visitor.fields;
+        event_fields.push(("target".to_string(), event.metadata().target().to_string()));
+        let message = self.format(
+            event.metadata().level(),
+            &event_fields,
+            &span_fields,
+            &visitor.message,
+        );
+        if let Some(e) = self.send_errors.report(self.sender.send(&message)) {
+            eprintln!(
+                "Failed to send syslog message, dropping messages until sending succeeds: {e}"
+            );
+        }
+    }
+}
+
+/// Collects the message and formatted fields of an event or span.
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, format!("{value:?}"));
+    }
+}
+
+impl FieldVisitor {
+    fn record(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = value;
+        } else {
+            self.fields.push((field.name().to_string(), value));
+        }
+    }
+}
+
+/// Append an SD-ELEMENT like `[fields@32473 key="value"]`; nothing if `params` is empty.
+fn write_sd_element(out: &mut String, name: &str, params: &[(String, String)]) {
+    if params.is_empty() {
+        return;
+    }
+    let _ = write!(out, "[{name}@{ENTERPRISE_ID}");
+    for (key, value) in params {
+        let _ = write!(out, " {}=\"{}\"", sd_name(key), escape_sd_value(value));
+    }
+    out.push(']');
+}
+
+/// Sanitize an SD-NAME: printable ASCII without `=`, space, `]` or `"`, at most 32 chars.
+fn sd_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '=' | ' ' | ']' | '"' => '_',
+            c if c.is_ascii_graphic() => c,
+            _ => '_',
+        })
+        .take(32)
+        .collect()
+}
+
+/// Escape `"`, `\` and `]` in a PARAM-VALUE.
+fn escape_sd_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Sanitize a header field to printable ASCII, using `-` when empty.
+fn header_field(value: &str, max_len: usize) -> String {
+    let value: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+    if value.is_empty() {
+        "-".to_string()
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(socket: &UdpSocket) -> SyslogLayer {
+        let address = socket.local_addr().unwrap().to_string();
+        SyslogLayer::new(
+            &SyslogTransport::Udp,
+            &address,
+            SyslogFacility::Local0,
+            "my app",
+            Some("host-1"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_format_rfc5424_message() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let layer = layer(&socket);
+        let message = layer.format(
+            &Level::WARN,
+            &[("user id".to_string(), "a\"b]".to_string())],
+            &[("request_id".to_string(), "42".to_string())],
+            "hello",
+        );
+
+        // local0 (16) * 8 + warning (4)
+        assert!(message.starts_with("<132>1 "));
+        assert!(message.contains(" host-1 myapp "));
+        assert!(
+            message
+                .ends_with(r#"[fields@32473 user_id="a\"b\]"][span@32473 request_id="42"] hello"#)
+        );
+    }
+
+    #[test]
+    fn test_send_errors_reported_once_per_outage() {
+        let errors = SendErrors::default();
+        let refused = || Err(io::Error::from(io::ErrorKind::ConnectionRefused));
+
+        assert!(errors.report(refused()).is_some());
+        assert!(errors.report(refused()).is_none());
+        assert!(
+            errors
+                .report(Err(io::Error::from(io::ErrorKind::NotConnected)))
+                .is_none()
+        );
+        assert!(errors.report(Ok(())).is_none());
+        assert!(errors.report(refused()).is_some());
+    }
+
+    #[test]
+    fn test_format_without_structured_data() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let message = layer(&socket).format(&Level::ERROR, &[], &[], "boom");
+        assert!(message.starts_with("<131>1 "));
+        assert!(message.ends_with(" - - boom"));
+    }
+
+    #[test]
+    fn test_udp_binds_to_the_target_address_family() {
+        // Skip where IPv6 is unavailable
+        let Ok(socket) = UdpSocket::bind("[::1]:0") else {
+            return;
+        };
+        layer(&socket).sender.send("hello").unwrap();
+        let mut buf = [0u8; 16];
+        let len = socket.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"hello");
+    }
+
+    #[test]
+    fn test_tcp_reconnect_is_backed_off() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        let mut connection = TcpConnection {
+            stream: None,
+            retry_at: None,
+            backoff: TCP_BACKOFF.0,
+        };
+        let refused = connection.send(&address, b"1 a").unwrap_err();
+        assert_ne!(refused.kind(), io::ErrorKind::NotConnected);
+        assert_eq!(connection.backoff, TCP_BACKOFF.0 * 2);
+
+        // Until the backoff elapses, sends fail without connecting
+        let skipped = connection.send(&address, b"1 a").unwrap_err();
+        assert_eq!(skipped.kind(), io::ErrorKind::NotConnected);
+        assert_eq!(connection.backoff, TCP_BACKOFF.0 * 2);
+    }
+
+    #[test]
+    fn test_sends_events_over_udp() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let subscriber = tracing_subscriber::registry().with(layer(&socket));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", request_id = "abc");
+            let _entered = span.enter();
+            tracing::error!(code = 7, "failed");
+        });
+
+        let mut buf = [0u8; 1024];
+        let len = socket.recv(&mut buf).unwrap();
+        let message = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(message.starts_with("<131>1 "));
+        assert!(message.contains(r#"code="7""#));
+        assert!(message.contains(r#"[span@32473 request_id="abc"]"#));
+        assert!(message.ends_with(" failed"));
+    }
+}
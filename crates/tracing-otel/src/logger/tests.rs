@@ -4,7 +4,9 @@ use super::*;
 use opentelemetry::KeyValue;
 use serial_test::serial;
 use tracing::Level;
+use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
 
 use super::deserialize::deserialize_span_events;
 
@@ -14,6 +16,29 @@ struct TestFmtSpan {
     span_events: FmtSpan,
 }
 
+/// Emits events at several levels for `my_app` and `hyper` through the output layers of `logger`.
+fn emit_through_output_layers(logger: &Logger) {
+    let layers = create_output_layers(logger).unwrap();
+    let subscriber = tracing_subscriber::registry().with(layers);
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::debug!(target: "my_app", "my_app debug");
+        tracing::info!(target: "my_app", "my_app info");
+        tracing::error!(target: "my_app", "my_app error");
+        tracing::debug!(target: "hyper", "hyper debug");
+        tracing::warn!(target: "hyper", "hyper warn");
+    });
+}
+
+/// Reads everything written to the files in `dir` whose name starts with `prefix`.
+fn read_sink_output(dir: &std::path::Path, prefix: &str) -> String {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(prefix))
+        .map(|entry| std::fs::read_to_string(entry.path()).unwrap())
+        .collect()
+}
+
 #[test]
 #[serial]
 fn test_logger_builder() {
@@ -63,6 +88,7 @@ fn test_logger_with_file_appender() {
         enable: true,
         non_blocking: false,
         level: Some(Level::INFO),
+        filter: None,
         ansi: false,
         format: Some(LogFormat::Json),
         rotation: LogRollingRotation::Daily,
@@ -70,6 +96,7 @@ fn test_logger_with_file_appender() {
         filename_prefix: Some("test".to_string()),
         filename_suffix: Some("log".to_string()),
         max_log_files: 10,
+        ..Default::default()
     };
 
     let logger = Logger::new("test-service").with_file_appender(Some(file_appender));
@@ -109,3 +136,632 @@ fn test_logger_console_control() {
     let logger = Logger::new("test-service").with_console_enabled(false);
     assert!(!logger.console_enabled);
 }
+
+#[test]
+#[serial]
+fn test_logger_filter_directives() {
+    let logger = Logger::new("test-service").with_level(Level::DEBUG);
+    assert_eq!(logger.filter_or_level(), "DEBUG");
+
+    let logger = logger.with_filter("hyper=warn,my_app=debug");
+    assert_eq!(logger.filter_or_level(), "hyper=warn,my_app=debug");
+    assert!(init_env_filter_from_logger(&logger).is_ok());
+
+    let logger = logger.with_filter("my_app=[");
+    assert!(init_env_filter_from_logger(&logger).is_err());
+}
+
+#[test]
+#[serial]
+fn test_filter_precedence() {
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("TEST_LOG_FILTER", "hyper=trace");
+    }
+
+    let logger = Logger::new("test-service")
+        .with_filter("hyper=warn")
+        .with_filter_env_var("TEST_LOG_FILTER");
+    let filter = init_env_filter_from_logger(&logger).unwrap();
+    assert_eq!(filter.to_string(), "hyper=trace");
+
+    let logger = logger.with_filter_precedence(FilterPrecedence::Config);
+    let filter = init_env_filter_from_logger(&logger).unwrap();
+    assert_eq!(filter.to_string(), "hyper=warn");
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("TEST_LOG_FILTER");
+    }
+}
+
+#[test]
+#[serial]
+fn test_sink_filters_ignore_rust_log() {
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("RUST_LOG", "trace");
+    }
+
+    let logger = Logger::new("test-service")
+        .with_level(Level::INFO)
+        .with_console_filter("my_app=debug");
+    let sink = LoggerFileAppender {
+        level: Some(Level::ERROR),
+        ..Default::default()
+    };
+    assert_eq!(
+        init_env_filter_from_logger(&logger).unwrap().to_string(),
+        "trace"
+    );
+    let console = init_sink_filter(&logger, logger.console_filter_directives().as_deref());
+    assert_eq!(console.unwrap().to_string(), "my_app=debug");
+    let file = init_sink_filter(&logger, sink.filter_directives().as_deref());
+    assert_eq!(file.unwrap().to_string(), "error");
+    let otel_logs = init_sink_filter(&logger, logger.otel_logs_filter_directives().as_deref());
+    assert_eq!(otel_logs.unwrap().to_string(), "trace");
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("RUST_LOG");
+    }
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "env")]
+fn test_env_filter_parsing() {
+    use super::env::init_logger_from_env;
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("LOG_FILTER", "hyper=warn,my_app=debug");
+        std::env::set_var("LOG_FILTER_ENV_VAR", "APP_LOG");
+        std::env::set_var("LOG_FILTER_PRECEDENCE", "config");
+    }
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert_eq!(logger.filter.as_deref(), Some("hyper=warn,my_app=debug"));
+    assert_eq!(logger.filter_env_var, "APP_LOG");
+    assert_eq!(logger.filter_precedence, FilterPrecedence::Config);
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("LOG_FILTER");
+        std::env::remove_var("LOG_FILTER_ENV_VAR");
+        std::env::remove_var("LOG_FILTER_PRECEDENCE");
+    }
+}
+
+#[test]
+#[serial]
+fn test_per_sink_filters() {
+    let logger = Logger::new("test-service")
+        .with_filter("my_app=debug")
+        .with_console_filter("info")
+        .with_otel_logs_filter("warn");
+    let layers = create_output_layers(&logger).unwrap();
+    assert_eq!(layers[0].max_level_hint(), Some(LevelFilter::INFO));
+    let otel_logs = init_sink_filter(&logger, logger.otel_logs_filter_directives().as_deref());
+    assert_eq!(otel_logs.unwrap().to_string(), "warn");
+
+    let logger = Logger::new("test-service")
+        .with_level(Level::WARN)
+        .with_filter_precedence(FilterPrecedence::Config);
+    let layers = create_output_layers(&logger).unwrap();
+    assert_eq!(layers[0].max_level_hint(), Some(LevelFilter::WARN));
+    let otel_logs = init_sink_filter(&logger, logger.otel_logs_filter_directives().as_deref());
+    assert_eq!(otel_logs.unwrap().to_string(), "warn");
+}
+
+#[test]
+#[serial]
+fn test_file_appender_filter_merge() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_appender = LoggerFileAppender {
+        enable: true,
+        dir: Some(dir.path().display().to_string()),
+        filename_prefix: Some("inherited".to_string()),
+        ..Default::default()
+    };
+    let logger = Logger::new("test-service")
+        .with_console_enabled(false)
+        .with_filter("hyper=warn,my_app=debug")
+        .with_filter_precedence(FilterPrecedence::Config);
+
+    let merged = file_appender.clone().merge_with_logger(&logger);
+    emit_through_output_layers(&logger.clone().with_file_appender(Some(merged)));
+    let written = read_sink_output(dir.path(), "inherited");
+    assert!(written.contains("my_app debug"));
+    assert!(written.contains("hyper warn"));
+    assert!(!written.contains("hyper debug"));
+
+    let file_appender = LoggerFileAppender {
+        level: Some(Level::ERROR),
+        filename_prefix: Some("own".to_string()),
+        ..file_appender
+    };
+    let merged = file_appender.merge_with_logger(&logger);
+    emit_through_output_layers(&logger.with_file_appender(Some(merged)));
+    let written = read_sink_output(dir.path(), "own");
+    assert!(written.contains("my_app error"));
+    assert!(!written.contains("my_app debug"));
+    assert!(!written.contains("hyper warn"));
+}
+
+#[test]
+#[serial]
+fn test_logger_with_named_sinks() {
+    let dir = tempfile::tempdir().unwrap();
+    let errors = LoggerFileAppender {
+        enable: true,
+        level: Some(Level::ERROR),
+        dir: Some(dir.path().display().to_string()),
+        ..Default::default()
+    };
+    let all = LoggerFileAppender {
+        filter: Some("my_app=debug".to_string()),
+        ..errors.clone()
+    };
+
+    let logger = Logger::new("test-service")
+        .with_console_enabled(false)
+        .with_sink("errors", errors)
+        .with_sink("all", all);
+    assert_eq!(logger.sinks.len(), 2);
+    emit_through_output_layers(&logger);
+
+    let errors = read_sink_output(dir.path(), "errors");
+    assert!(errors.contains("my_app error"));
+    assert!(!errors.contains("my_app info"));
+    assert!(!errors.contains("hyper warn"));
+
+    let all = read_sink_output(dir.path(), "all");
+    assert!(all.contains("my_app debug"));
+    assert!(all.contains("my_app error"));
+    assert!(!all.contains("hyper warn"));
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "env")]
+fn test_env_named_sinks_parsing() {
+    use super::env::init_logger_from_env;
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("LOG_FORMAT", "json");
+        std::env::set_var("LOG_SINKS__ERRORS__ENABLE", "true");
+        std::env::set_var("LOG_SINKS__ERRORS__LEVEL", "error");
+        std::env::set_var("LOG_SINKS__ERRORS__DIR", "/var/log/errors");
+        std::env::set_var("LOG_SINKS__ALL__ENABLE", "true");
+        std::env::set_var("LOG_SINKS__ALL__FILTER", "my_app=debug");
+        std::env::set_var("LOG_SINKS__ALL__ROTATION", "daily");
+    }
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert_eq!(logger.format, LogFormat::Json);
+    assert_eq!(logger.sinks.len(), 2);
+
+    let errors = &logger.sinks["errors"];
+    assert!(errors.enable);
+    assert_eq!(errors.level, Some(Level::ERROR));
+    assert_eq!(errors.dir_or_default(), "/var/log/errors");
+    assert_eq!(errors.format_or_default(), LogFormat::Json);
+
+    let all = &logger.sinks["all"];
+    assert_eq!(all.filter.as_deref(), Some("my_app=debug"));
+    assert_eq!(all.rotation, LogRollingRotation::Daily);
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("LOG_FORMAT");
+        std::env::remove_var("LOG_SINKS__ERRORS__ENABLE");
+        std::env::remove_var("LOG_SINKS__ERRORS__LEVEL");
+        std::env::remove_var("LOG_SINKS__ERRORS__DIR");
+        std::env::remove_var("LOG_SINKS__ALL__ENABLE");
+        std::env::remove_var("LOG_SINKS__ALL__FILTER");
+        std::env::remove_var("LOG_SINKS__ALL__ROTATION");
+    }
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "env")]
+fn test_env_sinks_follow_filter_env_var() {
+    use super::env::init_logger_from_env;
+
+    let dir = tempfile::tempdir().unwrap();
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("LOG_LEVEL", "error");
+        std::env::set_var("LOG_CONSOLE_ENABLED", "false");
+        std::env::set_var("LOG_FILTER_ENV_VAR", "TEST_SINK_LOG");
+        std::env::set_var("TEST_SINK_LOG", "my_app=debug");
+        std::env::set_var("LOG_FILE_ENABLE", "true");
+        std::env::set_var("LOG_FILE_DIR", dir.path());
+        std::env::set_var("LOG_FILE_FILENAME_PREFIX", "file");
+        std::env::set_var("LOG_SINKS__ALL__ENABLE", "true");
+        std::env::set_var("LOG_SINKS__ALL__DIR", dir.path());
+    }
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert_eq!(logger.file_appender.as_ref().unwrap().level, None);
+    assert_eq!(logger.sinks["all"].level, None);
+    emit_through_output_layers(&logger);
+    for prefix in ["file", "all"] {
+        let written = read_sink_output(dir.path(), prefix);
+        assert!(written.contains("my_app debug"), "{prefix}: {written}");
+        assert!(!written.contains("hyper warn"), "{prefix}: {written}");
+    }
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("LOG_FILTER_PRECEDENCE", "config");
+        std::env::set_var("LOG_FILE_FILENAME_PREFIX", "config");
+        std::env::set_var("LOG_SINKS__ALL__FILENAME_PREFIX", "config-all");
+    }
+    let logger = init_logger_from_env(None).unwrap();
+    emit_through_output_layers(&logger);
+    for prefix in ["config.", "config-all"] {
+        let written = read_sink_output(dir.path(), prefix);
+        assert!(written.contains("my_app error"), "{prefix}: {written}");
+        assert!(!written.contains("my_app debug"), "{prefix}: {written}");
+    }
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("LOG_LEVEL");
+        std::env::remove_var("LOG_CONSOLE_ENABLED");
+        std::env::remove_var("LOG_FILTER_ENV_VAR");
+        std::env::remove_var("TEST_SINK_LOG");
+        std::env::remove_var("LOG_FILTER_PRECEDENCE");
+        std::env::remove_var("LOG_FILE_ENABLE");
+        std::env::remove_var("LOG_FILE_DIR");
+        std::env::remove_var("LOG_FILE_FILENAME_PREFIX");
+        std::env::remove_var("LOG_SINKS__ALL__ENABLE");
+        std::env::remove_var("LOG_SINKS__ALL__DIR");
+        std::env::remove_var("LOG_SINKS__ALL__FILENAME_PREFIX");
+    }
+}
+
+#[test]
+#[serial]
+fn test_file_appender_rolling_policy() {
+    let file_appender: LoggerFileAppender = serde_json::from_str(
+        r#"{"enable": true, "rotation": "daily", "max_file_size": "10MB", "max_age_secs": 3600, "compress": true}"#,
+    )
+    .unwrap();
+
+    assert!(file_appender.uses_rolling_policy());
+    let policy = file_appender.rolling_policy();
+    assert_eq!(policy.period_secs, Some(24 * 60 * 60));
+    assert_eq!(policy.max_file_size, Some(10 * 1024 * 1024));
+    assert_eq!(policy.max_files, 5);
+    assert_eq!(policy.max_age, Some(std::time::Duration::from_secs(3600)));
+    assert!(policy.compress);
+
+    let file_appender: LoggerFileAppender = serde_json::from_str(r#"{"enable": true}"#).unwrap();
+    assert!(!file_appender.uses_rolling_policy());
+}
+
+#[test]
+#[serial]
+fn test_logger_with_syslog_sink() {
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(std::time::Duration::from_millis(200)))
+        .unwrap();
+    let syslog = LoggerSyslog {
+        enable: true,
+        transport: SyslogTransport::Udp,
+        address: Some(socket.local_addr().unwrap().to_string()),
+        level: Some(Level::WARN),
+        ..Default::default()
+    };
+    let logger = Logger::new("test-service")
+        .with_console_enabled(false)
+        .with_syslog(Some(syslog));
+    assert_eq!(create_output_layers(&logger).unwrap().len(), 1);
+    emit_through_output_layers(&logger);
+
+    let mut received = String::new();
+    let mut buf = [0u8; 2048];
+    while let Ok(len) = socket.recv(&mut buf) {
+        received.push_str(&String::from_utf8_lossy(&buf[..len]));
+        received.push('\n');
+    }
+    assert!(received.contains("hyper warn"));
+    assert!(received.contains("my_app error"));
+    assert!(!received.contains("my_app info"));
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "env")]
+fn test_env_syslog_parsing() {
+    use super::env::init_logger_from_env;
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("LOG_SYSLOG__ENABLE", "true");
+        std::env::set_var("LOG_SYSLOG__TRANSPORT", "tcp");
+        std::env::set_var("LOG_SYSLOG__FACILITY", "local3");
+        std::env::set_var("LOG_JOURNALD__ENABLE", "true");
+        std::env::set_var("LOG_JOURNALD__SYSLOG_IDENTIFIER", "my-app");
+    }
+
+    let logger = init_logger_from_env(None).unwrap();
+    let syslog = logger.syslog.expect("syslog should be Some");
+    assert!(syslog.enable);
+    assert_eq!(syslog.transport, SyslogTransport::Tcp);
+    assert_eq!(syslog.facility, SyslogFacility::Local3);
+    assert_eq!(syslog.address_or_default(), "127.0.0.1:514");
+    let journald = logger.journald.expect("journald should be Some");
+    assert_eq!(journald.syslog_identifier.as_deref(), Some("my-app"));
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("LOG_SYSLOG__ENABLE");
+        std::env::remove_var("LOG_SYSLOG__TRANSPORT");
+        std::env::remove_var("LOG_SYSLOG__FACILITY");
+        std::env::remove_var("LOG_JOURNALD__ENABLE");
+        std::env::remove_var("LOG_JOURNALD__SYSLOG_IDENTIFIER");
+    }
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "env")]
+fn test_env_trace_ids_parsing() {
+    use super::env::init_logger_from_env;
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert_eq!(logger.trace_ids, LoggerTraceIds::default());
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("LOG_TRACE_IDS__TRACE_ID_KEY", "traceId");
+        std::env::set_var("LOG_TRACE_IDS__SPAN_ID_KEY", "spanId");
+    }
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert!(logger.trace_ids.enable);
+    assert_eq!(logger.trace_ids.trace_id_key, "traceId");
+    assert_eq!(logger.trace_ids.span_id_key, "spanId");
+    assert_eq!(logger.trace_ids.sampled_key, "trace_sampled");
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("LOG_TRACE_IDS__TRACE_ID_KEY");
+        std::env::remove_var("LOG_TRACE_IDS__SPAN_ID_KEY");
+    }
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "env")]
+fn test_env_json_layout_parsing() {
+    use super::env::init_logger_from_env;
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("LOG_JSON__MESSAGE_KEY", "msg");
+        std::env::set_var(
+            "LOG_JSON__STATIC_FIELDS",
+            "environment=production,region=eu",
+        );
+        std::env::set_var("LOG_JSON__SPANS", "current");
+        std::env::set_var("LOG_JSON__MAX_DEPTH", "2");
+        std::env::set_var("LOG_FILE_ENABLE", "true");
+    }
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert_eq!(logger.json.message_key, "msg");
+    assert_eq!(logger.json.level_key, "level");
+    assert_eq!(logger.json.static_fields["environment"], "production");
+    assert_eq!(logger.json.static_fields["region"], "eu");
+    assert_eq!(logger.json.spans, JsonSpans::Current);
+    assert_eq!(logger.json.max_depth, Some(2));
+    // File appenders inherit the Logger's layout
+    let file = logger.file_appender.expect("file appender should be Some");
+    assert_eq!(file.json, Some(logger.json.clone()));
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("LOG_JSON__MESSAGE_KEY");
+        std::env::remove_var("LOG_JSON__STATIC_FIELDS");
+        std::env::remove_var("LOG_JSON__SPANS");
+        std::env::remove_var("LOG_JSON__MAX_DEPTH");
+        std::env::remove_var("LOG_FILE_ENABLE");
+    }
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "env")]
+fn test_env_timestamp_parsing() {
+    use super::env::init_logger_from_env;
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("LOG_TIMESTAMP__FORMAT", "none");
+        std::env::set_var("LOG_FILE_ENABLE", "true");
+        std::env::set_var("LOG_FILE_TIMESTAMP__TIMEZONE", "+02:00");
+        std::env::set_var("LOG_FILE_TIMESTAMP__PRECISION", "millis");
+    }
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert_eq!(logger.timestamp.format, TimestampFormat::None);
+    let file = logger.file_appender.expect("file appender should be Some");
+    let timestamp = file.timestamp.expect("file timestamp should be Some");
+    assert_eq!(timestamp.format, TimestampFormat::Rfc3339);
+    assert_eq!(timestamp.timezone.as_deref(), Some("+02:00"));
+    assert_eq!(timestamp.precision, TimestampPrecision::Millis);
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("LOG_TIMESTAMP__FORMAT");
+        std::env::remove_var("LOG_FILE_ENABLE");
+        std::env::remove_var("LOG_FILE_TIMESTAMP__TIMEZONE");
+        std::env::remove_var("LOG_FILE_TIMESTAMP__PRECISION");
+    }
+}
+
+#[test]
+fn test_invalid_timestamp_timezone() {
+    let logger = Logger::new("test-service")
+        .with_timestamp(LoggerTimestamp::default().with_timezone("somewhere"));
+    assert!(create_output_layers(&logger).is_err());
+}
+
+#[test]
+fn test_journald_refused_with_redaction() {
+    let logger = Logger::new("test-service")
+        .with_console_enabled(false)
+        .with_journald(Some(LoggerJournald {
+            enable: true,
+            ..Default::default()
+        }))
+        .with_redaction(LoggerRedaction::enabled());
+    let error = create_output_layers(&logger).err().unwrap();
+    assert!(
+        error
+            .to_string()
+            .contains("journald output cannot be redacted")
+    );
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "env")]
+fn test_env_redaction_parsing() {
+    use super::env::init_logger_from_env;
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert_eq!(logger.redaction, LoggerRedaction::default());
+    assert!(!logger.redaction.enable);
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("LOG_REDACTION__ENABLE", "true");
+        std::env::set_var("LOG_REDACTION__KEYS", "password, x-session");
+        std::env::set_var("LOG_REDACTION__PATTERNS", r"\b\d{13,19}\b");
+        std::env::set_var("LOG_REDACTION__MODE", "hash");
+        std::env::set_var("LOG_REDACTION__HASH_SECRET", "s3cret");
+    }
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert!(logger.redaction.enable);
+    assert_eq!(logger.redaction.keys, vec!["password", "x-session"]);
+    assert_eq!(logger.redaction.patterns, vec![r"\b\d{13,19}\b"]);
+    assert_eq!(logger.redaction.mode, RedactMode::Hash);
+    assert_eq!(logger.redaction.mask, "[REDACTED]");
+    assert_eq!(logger.redaction.hash_secret.as_deref(), Some("s3cret"));
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("LOG_REDACTION__ENABLE");
+        std::env::remove_var("LOG_REDACTION__KEYS");
+        std::env::remove_var("LOG_REDACTION__PATTERNS");
+        std::env::remove_var("LOG_REDACTION__MODE");
+        std::env::remove_var("LOG_REDACTION__HASH_SECRET");
+    }
+}
+
+#[test]
+fn test_invalid_redaction_pattern() {
+    let logger = Logger::new("test-service")
+        .with_redaction(LoggerRedaction::enabled().with_pattern("[unclosed"));
+    assert!(create_output_layers(&logger).is_err());
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "env")]
+fn test_env_rate_limit_parsing() {
+    use super::env::init_logger_from_env;
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert_eq!(logger.rate_limit, LoggerRateLimit::default());
+    assert!(RateLimitLayer::from_config(&logger.rate_limit).is_none());
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("LOG_RATE_LIMIT__ENABLE", "true");
+        std::env::set_var("LOG_RATE_LIMIT__KEY", "message");
+        std::env::set_var("LOG_RATE_LIMIT__MAX_EVENTS", "5");
+        std::env::set_var("LOG_RATE_LIMIT__WINDOW_SECS", "30");
+    }
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert_eq!(
+        logger.rate_limit,
+        LoggerRateLimit::enabled()
+            .with_key(RateLimitKey::Message)
+            .with_max_events(5)
+            .with_window_secs(30)
+    );
+    assert!(RateLimitLayer::from_config(&logger.rate_limit).is_some());
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("LOG_RATE_LIMIT__ENABLE");
+        std::env::remove_var("LOG_RATE_LIMIT__KEY");
+        std::env::remove_var("LOG_RATE_LIMIT__MAX_EVENTS");
+        std::env::remove_var("LOG_RATE_LIMIT__WINDOW_SECS");
+    }
+}
+
+#[test]
+#[serial]
+fn test_sampled_only_fallbacks() {
+    let logger = Logger::new("test-service").with_sampled_only(LoggerSampledOnly::enabled());
+    assert!(logger.console_sampled_only_or_default().enable);
+    assert!(logger.otel_logs_sampled_only_or_default().enable);
+
+    let logger = logger.with_console_sampled_only(LoggerSampledOnly::disabled());
+    assert!(!logger.console_sampled_only_or_default().enable);
+    assert!(logger.otel_logs_sampled_only_or_default().enable);
+
+    let syslog = LoggerSyslog::default();
+    assert!(syslog.sampled_only_or_default(&logger).enable);
+    let file_appender = LoggerFileAppender::default().merge_with_logger(&logger);
+    assert_eq!(
+        file_appender.sampled_only,
+        Some(LoggerSampledOnly::enabled())
+    );
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "env")]
+fn test_env_sampled_only_parsing() {
+    use super::env::init_logger_from_env;
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert_eq!(logger.sampled_only, LoggerSampledOnly::default());
+    assert!(SampledOnlyFilter::from_config(&logger.sampled_only).is_none());
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::set_var("LOG_SAMPLED_ONLY__ENABLE", "true");
+        std::env::set_var("LOG_SAMPLED_ONLY__ALWAYS_LEVEL", "error");
+        std::env::set_var("LOG_OTEL_LOGS_SAMPLED_ONLY__ENABLE", "false");
+    }
+
+    let logger = init_logger_from_env(None).unwrap();
+    assert_eq!(
+        logger.sampled_only,
+        LoggerSampledOnly::enabled().with_always_level(Level::ERROR)
+    );
+    assert!(logger.console_sampled_only_or_default().enable);
+    assert!(!logger.otel_logs_sampled_only_or_default().enable);
+
+    #[allow(unsafe_code)]
+    unsafe {
+        std::env::remove_var("LOG_SAMPLED_ONLY__ENABLE");
+        std::env::remove_var("LOG_SAMPLED_ONLY__ALWAYS_LEVEL");
+        std::env::remove_var("LOG_OTEL_LOGS_SAMPLED_ONLY__ENABLE");
+    }
+}
@@ -1,19 +1,17 @@
 use anyhow::Result;
 use axum::{
-    Json, Router,
+    Extension, Json, Router,
     extract::{Path, State},
     routing::{get, post},
 };
-use axum_otel::{AxumOtelOnFailure, AxumOtelOnResponse, AxumOtelSpanCreator, Level};
+use axum_otel::{Level, OtelLayer};
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_otel::{OtelMiddleware, RequestId};
 use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
-use reqwest_tracing::TracingMiddleware;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tower::ServiceBuilder;
-use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
-use tower_http::trace::TraceLayer;
+use tower_http::request_id;
 use tracing_otel_extra::Logger;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -59,16 +57,24 @@ async fn get_article(
     }
 }
 
-#[tracing::instrument(skip(state))]
+#[tracing::instrument(skip(state, request_id))]
 async fn get_articles_by_author(
     State(state): State<AppState>,
+    Extension(request_id): Extension<request_id::RequestId>,
     Path(author_id): Path<u64>,
 ) -> Result<Json<Vec<Article>>, (axum::http::StatusCode, String)> {
     let base_url =
         std::env::var("USERS_SERVICE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
     // First verify that the author exists by calling the users service
     let user_url = format!("{base_url}/users/{author_id}");
-    match state.http_client.get(&user_url).send().await {
+    let response = state
+        .http_client
+        .get(&user_url)
+        // Forward the request id to the users service
+        .with_extension(RequestId::from(request_id.header_value().clone()))
+        .send()
+        .await;
+    match response {
         Ok(response) => {
             if !response.status().is_success() {
                 return Err((
@@ -118,12 +124,15 @@ async fn main() -> Result<()> {
     let logger = Logger::from_env(Some("LOG"))?;
     let _guard = logger.init()?;
 
+    let otel = OtelMiddleware::default().level(Level::INFO);
     let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
     let client: ClientWithMiddleware = ClientBuilder::new(reqwest::Client::new())
-        // Trace HTTP requests. See the tracing crate to make use of these traces.
-        .with(TracingMiddleware::default())
+        // Trace HTTP requests with one span around the retries.
+        .with(otel.clone())
         // Retry failed requests.
         .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+        // Record each retry as an event on that span.
+        .with(otel)
         .build();
 
     let state = AppState {
@@ -136,17 +145,7 @@ async fn main() -> Result<()> {
         .route("/articles/{id}", get(get_article))
         .route("/articles/author/{author_id}", get(get_articles_by_author))
         .route("/articles", post(create_article))
-        .layer(
-            ServiceBuilder::new()
-                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
-                .layer(
-                    TraceLayer::new_for_http()
-                        .make_span_with(AxumOtelSpanCreator::new().level(Level::INFO))
-                        .on_response(AxumOtelOnResponse::new().level(Level::INFO))
-                        .on_failure(AxumOtelOnFailure::new()),
-                )
-                .layer(PropagateRequestIdLayer::x_request_id()),
-        )
+        .layer(OtelLayer::new().level(Level::INFO))
         .with_state(state);
 
     let listener = TcpListener::bind("0.0.0.0:8082").await?;
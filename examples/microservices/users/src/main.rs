@@ -4,13 +4,10 @@ use axum::{
     extract::{Path, State},
     routing::{get, post},
 };
-use axum_otel::{AxumOtelOnFailure, AxumOtelOnResponse, AxumOtelSpanCreator};
+use axum_otel::OtelLayer;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tower::ServiceBuilder;
-use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
-use tower_http::trace::TraceLayer;
 use tracing::Level;
 use tracing_otel_extra::Logger;
 
@@ -87,17 +84,7 @@ async fn main() -> Result<()> {
         .route("/users", get(get_users))
         .route("/users/{id}", get(get_user))
         .route("/users", post(create_user))
-        .layer(
-            ServiceBuilder::new()
-                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
-                .layer(
-                    TraceLayer::new_for_http()
-                        .make_span_with(AxumOtelSpanCreator::new().level(Level::INFO))
-                        .on_response(AxumOtelOnResponse::new().level(Level::INFO))
-                        .on_failure(AxumOtelOnFailure::new().level(Level::ERROR)),
-                )
-                .layer(PropagateRequestIdLayer::x_request_id()),
-        )
+        .layer(OtelLayer::new().level(Level::INFO))
         .route("/health", get(health)) // without request id, the span will not be created
         .with_state(state);
 
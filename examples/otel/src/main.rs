@@ -1,12 +1,9 @@
 use anyhow::Result;
 use axum::extract::Query;
 use axum::{Router, routing::get};
-use axum_otel::{AxumOtelOnFailure, AxumOtelOnResponse, AxumOtelSpanCreator, Level};
+use axum_otel::{Level, OtelLayer};
 use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
-use tower::ServiceBuilder;
-use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
-use tower_http::trace::TraceLayer;
 use tracing::info;
 use tracing_otel_extra::Logger;
 
@@ -39,17 +36,7 @@ async fn main() -> Result<()> {
     // Setup Axum router and server
     let app = Router::new()
         .route("/hello", get(hello))
-        .layer(
-            ServiceBuilder::new()
-                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
-                .layer(
-                    TraceLayer::new_for_http()
-                        .make_span_with(AxumOtelSpanCreator::new().level(Level::INFO))
-                        .on_response(AxumOtelOnResponse::new().level(Level::INFO))
-                        .on_failure(AxumOtelOnFailure::new().level(Level::ERROR)),
-                )
-                .layer(PropagateRequestIdLayer::x_request_id()),
-        )
+        .layer(OtelLayer::new().level(Level::INFO))
         .route("/health", get(health)); // without request id, the span will not be created
 
     let listener = TcpListener::bind("0.0.0.0:8080").await?;